@@ -0,0 +1,526 @@
+use crate::error::JwkError;
+use crate::jwk::{Jwk, Jwks};
+use std::fmt;
+use std::future::poll_fn;
+use std::mem;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Poll, Waker};
+use std::time::{Duration, SystemTime};
+use transport::{AsyncHttpTransport, HttpRequest, HttpResponse, HttpTransport, MAX_DOCUMENT_SIZE};
+
+/// # Sending the requests of a JWKS client
+///
+/// This module defines how a [`JwksClient`] or an [`AsyncJwksClient`] talks to the server, so
+/// that the caching logic runs on any HTTP stack.
+///
+/// ## Key functionalities:
+/// - Plug in a blocking or an asynchronous HTTP client through `HttpTransport` and `AsyncHttpTransport`.
+/// - Answer requests from canned responses in tests with `StubTransport`.
+/// - Send requests with `ureq` through `UreqTransport` (requires the `client` feature).
+pub mod transport;
+
+/// # A caching client for a remote JWKS.
+///
+/// The client downloads the JWKS published at a URL and caches it:
+/// - for the `max-age` of the response's `Cache-Control` header, or
+///   [`set_default_max_age`](JwksClient::set_default_max_age) when there is none, capped by
+///   [`set_max_age_limit`](JwksClient::set_max_age_limit); `no-cache` and `no-store` make
+///   the keys expire immediately,
+/// - revalidating expired keys with `If-None-Match` when the server sent an `ETag`.
+///
+/// When a token names a `kid` that is not in the cache, [`JwksClient::find_key`] downloads the
+/// JWKS again, so that keys published by a rotation are picked up before the cache expires.
+/// To keep tokens with made-up `kid`s from turning into requests to the key server, the client
+/// never contacts the server more than once per
+/// [`set_min_refresh_interval`](JwksClient::set_min_refresh_interval).
+///
+/// When the server cannot be reached, or answers with an error or an invalid document, the
+/// expired keys are still served for [`set_max_stale`](JwksClient::set_max_stale) after they
+/// expire.
+///
+/// Documents larger than [`MAX_DOCUMENT_SIZE`] are rejected, whatever the transport. Keys that
+/// fail [`Jwk::validate`] are dropped, and so are private members: a remote JWKS is only used
+/// to verify signatures. The client can be shared between threads; concurrent
+/// callers that find the cache expired wait for a single download.
+///
+/// Requests are sent through an [`HttpTransport`]. [`JwksClient::new`] uses `ureq`, and
+/// requires the `client` feature; [`JwksClient::with_transport`] accepts any transport. For
+/// asynchronous code, use [`AsyncJwksClient`].
+///
+/// ## Example
+/// ```rust,no_run
+/// # #[cfg(feature = "client")] {
+/// use std::time::Duration;
+/// use jwk_kit::client::JwksClient;
+///
+/// let mut client = JwksClient::new("https://auth.example.com/.well-known/jwks.json");
+/// client.set_max_stale(Duration::from_secs(6 * 3600));
+///
+/// let key = client.find_key("2024-06-key").unwrap();
+/// # }
+/// ```
+pub struct JwksClient {
+    core: Core,
+    transport: Arc<dyn HttpTransport>,
+    gate: Mutex<()>,
+}
+
+impl fmt::Debug for JwksClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwksClient").field("core", &self.core).finish_non_exhaustive()
+    }
+}
+
+impl JwksClient {
+    /// Creates a client for the JWKS at `url`, sending requests with `ureq`. Nothing is
+    /// downloaded until keys are requested.
+    ///
+    /// The defaults are a minimum refresh interval of 30 seconds, a default `max-age` of
+    /// 10 minutes, a `max-age` limit of 24 hours, stale keys served for up to 1 hour, and a
+    /// 10 second timeout per request. For another timeout, pass a
+    /// [`UreqTransport::with_timeout`](transport::UreqTransport::with_timeout) to
+    /// [`JwksClient::with_transport`].
+    ///
+    /// Requires the `client` feature.
+    #[cfg(feature = "client")]
+    pub fn new(url: &str) -> Self {
+        Self::with_transport(url, Arc::new(transport::UreqTransport::new()))
+    }
+
+    /// Creates a client for the JWKS at `url`, sending requests through `transport`, with the
+    /// defaults described in [`JwksClient::new`].
+    pub fn with_transport(url: &str, transport: Arc<dyn HttpTransport>) -> Self {
+        Self { core: Core::new(url), transport, gate: Mutex::new(()) }
+    }
+
+    /// Sets the minimum time between two requests to the server, whatever triggers them.
+    pub fn set_min_refresh_interval(&mut self, value: Duration) -> &mut Self {
+        self.core.min_refresh_interval = value;
+        self
+    }
+
+    /// Sets how long keys are cached when the response has no `Cache-Control: max-age`.
+    pub fn set_default_max_age(&mut self, value: Duration) -> &mut Self {
+        self.core.default_max_age = value;
+        self
+    }
+
+    /// Sets the longest time keys are cached, whatever the server's `max-age`. `Duration::MAX`
+    /// lets the server's `max-age` through unbounded.
+    pub fn set_max_age_limit(&mut self, value: Duration) -> &mut Self {
+        self.core.max_age_limit = value;
+        self
+    }
+
+    /// Sets how long expired keys are still served when they cannot be downloaded again.
+    /// `Duration::MAX` serves them for as long as the server cannot be reached.
+    pub fn set_max_stale(&mut self, value: Duration) -> &mut Self {
+        self.core.max_stale = value;
+        self
+    }
+
+    /// Returns the URL the keys are downloaded from.
+    pub fn url(&self) -> &str {
+        &self.core.url
+    }
+
+    /// Returns the current keys, downloading them if the cache has expired.
+    ///
+    /// # Errors
+    /// - `JwkError::JwksFetch` if the keys cannot be downloaded and no cached keys are usable.
+    pub fn keys(&self) -> Result<Arc<Jwks>, JwkError> {
+        self.keys_at(SystemTime::now())
+    }
+
+    /// Same as [`JwksClient::keys`], at the given instant.
+    ///
+    /// # Errors
+    /// The same errors as [`JwksClient::keys`].
+    pub fn keys_at(&self, now: SystemTime) -> Result<Arc<Jwks>, JwkError> {
+        if let Some(jwks) = self.core.fresh(now) {
+            return Ok(jwks);
+        }
+
+        let _gate = self.gate.lock().unwrap_or_else(|e| e.into_inner());
+        if self.core.fresh(now).is_none() && self.core.may_fetch(now) {
+            // On failure, the cached keys or the error are returned below.
+            let _ = self.fetch(now);
+        }
+        self.core.keys(now)
+    }
+
+    /// Returns the key with the given `kid` that may be used now (see [`Jwk::is_valid_at`]).
+    ///
+    /// If no such key is cached, the JWKS is downloaded again, unless the server was contacted
+    /// less than the minimum refresh interval ago.
+    ///
+    /// # Errors
+    /// - `JwkError::UnknownKeyId` if the JWKS has no such key.
+    /// - `JwkError::JwksFetch` if the keys cannot be downloaded and no cached keys are usable.
+    pub fn find_key(&self, kid: &str) -> Result<Jwk, JwkError> {
+        self.find_key_at(kid, SystemTime::now())
+    }
+
+    /// Same as [`JwksClient::find_key`], at the given instant.
+    ///
+    /// # Errors
+    /// The same errors as [`JwksClient::find_key`].
+    pub fn find_key_at(&self, kid: &str, now: SystemTime) -> Result<Jwk, JwkError> {
+        let jwks = self.keys_at(now)?;
+        if let Some(jwk) = jwks.find_by_kid_at(kid, now) {
+            return Ok(jwk.clone());
+        }
+
+        let _gate = self.gate.lock().unwrap_or_else(|e| e.into_inner());
+        let jwks = match self.core.replaced(now, &jwks) {
+            Some(jwks) => jwks,
+            None if self.core.may_fetch(now) => self.fetch(now).unwrap_or(jwks),
+            None => jwks,
+        };
+        find(&jwks, kid, now)
+    }
+
+    /// Downloads the keys now, ignoring the cache and the minimum refresh interval.
+    ///
+    /// # Errors
+    /// - `JwkError::JwksFetch` if the request fails, the server answers with an error status
+    ///   or the response is not a JWKS, or is larger than [`MAX_DOCUMENT_SIZE`]. The cached
+    ///   keys are kept.
+    pub fn refresh(&self) -> Result<Arc<Jwks>, JwkError> {
+        self.refresh_at(SystemTime::now())
+    }
+
+    /// Same as [`JwksClient::refresh`], at the given instant.
+    ///
+    /// # Errors
+    /// The same errors as [`JwksClient::refresh`].
+    pub fn refresh_at(&self, now: SystemTime) -> Result<Arc<Jwks>, JwkError> {
+        let _gate = self.gate.lock().unwrap_or_else(|e| e.into_inner());
+        self.fetch(now)
+    }
+
+    fn fetch(&self, now: SystemTime) -> Result<Arc<Jwks>, JwkError> {
+        let request = self.core.begin(now);
+        self.core.complete(now, self.transport.get(&request))
+    }
+}
+
+/// # A caching client for a remote JWKS, for asynchronous code.
+///
+/// It behaves like [`JwksClient`], with `async` methods, and sends its requests through an
+/// [`AsyncHttpTransport`]. It does not depend on a particular runtime: concurrent callers that
+/// find the cache expired wait for a single download without blocking their thread.
+///
+/// ## Example
+/// ```rust
+/// use std::sync::Arc;
+/// use jwk_kit::client::AsyncJwksClient;
+/// use jwk_kit::client::transport::{HttpResponse, StubTransport};
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let transport = Arc::new(StubTransport::new());
+/// transport.push_response(HttpResponse::new(200, r#"{"keys":[]}"#));
+///
+/// let client = AsyncJwksClient::with_transport("https://auth.example.com/jwks", transport);
+/// assert!(client.keys().await.unwrap().keys.is_empty());
+/// # });
+/// ```
+pub struct AsyncJwksClient<T> {
+    core: Core,
+    transport: Arc<T>,
+    gate: FetchGate,
+}
+
+impl<T> fmt::Debug for AsyncJwksClient<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncJwksClient").field("core", &self.core).finish_non_exhaustive()
+    }
+}
+
+impl<T: AsyncHttpTransport> AsyncJwksClient<T> {
+    /// Creates a client for the JWKS at `url`, sending requests through `transport`, with the
+    /// defaults described in [`JwksClient::new`].
+    pub fn with_transport(url: &str, transport: Arc<T>) -> Self {
+        Self { core: Core::new(url), transport, gate: FetchGate::default() }
+    }
+
+    /// Same as [`JwksClient::set_min_refresh_interval`].
+    pub fn set_min_refresh_interval(&mut self, value: Duration) -> &mut Self {
+        self.core.min_refresh_interval = value;
+        self
+    }
+
+    /// Same as [`JwksClient::set_default_max_age`].
+    pub fn set_default_max_age(&mut self, value: Duration) -> &mut Self {
+        self.core.default_max_age = value;
+        self
+    }
+
+    /// Same as [`JwksClient::set_max_age_limit`].
+    pub fn set_max_age_limit(&mut self, value: Duration) -> &mut Self {
+        self.core.max_age_limit = value;
+        self
+    }
+
+    /// Same as [`JwksClient::set_max_stale`].
+    pub fn set_max_stale(&mut self, value: Duration) -> &mut Self {
+        self.core.max_stale = value;
+        self
+    }
+
+    /// Returns the URL the keys are downloaded from.
+    pub fn url(&self) -> &str {
+        &self.core.url
+    }
+
+    /// Same as [`JwksClient::keys`].
+    ///
+    /// # Errors
+    /// The same errors as [`JwksClient::keys`].
+    pub async fn keys(&self) -> Result<Arc<Jwks>, JwkError> {
+        self.keys_at(SystemTime::now()).await
+    }
+
+    /// Same as [`JwksClient::keys_at`].
+    ///
+    /// # Errors
+    /// The same errors as [`JwksClient::keys`].
+    pub async fn keys_at(&self, now: SystemTime) -> Result<Arc<Jwks>, JwkError> {
+        if let Some(jwks) = self.core.fresh(now) {
+            return Ok(jwks);
+        }
+
+        let _gate = self.gate.lock().await;
+        if self.core.fresh(now).is_none() && self.core.may_fetch(now) {
+            let _ = self.fetch(now).await;
+        }
+        self.core.keys(now)
+    }
+
+    /// Same as [`JwksClient::find_key`].
+    ///
+    /// # Errors
+    /// The same errors as [`JwksClient::find_key`].
+    pub async fn find_key(&self, kid: &str) -> Result<Jwk, JwkError> {
+        self.find_key_at(kid, SystemTime::now()).await
+    }
+
+    /// Same as [`JwksClient::find_key_at`].
+    ///
+    /// # Errors
+    /// The same errors as [`JwksClient::find_key`].
+    pub async fn find_key_at(&self, kid: &str, now: SystemTime) -> Result<Jwk, JwkError> {
+        let jwks = self.keys_at(now).await?;
+        if let Some(jwk) = jwks.find_by_kid_at(kid, now) {
+            return Ok(jwk.clone());
+        }
+
+        let _gate = self.gate.lock().await;
+        let jwks = match self.core.replaced(now, &jwks) {
+            Some(jwks) => jwks,
+            None if self.core.may_fetch(now) => self.fetch(now).await.unwrap_or(jwks),
+            None => jwks,
+        };
+        find(&jwks, kid, now)
+    }
+
+    /// Same as [`JwksClient::refresh`].
+    ///
+    /// # Errors
+    /// The same errors as [`JwksClient::refresh`].
+    pub async fn refresh(&self) -> Result<Arc<Jwks>, JwkError> {
+        self.refresh_at(SystemTime::now()).await
+    }
+
+    /// Same as [`JwksClient::refresh_at`].
+    ///
+    /// # Errors
+    /// The same errors as [`JwksClient::refresh`].
+    pub async fn refresh_at(&self, now: SystemTime) -> Result<Arc<Jwks>, JwkError> {
+        let _gate = self.gate.lock().await;
+        self.fetch(now).await
+    }
+
+    async fn fetch(&self, now: SystemTime) -> Result<Arc<Jwks>, JwkError> {
+        let request = self.core.begin(now);
+        let response = self.transport.get(&request).await;
+        self.core.complete(now, response)
+    }
+}
+
+/// The cache and policy shared by both clients, independent of how requests are sent.
+#[derive(Debug)]
+struct Core {
+    url: String,
+    min_refresh_interval: Duration,
+    default_max_age: Duration,
+    max_age_limit: Duration,
+    max_stale: Duration,
+    cache: Mutex<Cache>,
+}
+
+#[derive(Debug, Default)]
+struct Cache {
+    jwks: Option<Arc<Jwks>>,
+    etag: Option<String>,
+    /// When `jwks` expires, or `None` if it never does.
+    expires_at: Option<SystemTime>,
+    last_attempt: Option<SystemTime>,
+    last_error: Option<JwkError>,
+}
+
+impl Core {
+    fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            min_refresh_interval: Duration::from_secs(30),
+            default_max_age: Duration::from_secs(10 * 60),
+            max_age_limit: Duration::from_secs(24 * 60 * 60),
+            max_stale: Duration::from_secs(60 * 60),
+            cache: Mutex::new(Cache::default()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Cache> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn fresh(&self, now: SystemTime) -> Option<Arc<Jwks>> {
+        let cache = self.lock();
+        cache.jwks.clone().filter(|_| cache.expires_at.is_none_or(|expires_at| now < expires_at))
+    }
+
+    fn usable(&self, now: SystemTime) -> Option<Arc<Jwks>> {
+        let cache = self.lock();
+        let stale_for = |expires_at| now.duration_since(expires_at).unwrap_or_default();
+        cache.jwks.clone().filter(|_| cache.expires_at.is_none_or(|expires_at| stale_for(expires_at) < self.max_stale))
+    }
+
+    /// Returns the usable keys if they are no longer `seen`, i.e. were downloaded meanwhile.
+    fn replaced(&self, now: SystemTime, seen: &Arc<Jwks>) -> Option<Arc<Jwks>> {
+        self.usable(now).filter(|current| !Arc::ptr_eq(current, seen))
+    }
+
+    fn may_fetch(&self, now: SystemTime) -> bool {
+        self.lock()
+            .last_attempt
+            .is_none_or(|last| now.duration_since(last).is_ok_and(|elapsed| elapsed >= self.min_refresh_interval))
+    }
+
+    fn keys(&self, now: SystemTime) -> Result<Arc<Jwks>, JwkError> {
+        self.fresh(now).or_else(|| self.usable(now)).ok_or_else(|| {
+            self.lock().last_error.clone().unwrap_or_else(|| JwkError::JwksFetch("no keys have been downloaded".into()))
+        })
+    }
+
+    /// Records a download attempt at `now`, and returns the request to send.
+    fn begin(&self, now: SystemTime) -> HttpRequest {
+        let mut cache = self.lock();
+        cache.last_attempt = Some(now);
+
+        let mut headers = vec![("Accept".to_string(), "application/jwk-set+json, application/json".to_string())];
+        if let (Some(_), Some(etag)) = (&cache.jwks, &cache.etag) {
+            headers.push(("If-None-Match".to_string(), etag.clone()));
+        }
+        HttpRequest { url: self.url.clone(), headers }
+    }
+
+    /// Updates the cache with the outcome of the request sent at `now`.
+    fn complete(&self, now: SystemTime, response: Result<HttpResponse, JwkError>) -> Result<Arc<Jwks>, JwkError> {
+        let mut cache = self.lock();
+
+        let result = response.and_then(|response| {
+            // A lifetime too long to represent never runs out.
+            let expires_at = now.checked_add(max_age(&response).unwrap_or(self.default_max_age).min(self.max_age_limit));
+            match response.status {
+                304 => {
+                    let jwks = cache.jwks.clone().ok_or_else(|| JwkError::JwksFetch("unexpected 304 Not Modified".into()))?;
+                    cache.expires_at = expires_at;
+                    Ok(jwks)
+                }
+                200 if response.body.len() > MAX_DOCUMENT_SIZE => {
+                    Err(JwkError::JwksFetch(format!("the JWKS is larger than {} bytes", MAX_DOCUMENT_SIZE)))
+                }
+                200 => {
+                    let jwks = Arc::new(parse(&response.body)?);
+                    cache.jwks = Some(jwks.clone());
+                    cache.etag = response.header("ETag").map(str::to_string);
+                    cache.expires_at = expires_at;
+                    Ok(jwks)
+                }
+                status => Err(JwkError::JwksFetch(format!("{} answered {}", self.url, status))),
+            }
+        });
+
+        cache.last_error = result.as_ref().err().cloned();
+        result
+    }
+}
+
+/// A lock that can be held across `.await` points, whatever the runtime.
+#[derive(Debug, Default)]
+struct FetchGate {
+    state: Mutex<(bool, Vec<Waker>)>,
+}
+
+struct FetchGuard<'a> {
+    gate: &'a FetchGate,
+}
+
+impl FetchGate {
+    async fn lock(&self) -> FetchGuard<'_> {
+        poll_fn(|cx| {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            if state.0 {
+                state.1.push(cx.waker().clone());
+                Poll::Pending
+            } else {
+                state.0 = true;
+                Poll::Ready(FetchGuard { gate: self })
+            }
+        })
+        .await
+    }
+}
+
+impl Drop for FetchGuard<'_> {
+    fn drop(&mut self) {
+        let waiters = {
+            let mut state = self.gate.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.0 = false;
+            mem::take(&mut state.1)
+        };
+        waiters.into_iter().for_each(Waker::wake);
+    }
+}
+
+fn find(jwks: &Jwks, kid: &str, now: SystemTime) -> Result<Jwk, JwkError> {
+    jwks.find_by_kid_at(kid, now).cloned().ok_or_else(|| JwkError::UnknownKeyId(kid.to_string()))
+}
+
+/// Returns the freshness lifetime of a response: its `Cache-Control: max-age` minus its `Age`.
+fn max_age(response: &HttpResponse) -> Option<Duration> {
+    let directives = response
+        .headers_named("Cache-Control")
+        .flat_map(|value| value.split(','))
+        .map(|directive| directive.trim().to_ascii_lowercase());
+
+    let mut max_age = None;
+    for directive in directives {
+        match directive.split_once('=') {
+            _ if directive == "no-cache" || directive == "no-store" => return Some(Duration::ZERO),
+            Some(("max-age", seconds)) => max_age = seconds.trim_matches('"').parse().ok(),
+            _ => {}
+        }
+    }
+
+    let age = response.header("Age").and_then(|value| value.trim().parse().ok()).unwrap_or(0);
+    max_age.map(|seconds: u64| Duration::from_secs(seconds.saturating_sub(age)))
+}
+
+/// Parses a downloaded JWKS, keeping the public members of the keys that pass validation.
+fn parse(body: &[u8]) -> Result<Jwks, JwkError> {
+    let jwks: Jwks = serde_json::from_slice(body).map_err(|e| JwkError::JwksFetch(format!("invalid JWKS: {}", e)))?;
+    let keys = jwks.keys.into_iter().filter(|jwk| jwk.validate().is_ok()).map(|jwk| jwk.to_public()).collect();
+    Ok(Jwks { keys })
+}
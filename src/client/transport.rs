@@ -0,0 +1,228 @@
+use crate::error::JwkError;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Mutex;
+#[cfg(feature = "client")]
+use std::time::Duration;
+
+/// The largest JWKS document, in bytes, the clients accept from any transport.
+pub const MAX_DOCUMENT_SIZE: usize = 1024 * 1024;
+
+/// A `GET` request for a JWKS, as sent by [`JwksClient`](super::JwksClient).
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub url: String,
+
+    /// Request headers, such as `Accept` and `If-None-Match`.
+    pub headers: Vec<(String, String)>,
+}
+
+impl HttpRequest {
+    /// Returns the first value of the header `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/// The response to an [`HttpRequest`]. Any status is a response; transport errors are not.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Creates a response without headers.
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self { status, headers: Vec::new(), body: body.into() }
+    }
+
+    /// Adds a header, keeping the headers already set under the same name.
+    pub fn set_header(&mut self, name: &str, value: &str) -> &mut Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Returns the first value of the header `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    /// Returns every value of the header `name`, compared case-insensitively.
+    pub fn headers_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.headers
+            .iter()
+            .filter(move |(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// # Sends the requests of a [`JwksClient`](super::JwksClient).
+///
+/// Implement it on top of the HTTP stack the application already uses. Redirects, proxies,
+/// TLS and timeouts are the transport's responsibility; the client only interprets the
+/// response, and rejects bodies larger than [`MAX_DOCUMENT_SIZE`]. Transports should stop
+/// reading at that size too. Return an error only when no response was received.
+///
+/// Closures of the form `Fn(&HttpRequest) -> Result<HttpResponse, JwkError>` are transports.
+pub trait HttpTransport: Send + Sync {
+    /// Sends a `GET` request and returns the response.
+    ///
+    /// # Errors
+    /// `JwkError::JwksFetch` if no response was received.
+    fn get(&self, request: &HttpRequest) -> Result<HttpResponse, JwkError>;
+}
+
+impl<F> HttpTransport for F
+where
+    F: Fn(&HttpRequest) -> Result<HttpResponse, JwkError> + Send + Sync,
+{
+    fn get(&self, request: &HttpRequest) -> Result<HttpResponse, JwkError> {
+        self(request)
+    }
+}
+
+/// # Sends the requests of an [`AsyncJwksClient`](super::AsyncJwksClient).
+///
+/// The asynchronous counterpart of [`HttpTransport`], for clients such as `reqwest` or
+/// `hyper`. It can be implemented with an `async fn`, as long as the returned future is `Send`.
+pub trait AsyncHttpTransport: Send + Sync {
+    /// Sends a `GET` request and returns the response.
+    ///
+    /// # Errors
+    /// `JwkError::JwksFetch` if no response was received.
+    fn get(&self, request: &HttpRequest) -> impl Future<Output = Result<HttpResponse, JwkError>> + Send;
+}
+
+/// # A transport answering from a queue of canned responses.
+///
+/// Each request takes the next queued response, and is recorded so that tests can inspect
+/// it. When the queue is empty, requests fail as if the server could not be reached.
+/// It implements both [`HttpTransport`] and [`AsyncHttpTransport`].
+///
+/// ## Example
+/// ```rust
+/// use std::sync::Arc;
+/// use jwk_kit::client::JwksClient;
+/// use jwk_kit::client::transport::{HttpResponse, StubTransport};
+///
+/// let transport = Arc::new(StubTransport::new());
+/// transport.push_response(HttpResponse::new(200, r#"{"keys":[]}"#));
+///
+/// let client = JwksClient::with_transport("https://auth.example.com/jwks", transport.clone());
+/// assert!(client.keys().unwrap().keys.is_empty());
+/// assert_eq!(transport.requests().len(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct StubTransport {
+    responses: Mutex<VecDeque<Result<HttpResponse, JwkError>>>,
+    requests: Mutex<Vec<HttpRequest>>,
+}
+
+impl StubTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a response.
+    pub fn push_response(&self, response: HttpResponse) {
+        self.responses.lock().unwrap_or_else(|e| e.into_inner()).push_back(Ok(response));
+    }
+
+    /// Queues a transport error.
+    pub fn push_error(&self, error: JwkError) {
+        self.responses.lock().unwrap_or_else(|e| e.into_inner()).push_back(Err(error));
+    }
+
+    /// Returns the requests received so far.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn answer(&self, request: &HttpRequest) -> Result<HttpResponse, JwkError> {
+        self.requests.lock().unwrap_or_else(|e| e.into_inner()).push(request.clone());
+        self.responses
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop_front()
+            .unwrap_or_else(|| Err(JwkError::JwksFetch("no response queued".into())))
+    }
+}
+
+impl HttpTransport for StubTransport {
+    fn get(&self, request: &HttpRequest) -> Result<HttpResponse, JwkError> {
+        self.answer(request)
+    }
+}
+
+impl AsyncHttpTransport for StubTransport {
+    async fn get(&self, request: &HttpRequest) -> Result<HttpResponse, JwkError> {
+        self.answer(request)
+    }
+}
+
+/// # A blocking transport based on `ureq`.
+///
+/// Responses larger than [`MAX_DOCUMENT_SIZE`] are rejected while they are read. The default timeout is 10 seconds per request.
+///
+/// Requires the `client` feature.
+#[cfg(feature = "client")]
+#[derive(Debug)]
+pub struct UreqTransport {
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "client")]
+impl UreqTransport {
+    pub fn new() -> Self {
+        Self::with_timeout(Duration::from_secs(10))
+    }
+
+    /// Creates a transport whose requests time out after `timeout`.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(timeout))
+            .http_status_as_error(false)
+            .build()
+            .into();
+
+        Self { agent }
+    }
+}
+
+#[cfg(feature = "client")]
+impl Default for UreqTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "client")]
+impl HttpTransport for UreqTransport {
+    fn get(&self, request: &HttpRequest) -> Result<HttpResponse, JwkError> {
+        let fetch_error = |error: ureq::Error| JwkError::JwksFetch(error.to_string());
+
+        let mut builder = self.agent.get(&request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        let mut response = builder.call().map_err(fetch_error)?;
+
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let body = response.body_mut().with_config().limit(MAX_DOCUMENT_SIZE as u64).read_to_vec().map_err(fetch_error)?;
+
+        Ok(HttpResponse { status: response.status().as_u16(), headers, body })
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
@@ -4,7 +4,7 @@ use thiserror::Error;
 /// This enum captures various failure modes encountered during key parsing,
 /// encoding, decoding, or conversion operations within the `jwk_kit` library.
 /// It is designed to help developers handle errors gracefully and with clarity.
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Clone, Error, PartialEq)]
 pub enum JwkError {
     #[error("Missing required RSA parameters: 'n' (modulus) and/or 'e' (exponent)")]
     MissingRsaParams,
@@ -18,7 +18,13 @@ pub enum JwkError {
     #[error("Missing EC coordinate 'y' (public key Y component is required)")]
     MissingEcY,
 
-    #[error("Unsupported key type: {0}. Only 'RSA' and 'EC' are supported")]
+    #[error("Missing required OKP parameters: 'crv' (curve) and/or 'x' (public key)")]
+    MissingOkpParams,
+
+    #[error("Missing required oct parameter: 'k' (key value)")]
+    MissingOctParams,
+
+    #[error("Unsupported key type: {0}. Only 'RSA', 'EC', 'OKP' and 'oct' are supported")]
     UnsupportedKeyType(String),
 
     #[error("RSA key generation failed (internal error or RNG failure)")]
@@ -39,6 +45,102 @@ pub enum JwkError {
     #[error("Failed to encode data as base64url")]
     Base64EncodingError,
 
+    #[error("Invalid base64url value in member '{0}'")]
+    InvalidBase64(String),
+
     #[error("Invalid or unsupported curve type: {0}")]
     UnsupportedCurve(String),
+
+    #[error("Unsupported key encoding: {0}")]
+    UnsupportedEncoding(String),
+
+    #[error("Unable to detect the format of the key input")]
+    UnrecognizedKeyFormat,
+
+    #[error("Expected exactly one key but found {0}")]
+    UnexpectedKeyCount(usize),
+
+    #[error("Invalid JWK or JWKS JSON: {0}")]
+    InvalidJson(String),
+
+    #[error("Failed to parse X.509 certificate")]
+    CertificateParseError,
+
+    #[error("X.509 certificate does not match the key: {0}")]
+    X509Mismatch(String),
+
+    #[error("Failed to build X.509 certificate: {0}")]
+    CertificateBuildError(String),
+
+    #[error("Refusing to serialize private key material (kid: {0})")]
+    PrivateKeyMaterial(String),
+
+    #[error("Invalid 'key_ops': {0}")]
+    InvalidKeyOps(String),
+
+    #[error("The key is not permitted to perform '{0}'")]
+    OperationNotPermitted(String),
+
+    #[error("Unknown JWK member '{0}'")]
+    UnknownMember(String),
+
+    #[error("Duplicate JWK member '{0}'")]
+    DuplicateMember(String),
+
+    #[error("Unsupported or mismatched algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+
+    #[error("The key has no 'kid'")]
+    MissingKeyId,
+
+    #[error("A key with kid '{0}' already exists")]
+    DuplicateKeyId(String),
+
+    #[error("No key with kid '{0}'")]
+    UnknownKeyId(String),
+
+    #[error("The key store has no active key")]
+    NoActiveKey,
+
+    #[error("Invalid key state transition: {0}")]
+    InvalidStateTransition(String),
+
+    #[error("Key id '{0}' cannot be used as a file name")]
+    InvalidKeyId(String),
+
+    #[error("Key store I/O error: {0}")]
+    KeyStoreIo(String),
+
+    #[error("Key wrapping failed: {0}")]
+    KeyWrapError(String),
+
+    #[error("Key store database error: {0}")]
+    KeyStoreDatabase(String),
+
+    #[error("Invalid key file '{0}': {1}")]
+    InvalidKeyFile(String, Box<JwkError>),
+
+    #[error("Invalid key validity: {0}")]
+    InvalidValidity(String),
+
+    #[error("Invalid OpenID Provider metadata: {0}")]
+    InvalidMetadata(String),
+
+    #[error("Failed to fetch the JWKS: {0}")]
+    JwksFetch(String),
+
+    #[error("Invalid token: {0}")]
+    InvalidToken(String),
+
+    #[error("Untrusted issuer '{0}'")]
+    UntrustedIssuer(String),
+
+    #[error("Audience mismatch: {0}")]
+    AudienceMismatch(String),
+
+    #[error("Signing failed: {0}")]
+    SigningFailed(String),
+
+    #[error("The signature does not verify with any trusted key")]
+    InvalidSignature,
 }
@@ -21,3 +21,21 @@ pub mod ecdsa;
 /// - Convert RSA keys to JWK format for use in signing and verification operations.
 /// - Export RSA keys in PEM format (PKCS#8).
 pub mod rsa;
+
+/// PEM encodings that can be produced when generating key pairs.
+///
+/// Not every encoding applies to every key type: PKCS#1 is specific to RSA and SEC1 is
+/// specific to elliptic curve keys. Requesting an encoding that does not apply results
+/// in [`JwkError::UnsupportedEncoding`](crate::error::JwkError::UnsupportedEncoding).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyEncoding {
+    /// `PRIVATE KEY` (PKCS#8) and `PUBLIC KEY` (SPKI) blocks. This is the default.
+    #[default]
+    Pkcs8,
+
+    /// `RSA PRIVATE KEY` and `RSA PUBLIC KEY` (PKCS#1) blocks. RSA only.
+    Pkcs1,
+
+    /// `EC PRIVATE KEY` (SEC1) block with a `PUBLIC KEY` (SPKI) block. EC only.
+    Sec1,
+}
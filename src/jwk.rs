@@ -1,5 +1,17 @@
+use base64::{
+    Engine,
+    engine::general_purpose::URL_SAFE_NO_PAD
+};
 use crate::error::JwkError;
-use serde::{Deserialize, Serialize};
+use crate::x509::check_x509_consistency;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
 
 /// # Representing a JSON Web Key (JWK)
 ///
@@ -10,17 +22,27 @@ use serde::{Deserialize, Serialize};
 /// - `kty` (Key Type): The type of the key (e.g., RSA, EC).
 /// - `alg` (Algorithm): The algorithm used with the key (e.g., RS256, ES256).
 /// - `use` (Key Use): The intended use of the key, such as "sig" for signing or "enc" for encryption.
+/// - `key_ops` (Key Operations): The operations the key may be used for (see [`KeyOperation`]).
 /// - `kid` (Key ID): An identifier for the key, useful for key rotation and lookup.
 /// - `n`, `e` (RSA-specific): The RSA modulus and exponent components, if the key type is RSA.
 /// - `x`, `y` (ECDSA-specific): The elliptic curve coordinates, if the key type is EC (P-256 for ES256).
+///   For OKP keys (Ed25519, [RFC 8037](https://datatracker.ietf.org/doc/html/rfc8037)) only `x` is used.
 /// - `d` (Private key): The private key component, typically used for signing.
-#[derive(Debug, Serialize, Deserialize)]
+/// - `k` (Symmetric key): The key value of an `oct` (symmetric) key.
+/// - `p`, `q`, `dp`, `dq`, `qi` (RSA private key): The prime factors and CRT parameters of an RSA private key.
+/// - `x5u`, `x5c`, `x5t`, `x5t#S256` (X.509): The certificate URL, certificate chain and certificate thumbprints.
+/// - `extra`: Every other member (e.g. `issuer` as published by Microsoft, or custom metadata),
+///   kept as-is so that re-serializing a key does not lose data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Jwk {
     pub kty: String,
 
     #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
     pub use_: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_ops: Option<Vec<String>>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub alg: Option<String>,
 
@@ -44,6 +66,629 @@ pub struct Jwk {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub d: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub k: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub q: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dp: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dq: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qi: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5u: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5c: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5t: Option<String>,
+
+    #[serde(rename = "x5t#S256", skip_serializing_if = "Option::is_none")]
+    pub x5t_s256: Option<String>,
+
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+/// The members modelled by the fields of [`Jwk`]; anything else ends up in `Jwk::extra`.
+const JWK_MEMBERS: [&str; 21] = [
+    "kty", "use", "key_ops", "alg", "kid", "n", "e", "crv", "x", "y", "d", "k",
+    "p", "q", "dp", "dq", "qi", "x5u", "x5c", "x5t", "x5t#S256",
+];
+
+/// The extension members describing when a key may be used (see [`Jwk::is_valid_at`]).
+/// They are kept in `Jwk::extra`, and accepted by the strict parsers.
+const VALIDITY_MEMBERS: [&str; 4] = ["iat", "nbf", "exp", "revoked"];
+
+impl Jwk {
+    /// Checks that the key type is supported and that its required members are present.
+    ///
+    /// This applies the same rules as [`JwkBuilder::build`], and is useful for keys that were
+    /// deserialized from JSON rather than constructed with the builder.
+    ///
+    /// # Errors
+    /// - `JwkError::MissingRsaParams` if an RSA key lacks `n` or `e`.
+    /// - `JwkError::MissingEcParams` if an EC key lacks `crv`, `x` or `y`.
+    /// - `JwkError::MissingOkpParams` if an OKP key lacks `crv` or `x`.
+    /// - `JwkError::MissingOctParams` if an `oct` key lacks `k`.
+    /// - `JwkError::UnsupportedKeyType` for any other `kty`.
+    /// - `JwkError::DuplicateMember` if `extra` holds a member that has a dedicated field.
+    /// - `JwkError::InvalidKeyOps` if `key_ops` is invalid (see [`Jwk::validate_key_ops`]).
+    /// - `JwkError::InvalidValidity` if `iat`, `nbf` or `exp` is not a number of seconds, or
+    ///   `revoked` is not a boolean.
+    /// - `JwkError::X509Mismatch` if `x5c`, `x5t` or `x5t#S256` do not match the key
+    ///   (see [`check_x509_consistency`](crate::x509::check_x509_consistency)).
+    pub fn validate(&self) -> Result<(), JwkError> {
+        self.validate_members()?;
+        self.validate_key_ops()?;
+        self.validate_validity()?;
+
+        if self.x5c.is_some() || self.x5t.is_some() || self.x5t_s256.is_some() {
+            check_x509_consistency(self)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the key type is supported and that its required members are present.
+    pub(crate) fn validate_members(&self) -> Result<(), JwkError> {
+        if let Some(name) = self.extra.keys().find(|name| JWK_MEMBERS.contains(&name.as_str())) {
+            return Err(JwkError::DuplicateMember(name.clone()));
+        }
+
+        match self.kty.as_str() {
+            "RSA" => {
+                if self.n.is_none() || self.e.is_none() {
+                    return Err(JwkError::MissingRsaParams);
+                }
+            }
+            "EC" => {
+                if self.crv.is_none() || self.x.is_none() || self.y.is_none() {
+                    return Err(JwkError::MissingEcParams);
+                }
+            }
+            "OKP" => {
+                if self.crv.is_none() || self.x.is_none() {
+                    return Err(JwkError::MissingOkpParams);
+                }
+            }
+            "oct" => {
+                if self.k.is_none() {
+                    return Err(JwkError::MissingOctParams);
+                }
+            }
+            _ => return Err(JwkError::UnsupportedKeyType(self.kty.clone())),
+        }
+
+        Ok(())
+    }
+
+    /// Checks the `key_ops` member against
+    /// [RFC 7517, section 4.3](https://datatracker.ietf.org/doc/html/rfc7517#section-4.3).
+    ///
+    /// Every operation must be a registered [`KeyOperation`] and appear only once, must be
+    /// one the key type can perform (e.g. EC keys cannot `encrypt`), and must agree with
+    /// `use` when both are present (`"sig"` allows only `sign` and `verify`, `"enc"` allows
+    /// every other operation).
+    ///
+    /// # Errors
+    /// - `JwkError::InvalidKeyOps` describing the first violation found.
+    pub fn validate_key_ops(&self) -> Result<(), JwkError> {
+        let Some(key_ops) = &self.key_ops else {
+            return Ok(());
+        };
+
+        let mut seen = Vec::with_capacity(key_ops.len());
+        for value in key_ops {
+            let op = KeyOperation::from_str(value)?;
+            if seen.contains(&op) {
+                return Err(JwkError::InvalidKeyOps(format!("'{}' is listed more than once", op)));
+            }
+            seen.push(op);
+
+            if !op.supported_by(self) {
+                return Err(JwkError::InvalidKeyOps(format!("'{}' is not possible with a '{}' key", op, self.kty)));
+            }
+
+            let agrees_with_use = match self.use_.as_deref() {
+                Some("sig") => op.is_signature(),
+                Some("enc") => !op.is_signature(),
+                _ => true,
+            };
+            if !agrees_with_use {
+                return Err(JwkError::InvalidKeyOps(format!(
+                    "'{}' contradicts use '{}'",
+                    op,
+                    self.use_.as_deref().unwrap_or_default()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if this key may be used for `op`.
+    ///
+    /// A key is permitted an operation unless its `key_ops` omits it, or its `use` is
+    /// `"sig"` / `"enc"` and the operation belongs to the other use. Keys without `use` and
+    /// `key_ops` are unrestricted, as in RFC 7517.
+    pub fn permits(&self, op: KeyOperation) -> bool {
+        let listed = self
+            .key_ops
+            .as_ref()
+            .is_none_or(|key_ops| key_ops.iter().any(|value| value == op.as_str()));
+
+        let allowed_by_use = match self.use_.as_deref() {
+            Some("sig") => op.is_signature(),
+            Some("enc") => !op.is_signature(),
+            _ => true,
+        };
+
+        listed && allowed_by_use
+    }
+
+    /// Returns an error unless this key may be used for `op` (see [`Jwk::permits`]).
+    ///
+    /// # Errors
+    /// - `JwkError::OperationNotPermitted` if the key's `use` or `key_ops` forbid `op`.
+    pub fn check_operation(&self, op: KeyOperation) -> Result<(), JwkError> {
+        if self.permits(op) {
+            Ok(())
+        } else {
+            Err(JwkError::OperationNotPermitted(op.to_string()))
+        }
+    }
+
+    /// Computes the JWK thumbprint of this key as defined in
+    /// [RFC 7638](https://datatracker.ietf.org/doc/html/rfc7638), using SHA-256.
+    ///
+    /// Only the required public members of the key take part in the thumbprint, so a
+    /// private key and its public counterpart share the same thumbprint.
+    ///
+    /// # Returns
+    /// The base64url-encoded SHA-256 digest of the canonical JSON representation of the key.
+    ///
+    /// # Errors
+    /// Returns the same errors as [`Jwk::validate`] if required members are missing.
+    ///
+    /// # Example
+    /// ```rust
+    /// use jwk_kit::jwk::JwkBuilder;
+    ///
+    /// // Example key from RFC 7638, section 3.1
+    /// let jwk = JwkBuilder::new("RSA")
+    ///     .set_modulus("0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw")
+    ///     .set_exponent("AQAB")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(jwk.thumbprint().unwrap(), "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs");
+    /// ```
+    pub fn thumbprint(&self) -> Result<String, JwkError> {
+        self.validate()?;
+
+        let member = |value: &Option<String>| serde_json::Value::from(value.as_deref().unwrap_or_default());
+        let kty = serde_json::Value::from(self.kty.as_str());
+
+        let canonical = match self.kty.as_str() {
+            "RSA" => format!(r#"{{"e":{},"kty":{},"n":{}}}"#, member(&self.e), kty, member(&self.n)),
+            "EC" => format!(
+                r#"{{"crv":{},"kty":{},"x":{},"y":{}}}"#,
+                member(&self.crv), kty, member(&self.x), member(&self.y)
+            ),
+            "oct" => format!(r#"{{"k":{},"kty":{}}}"#, member(&self.k), kty),
+            _ => format!(r#"{{"crv":{},"kty":{},"x":{}}}"#, member(&self.crv), kty, member(&self.x)),
+        };
+
+        Ok(URL_SAFE_NO_PAD.encode(Sha256::digest(canonical.as_bytes())))
+    }
+
+    /// Returns `true` if this key carries any private member (`d`, the RSA CRT
+    /// parameters `p`, `q`, `dp`, `dq`, `qi` and `oth`, or the symmetric key value `k`).
+    pub fn is_private(&self) -> bool {
+        self.d.is_some()
+            || self.p.is_some()
+            || self.q.is_some()
+            || self.dp.is_some()
+            || self.dq.is_some()
+            || self.qi.is_some()
+            || self.k.is_some()
+            || self.extra.contains_key("oth")
+    }
+
+    /// Returns a copy of this key with every private member removed.
+    ///
+    /// The result is safe to publish, e.g. in a `/.well-known/jwks.json` document.
+    /// Symmetric (`oct`) keys have no public part, so their projection keeps only
+    /// metadata such as `kid` and `alg` and is not a usable key.
+    pub fn to_public(&self) -> Jwk {
+        let mut extra = self.extra.clone();
+        extra.remove("oth");
+
+        Jwk {
+            extra,
+            d: None,
+            k: None,
+            p: None,
+            q: None,
+            dp: None,
+            dq: None,
+            qi: None,
+            ..self.clone()
+        }
+    }
+
+    /// Checks that the validity members, when present, have the right types and represent
+    /// an instant.
+    pub(crate) fn validate_validity(&self) -> Result<(), JwkError> {
+        for name in ["iat", "nbf", "exp"] {
+            match self.extra.get(name).map(serde_json::Value::as_u64) {
+                None => {}
+                Some(None) => {
+                    return Err(JwkError::InvalidValidity(format!("'{}' must be a number of seconds", name)));
+                }
+                Some(Some(seconds)) if from_unix_seconds(seconds).is_none() => {
+                    return Err(JwkError::InvalidValidity(format!("'{}' is out of range", name)));
+                }
+                Some(Some(_)) => {}
+            }
+        }
+        if self.extra.get("revoked").is_some_and(|value| !value.is_boolean()) {
+            return Err(JwkError::InvalidValidity("'revoked' must be a boolean".into()));
+        }
+
+        Ok(())
+    }
+
+    /// Returns when the key was issued, from the `iat` member (seconds since the Unix epoch).
+    pub fn issued_at(&self) -> Option<SystemTime> {
+        self.timestamp("iat")
+    }
+
+    /// Returns the instant before which the key must not be used, from the `nbf` member.
+    pub fn not_before(&self) -> Option<SystemTime> {
+        self.timestamp("nbf")
+    }
+
+    /// Returns the instant from which the key must no longer be used, from the `exp` member.
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.timestamp("exp")
+    }
+
+    /// Returns `true` if the `revoked` member is `true`.
+    pub fn is_revoked(&self) -> bool {
+        self.extra_bool("revoked") == Some(true)
+    }
+
+    /// Sets or removes the `iat` member.
+    pub fn set_issued_at(&mut self, at: Option<SystemTime>) -> &mut Self {
+        self.set_timestamp("iat", at)
+    }
+
+    /// Sets or removes the `nbf` member.
+    pub fn set_not_before(&mut self, at: Option<SystemTime>) -> &mut Self {
+        self.set_timestamp("nbf", at)
+    }
+
+    /// Sets or removes the `exp` member.
+    pub fn set_expires_at(&mut self, at: Option<SystemTime>) -> &mut Self {
+        self.set_timestamp("exp", at)
+    }
+
+    /// Sets the `revoked` member, or removes it when `revoked` is `false`.
+    pub fn set_revoked(&mut self, revoked: bool) -> &mut Self {
+        if revoked {
+            self.extra.insert("revoked".to_string(), true.into());
+        } else {
+            self.extra.remove("revoked");
+        }
+        self
+    }
+
+    /// Returns `true` if the key may be used at `now`: it is not revoked, `now` is not before
+    /// its `nbf`, and it has not reached its `exp`.
+    ///
+    /// Keys without these members are always valid. Members with an invalid type make the key
+    /// invalid, so that a malformed key is never used by mistake.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::{Duration, SystemTime};
+    /// use jwk_kit::jwk::JwkBuilder;
+    ///
+    /// let now = SystemTime::now();
+    /// let jwk = JwkBuilder::new("oct")
+    ///     .set_symmetric_key("c2VjcmV0")
+    ///     .set_not_before(now + Duration::from_secs(3600))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert!(!jwk.is_valid_at(now));
+    /// assert!(jwk.is_valid_at(now + Duration::from_secs(3600)));
+    /// ```
+    pub fn is_valid_at(&self, now: SystemTime) -> bool {
+        if self.validate_validity().is_err() || self.is_revoked() {
+            return false;
+        }
+
+        self.not_before().is_none_or(|nbf| nbf <= now) && self.expires_at().is_none_or(|exp| now < exp)
+    }
+
+    fn timestamp(&self, name: &str) -> Option<SystemTime> {
+        self.extra
+            .get(name)
+            .and_then(serde_json::Value::as_u64)
+            .and_then(from_unix_seconds)
+    }
+
+    fn set_timestamp(&mut self, name: &str, at: Option<SystemTime>) -> &mut Self {
+        match at {
+            Some(at) => {
+                self.extra.insert(name.to_string(), unix_seconds(at).into());
+            }
+            None => {
+                self.extra.remove(name);
+            }
+        }
+        self
+    }
+
+    /// Returns the raw value of a member that has no dedicated field, such as `issuer`.
+    pub fn extra_member(&self, name: &str) -> Option<&serde_json::Value> {
+        self.extra.get(name)
+    }
+
+    /// Returns a member that has no dedicated field as a string, if it is one.
+    pub fn extra_str(&self, name: &str) -> Option<&str> {
+        self.extra.get(name).and_then(serde_json::Value::as_str)
+    }
+
+    /// Returns a member that has no dedicated field as a boolean, if it is one.
+    pub fn extra_bool(&self, name: &str) -> Option<bool> {
+        self.extra.get(name).and_then(serde_json::Value::as_bool)
+    }
+
+    /// Deserializes a member that has no dedicated field into `T`.
+    ///
+    /// # Returns
+    /// `Ok(None)` if the member is absent, `Ok(Some(value))` if it was converted.
+    ///
+    /// # Errors
+    /// - `JwkError::InvalidJson` if the member cannot be converted into `T`.
+    pub fn extra_as<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, JwkError> {
+        self.extra
+            .get(name)
+            .map(|value| {
+                serde_json::from_value(value.clone())
+                    .map_err(|e| JwkError::InvalidJson(format!("member '{}': {}", name, e)))
+            })
+            .transpose()
+    }
+
+    /// Parses a single JWK in strict mode.
+    ///
+    /// Unlike plain deserialization, which keeps unknown members in `extra` and lets the last
+    /// of several duplicated members win, strict mode rejects both. The key is also validated.
+    ///
+    /// # Errors
+    /// - `JwkError::DuplicateMember` if any JSON object in the input repeats a member.
+    /// - `JwkError::UnknownMember` if the key has a member without a dedicated field, other than
+    ///   the validity members `iat`, `nbf`, `exp` and `revoked`.
+    /// - `JwkError::InvalidJson` if the input is not a JWK.
+    /// - Any error returned by [`Jwk::validate`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use jwk_kit::error::JwkError;
+    /// use jwk_kit::jwk::Jwk;
+    ///
+    /// let json = r#"{"kty":"oct","k":"c2VjcmV0","kid":"a","kid":"b"}"#;
+    /// assert_eq!(Jwk::from_json_strict(json), Err(JwkError::DuplicateMember("kid".into())));
+    /// ```
+    pub fn from_json_strict(json: &str) -> Result<Jwk, JwkError> {
+        Jwk::from_strict_value(parse_strict(json)?)
+    }
+
+    fn from_strict_value(value: serde_json::Value) -> Result<Jwk, JwkError> {
+        if let Some(members) = value.as_object()
+            && let Some(name) = members
+                .keys()
+                .find(|name| !JWK_MEMBERS.contains(&name.as_str()) && !VALIDITY_MEMBERS.contains(&name.as_str()))
+        {
+            return Err(JwkError::UnknownMember(name.clone()));
+        }
+
+        let jwk: Jwk = serde_json::from_value(value).map_err(|e| JwkError::InvalidJson(e.to_string()))?;
+        jwk.validate()?;
+
+        Ok(jwk)
+    }
+}
+
+/// Converts a `SystemTime` to whole seconds since the Unix epoch; earlier instants map to 0.
+pub(crate) fn unix_seconds(at: SystemTime) -> u64 {
+    at.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// Converts seconds since the Unix epoch to a `SystemTime`, or `None` if the platform cannot
+/// represent that instant.
+pub(crate) fn from_unix_seconds(seconds: u64) -> Option<SystemTime> {
+    SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(seconds))
+}
+
+/// Parses JSON into a `serde_json::Value`, rejecting objects that repeat a member.
+fn parse_strict(json: &str) -> Result<serde_json::Value, JwkError> {
+    let duplicate = RefCell::new(None);
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+
+    StrictValue(&duplicate)
+        .deserialize(&mut deserializer)
+        .and_then(|value| deserializer.end().map(|_| value))
+        .map_err(|e| match duplicate.take() {
+            Some(name) => JwkError::DuplicateMember(name),
+            None => JwkError::InvalidJson(e.to_string()),
+        })
+}
+
+/// Deserializes any JSON value, recording the first repeated object member it meets.
+#[derive(Clone, Copy)]
+struct StrictValue<'a>(&'a RefCell<Option<String>>);
+
+impl<'de> DeserializeSeed<'de> for StrictValue<'_> {
+    type Value = serde_json::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for StrictValue<'_> {
+    type Value = serde_json::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
+        Ok(value.into())
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(value.into())
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(value.into())
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(value.into())
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(value.into())
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
+        Ok(value.into())
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(serde_json::Value::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element_seed(self)? {
+            values.push(value);
+        }
+        Ok(serde_json::Value::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut members = serde_json::Map::new();
+        while let Some(name) = map.next_key::<String>()? {
+            let value = map.next_value_seed(self)?;
+            if members.contains_key(&name) {
+                let error = de::Error::custom(format_args!("duplicate member `{}`", name));
+                *self.0.borrow_mut() = Some(name);
+                return Err(error);
+            }
+            members.insert(name, value);
+        }
+        Ok(serde_json::Value::Object(members))
+    }
+}
+
+/// An operation a key may be used for, as listed in the `key_ops` member
+/// ([RFC 7517, section 4.3](https://datatracker.ietf.org/doc/html/rfc7517#section-4.3)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyOperation {
+    Sign,
+    Verify,
+    Encrypt,
+    Decrypt,
+    WrapKey,
+    UnwrapKey,
+    DeriveKey,
+    DeriveBits,
+}
+
+impl KeyOperation {
+    /// Returns the name of the operation as it appears in `key_ops`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyOperation::Sign => "sign",
+            KeyOperation::Verify => "verify",
+            KeyOperation::Encrypt => "encrypt",
+            KeyOperation::Decrypt => "decrypt",
+            KeyOperation::WrapKey => "wrapKey",
+            KeyOperation::UnwrapKey => "unwrapKey",
+            KeyOperation::DeriveKey => "deriveKey",
+            KeyOperation::DeriveBits => "deriveBits",
+        }
+    }
+
+    /// Returns `true` for `sign` and `verify`, the operations covered by `use: "sig"`.
+    pub fn is_signature(&self) -> bool {
+        matches!(self, KeyOperation::Sign | KeyOperation::Verify)
+    }
+
+    /// Returns `true` if a key of this type and curve can perform the operation.
+    fn supported_by(&self, jwk: &Jwk) -> bool {
+        use KeyOperation::*;
+
+        match (jwk.kty.as_str(), jwk.crv.as_deref()) {
+            ("RSA", _) => !matches!(self, DeriveKey | DeriveBits),
+            ("EC", _) => matches!(self, Sign | Verify | DeriveKey | DeriveBits),
+            ("OKP", Some("Ed25519" | "Ed448")) => self.is_signature(),
+            ("OKP", Some("X25519" | "X448")) => matches!(self, DeriveKey | DeriveBits),
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for KeyOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for KeyOperation {
+    type Err = JwkError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "sign" => Ok(KeyOperation::Sign),
+            "verify" => Ok(KeyOperation::Verify),
+            "encrypt" => Ok(KeyOperation::Encrypt),
+            "decrypt" => Ok(KeyOperation::Decrypt),
+            "wrapKey" => Ok(KeyOperation::WrapKey),
+            "unwrapKey" => Ok(KeyOperation::UnwrapKey),
+            "deriveKey" => Ok(KeyOperation::DeriveKey),
+            "deriveBits" => Ok(KeyOperation::DeriveBits),
+            other => Err(JwkError::InvalidKeyOps(format!("unknown operation '{}'", other))),
+        }
+    }
+}
+
+/// Decodes a base64url-encoded JWK member, returning `missing` if the member is absent.
+pub(crate) fn decode_member(value: &Option<String>, name: &str, missing: JwkError) -> Result<Vec<u8>, JwkError> {
+    let value = value.as_deref().ok_or(missing)?;
+    URL_SAFE_NO_PAD
+        .decode(value)
+        .map_err(|_| JwkError::InvalidBase64(name.to_string()))
 }
 
 /// Represents a JSON Web Key Set (JWKS).
@@ -55,11 +700,239 @@ pub struct Jwk {
 ///
 /// This struct can be serialized to and deserialized from JSON using Serde, making it easy
 /// to expose or consume JWKS-compliant key sets in web applications.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Jwks {
     pub keys: Vec<Jwk>,
 }
 
+impl Jwks {
+    /// Parses a JWKS in strict mode, applying the rules of [`Jwk::from_json_strict`] to every
+    /// key and rejecting members other than `keys` at the top level.
+    ///
+    /// # Errors
+    /// The same errors as [`Jwk::from_json_strict`].
+    pub fn from_json_strict(json: &str) -> Result<Jwks, JwkError> {
+        let serde_json::Value::Object(mut members) = parse_strict(json)? else {
+            return Err(JwkError::InvalidJson("a JWKS must be a JSON object".into()));
+        };
+        if let Some(name) = members.keys().find(|name| *name != "keys") {
+            return Err(JwkError::UnknownMember(name.clone()));
+        }
+        let Some(serde_json::Value::Array(keys)) = members.remove("keys") else {
+            return Err(JwkError::InvalidJson("a JWKS must have a 'keys' array".into()));
+        };
+
+        let keys = keys
+            .into_iter()
+            .map(Jwk::from_strict_value)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Jwks { keys })
+    }
+
+    /// Returns the first key whose `kid` equals `kid`.
+    ///
+    /// This is a linear scan. For repeated lookups in large sets, build a [`JwksIndex`].
+    pub fn find_by_kid(&self, kid: &str) -> Option<&Jwk> {
+        self.keys.iter().find(|jwk| jwk.kid.as_deref() == Some(kid))
+    }
+
+    /// Returns the first key whose RFC 7638 thumbprint (see [`Jwk::thumbprint`]) equals `thumbprint`.
+    ///
+    /// Keys whose thumbprint cannot be computed are skipped.
+    pub fn find_by_thumbprint(&self, thumbprint: &str) -> Option<&Jwk> {
+        self.keys
+            .iter()
+            .find(|jwk| jwk.thumbprint().is_ok_and(|t| t == thumbprint))
+    }
+
+    /// Returns an iterator over the keys matching `predicate`.
+    pub fn filter<'a, F>(&'a self, predicate: F) -> impl Iterator<Item = &'a Jwk> + 'a
+    where
+        F: Fn(&Jwk) -> bool + 'a,
+    {
+        self.keys.iter().filter(move |jwk| predicate(jwk))
+    }
+
+    /// Returns an iterator over the keys whose `use` equals `key_use` (e.g. `"sig"` or `"enc"`).
+    pub fn by_use<'a>(&'a self, key_use: &'a str) -> impl Iterator<Item = &'a Jwk> + 'a {
+        self.filter(move |jwk| jwk.use_.as_deref() == Some(key_use))
+    }
+
+    /// Returns an iterator over the keys whose `alg` equals `alg` (e.g. `"RS256"`).
+    pub fn by_alg<'a>(&'a self, alg: &'a str) -> impl Iterator<Item = &'a Jwk> + 'a {
+        self.filter(move |jwk| jwk.alg.as_deref() == Some(alg))
+    }
+
+    /// Returns an iterator over the keys whose `kty` equals `kty` (e.g. `"RSA"` or `"EC"`).
+    pub fn by_kty<'a>(&'a self, kty: &'a str) -> impl Iterator<Item = &'a Jwk> + 'a {
+        self.filter(move |jwk| jwk.kty == kty)
+    }
+
+    /// Returns an iterator over the keys whose `crv` equals `crv` (e.g. `"P-256"`).
+    pub fn by_crv<'a>(&'a self, crv: &'a str) -> impl Iterator<Item = &'a Jwk> + 'a {
+        self.filter(move |jwk| jwk.crv.as_deref() == Some(crv))
+    }
+
+    /// Returns an iterator over the keys usable for signatures.
+    ///
+    /// A key qualifies when its `use` is `"sig"`, or when it has no `use` at all, since
+    /// RFC 7517 leaves such keys unrestricted. Keys whose `key_ops` lists neither `sign`
+    /// nor `verify` are excluded.
+    pub fn signing_keys(&self) -> impl Iterator<Item = &Jwk> + '_ {
+        self.filter(|jwk| jwk.permits(KeyOperation::Sign) || jwk.permits(KeyOperation::Verify))
+    }
+
+    /// Returns an iterator over the keys usable for encryption.
+    ///
+    /// A key qualifies when its `use` is `"enc"`, or when it has no `use` at all, since
+    /// RFC 7517 leaves such keys unrestricted. Keys whose `key_ops` lists only `sign` and
+    /// `verify` are excluded.
+    pub fn encryption_keys(&self) -> impl Iterator<Item = &Jwk> + '_ {
+        const ENCRYPTION_OPS: [KeyOperation; 6] = [
+            KeyOperation::Encrypt,
+            KeyOperation::Decrypt,
+            KeyOperation::WrapKey,
+            KeyOperation::UnwrapKey,
+            KeyOperation::DeriveKey,
+            KeyOperation::DeriveBits,
+        ];
+        self.filter(|jwk| ENCRYPTION_OPS.iter().any(|op| jwk.permits(*op)))
+    }
+
+    /// Returns an iterator over the keys that may be used at `now` (see [`Jwk::is_valid_at`]).
+    pub fn valid_at(&self, now: SystemTime) -> impl Iterator<Item = &Jwk> + '_ {
+        self.filter(move |jwk| jwk.is_valid_at(now))
+    }
+
+    /// Returns the first key whose `kid` equals `kid`, if it may be used at `now`.
+    ///
+    /// Keys published ahead of their activation, expired keys and revoked keys are ignored.
+    pub fn find_by_kid_at(&self, kid: &str, now: SystemTime) -> Option<&Jwk> {
+        self.find_by_kid(kid).filter(|jwk| jwk.is_valid_at(now))
+    }
+
+    /// Same as [`Jwks::signing_keys`], keeping only the keys that may be used at `now`.
+    pub fn signing_keys_at(&self, now: SystemTime) -> impl Iterator<Item = &Jwk> + '_ {
+        self.signing_keys().filter(move |jwk| jwk.is_valid_at(now))
+    }
+
+    /// Same as [`Jwks::encryption_keys`], keeping only the keys that may be used at `now`.
+    pub fn encryption_keys_at(&self, now: SystemTime) -> impl Iterator<Item = &Jwk> + '_ {
+        self.encryption_keys().filter(move |jwk| jwk.is_valid_at(now))
+    }
+
+    /// Returns a copy of this key set with every private member removed from every key.
+    ///
+    /// Symmetric (`oct`) keys are dropped entirely, since they have no public part.
+    pub fn to_public(&self) -> Jwks {
+        Jwks {
+            keys: self
+                .keys
+                .iter()
+                .filter(|jwk| jwk.kty != "oct")
+                .map(Jwk::to_public)
+                .collect(),
+        }
+    }
+
+    /// Serializes this key set to JSON, refusing to do so if any key carries private material.
+    ///
+    /// Use this when publishing a key set, e.g. on a `/.well-known/jwks.json` endpoint.
+    /// Call [`Jwks::to_public`] first to strip private members deliberately.
+    ///
+    /// # Errors
+    /// - `JwkError::PrivateKeyMaterial` with the offending `kid` if a key is private.
+    /// - `JwkError::InvalidJson` if serialization fails.
+    ///
+    /// # Example
+    /// ```rust
+    /// use jwk_kit::error::JwkError;
+    /// use jwk_kit::jwk::{create_jwks, JwkBuilder};
+    ///
+    /// let jwk = JwkBuilder::new("EC")
+    ///     .set_key_id("ec-1")
+    ///     .set_curve_type("P-256")
+    ///     .set_x_coordinate("x")
+    ///     .set_y_coordinate("y")
+    ///     .set_private_key("d")
+    ///     .build()
+    ///     .unwrap();
+    /// let jwks = create_jwks(vec![jwk]);
+    ///
+    /// assert_eq!(jwks.serialize_public(), Err(JwkError::PrivateKeyMaterial("ec-1".into())));
+    /// assert!(jwks.to_public().serialize_public().is_ok());
+    /// ```
+    pub fn serialize_public(&self) -> Result<String, JwkError> {
+        if let Some(jwk) = self.keys.iter().find(|jwk| jwk.is_private()) {
+            return Err(JwkError::PrivateKeyMaterial(
+                jwk.kid.clone().unwrap_or_else(|| "<no kid>".to_string()),
+            ));
+        }
+
+        serde_json::to_string(self).map_err(|e| JwkError::InvalidJson(e.to_string()))
+    }
+
+    /// Builds a [`JwksIndex`] over this key set for constant-time `kid` lookups.
+    pub fn index(self) -> JwksIndex {
+        JwksIndex::new(self)
+    }
+}
+
+/// # An indexed JSON Web Key Set
+///
+/// Wraps a `Jwks` together with a hash map from `kid` to key, so that lookups by `kid`
+/// take constant time instead of scanning the whole set. This is intended for hot paths
+/// such as token verification in a gateway, where the key set changes rarely but is
+/// queried on every request.
+///
+/// When several keys share a `kid`, the first one in the set wins, matching
+/// [`Jwks::find_by_kid`].
+#[derive(Debug, Clone)]
+pub struct JwksIndex {
+    jwks: Jwks,
+    by_kid: HashMap<String, usize>,
+}
+
+impl JwksIndex {
+    pub fn new(jwks: Jwks) -> Self {
+        let mut by_kid = HashMap::with_capacity(jwks.keys.len());
+        for (position, jwk) in jwks.keys.iter().enumerate() {
+            if let Some(kid) = &jwk.kid {
+                by_kid.entry(kid.clone()).or_insert(position);
+            }
+        }
+
+        Self { jwks, by_kid }
+    }
+
+    /// Returns the key with the given `kid`, if any.
+    pub fn find_by_kid(&self, kid: &str) -> Option<&Jwk> {
+        self.by_kid.get(kid).map(|&position| &self.jwks.keys[position])
+    }
+
+    /// Returns the key with the given `kid`, if it may be used at `now` (see [`Jwk::is_valid_at`]).
+    pub fn find_by_kid_at(&self, kid: &str, now: SystemTime) -> Option<&Jwk> {
+        self.find_by_kid(kid).filter(|jwk| jwk.is_valid_at(now))
+    }
+
+    /// Returns the underlying key set.
+    pub fn jwks(&self) -> &Jwks {
+        &self.jwks
+    }
+
+    /// Consumes the index and returns the underlying key set.
+    pub fn into_inner(self) -> Jwks {
+        self.jwks
+    }
+}
+
+impl From<Jwks> for JwksIndex {
+    fn from(jwks: Jwks) -> Self {
+        Self::new(jwks)
+    }
+}
+
 /// # A builder for constructing a JSON Web Key (JWK).
 ///
 /// A builder struct to facilitate the creation of a `Jwk`. The builder pattern is used
@@ -73,6 +946,7 @@ pub struct Jwks {
 pub struct JwkBuilder {
     kty: String,
     use_: Option<String>,
+    key_ops: Option<Vec<String>>,
     alg: Option<String>,
     kid: Option<String>,
     n: Option<String>,
@@ -81,6 +955,17 @@ pub struct JwkBuilder {
     x: Option<String>,
     y: Option<String>,
     d: Option<String>,
+    k: Option<String>,
+    p: Option<String>,
+    q: Option<String>,
+    dp: Option<String>,
+    dq: Option<String>,
+    qi: Option<String>,
+    x5u: Option<String>,
+    x5c: Option<Vec<String>>,
+    x5t: Option<String>,
+    x5t_s256: Option<String>,
+    extra: BTreeMap<String, serde_json::Value>,
 }
 
 impl JwkBuilder {
@@ -88,6 +973,7 @@ impl JwkBuilder {
         Self {
             kty: kty.to_string(),
             use_: None,
+            key_ops: None,
             alg: None,
             kid: None,
             n: None,
@@ -96,6 +982,17 @@ impl JwkBuilder {
             x: None,
             y: None,
             d: None,
+            k: None,
+            p: None,
+            q: None,
+            dp: None,
+            dq: None,
+            qi: None,
+            x5u: None,
+            x5c: None,
+            x5t: None,
+            x5t_s256: None,
+            extra: BTreeMap::new(),
         }
     }
 
@@ -104,6 +1001,11 @@ impl JwkBuilder {
         self
     }
 
+    pub fn set_key_operations(&mut self, value: &[KeyOperation]) -> &mut Self {
+        self.key_ops = Some(value.iter().map(|op| op.as_str().to_string()).collect());
+        self
+    }
+
     pub fn set_algorithm(&mut self, value: &str) -> &mut Self {
         self.alg = Some(value.to_string());
         self
@@ -144,25 +1046,88 @@ impl JwkBuilder {
         self
     }
 
-    // Update build method to take a reference to `self`
-    pub fn build(&self) -> Result<Jwk, JwkError> {
-        match self.kty.as_str() {
-            "RSA" => {
-                if self.n.is_none() || self.e.is_none() {
-                    return Err(JwkError::MissingRsaParams);
-                }
-            }
-            "EC" => {
-                if self.crv.is_none() || self.x.is_none() || self.y.is_none() {
-                    return Err(JwkError::MissingEcParams);
-                }
-            }
-            _ => return Err(JwkError::UnsupportedKeyType(self.kty.clone())),
+    pub fn set_symmetric_key(&mut self, value: &str) -> &mut Self {
+        self.k = Some(value.to_string());
+        self
+    }
+
+    pub fn set_first_prime(&mut self, value: &str) -> &mut Self {
+        self.p = Some(value.to_string());
+        self
+    }
+
+    pub fn set_second_prime(&mut self, value: &str) -> &mut Self {
+        self.q = Some(value.to_string());
+        self
+    }
+
+    pub fn set_first_crt_exponent(&mut self, value: &str) -> &mut Self {
+        self.dp = Some(value.to_string());
+        self
+    }
+
+    pub fn set_second_crt_exponent(&mut self, value: &str) -> &mut Self {
+        self.dq = Some(value.to_string());
+        self
+    }
+
+    pub fn set_crt_coefficient(&mut self, value: &str) -> &mut Self {
+        self.qi = Some(value.to_string());
+        self
+    }
+
+    pub fn set_x509_url(&mut self, value: &str) -> &mut Self {
+        self.x5u = Some(value.to_string());
+        self
+    }
+
+    pub fn set_x509_chain(&mut self, value: &[String]) -> &mut Self {
+        self.x5c = Some(value.to_vec());
+        self
+    }
+
+    pub fn set_x509_thumbprint(&mut self, value: &str) -> &mut Self {
+        self.x5t = Some(value.to_string());
+        self
+    }
+
+    pub fn set_x509_thumbprint_sha256(&mut self, value: &str) -> &mut Self {
+        self.x5t_s256 = Some(value.to_string());
+        self
+    }
+
+    pub fn set_extra_member(&mut self, name: &str, value: serde_json::Value) -> &mut Self {
+        self.extra.insert(name.to_string(), value);
+        self
+    }
+
+    pub fn set_issued_at(&mut self, value: SystemTime) -> &mut Self {
+        self.set_extra_member("iat", unix_seconds(value).into())
+    }
+
+    pub fn set_not_before(&mut self, value: SystemTime) -> &mut Self {
+        self.set_extra_member("nbf", unix_seconds(value).into())
+    }
+
+    pub fn set_expires_at(&mut self, value: SystemTime) -> &mut Self {
+        self.set_extra_member("exp", unix_seconds(value).into())
+    }
+
+    pub fn set_revoked(&mut self, value: bool) -> &mut Self {
+        if value {
+            self.set_extra_member("revoked", true.into());
+        } else {
+            self.extra.remove("revoked");
         }
+        self
+    }
 
-        Ok(Jwk {
+    // Update build method to take a reference to `self`
+    pub fn build(&self) -> Result<Jwk, JwkError> {
+        let jwk = Jwk {
             kty: self.kty.clone(),
             use_: self.use_.clone(),
+            key_ops: self.key_ops.clone(),
             alg: self.alg.clone(),
             kid: self.kid.clone(),
             n: self.n.clone(),
@@ -171,7 +1136,22 @@ impl JwkBuilder {
             x: self.x.clone(),
             y: self.y.clone(),
             d: self.d.clone(),
-        })
+            k: self.k.clone(),
+            p: self.p.clone(),
+            q: self.q.clone(),
+            dp: self.dp.clone(),
+            dq: self.dq.clone(),
+            qi: self.qi.clone(),
+            x5u: self.x5u.clone(),
+            x5c: self.x5c.clone(),
+            x5t: self.x5t.clone(),
+            x5t_s256: self.x5t_s256.clone(),
+            extra: self.extra.clone(),
+        };
+
+        jwk.validate()?;
+
+        Ok(jwk)
     }
 }
 
@@ -0,0 +1,159 @@
+use crate::error::JwkError;
+use crate::jwk::{from_unix_seconds, unix_seconds};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Something that happened to a key held in a [`KeyStore`](crate::keystore::KeyStore).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum KeyEvent {
+    /// A new key was generated by the store.
+    Generated { kid: String, alg: String },
+
+    /// An existing key was added to the store.
+    Imported { kid: String },
+
+    /// The key became the signing key.
+    Activated { kid: String },
+
+    /// The key stopped signing, but is still trusted for verification.
+    Retiring { kid: String },
+
+    /// The key is no longer trusted.
+    Retired { kid: String },
+
+    /// The key was revoked.
+    Revoked { kid: String },
+
+    /// The private key was exported out of the store.
+    Exported { kid: String },
+
+    /// The private key was handed out to sign.
+    PrivateKeyAccessed { kid: String },
+}
+
+impl KeyEvent {
+    /// Returns the `kid` of the key the event is about.
+    pub fn kid(&self) -> &str {
+        match self {
+            KeyEvent::Generated { kid, .. }
+            | KeyEvent::Imported { kid }
+            | KeyEvent::Activated { kid }
+            | KeyEvent::Retiring { kid }
+            | KeyEvent::Retired { kid }
+            | KeyEvent::Revoked { kid }
+            | KeyEvent::Exported { kid }
+            | KeyEvent::PrivateKeyAccessed { kid } => kid,
+        }
+    }
+}
+
+/// A [`KeyEvent`] and when it happened, as written to an audit log.
+///
+/// Serialized as a flat JSON object, e.g.
+/// `{"at":1700000000,"event":"generated","kid":"...","alg":"ES256"}`, with `at` in seconds since
+/// the Unix epoch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    #[serde(serialize_with = "serialize_unix", deserialize_with = "deserialize_unix")]
+    pub at: SystemTime,
+
+    #[serde(flatten)]
+    pub event: KeyEvent,
+}
+
+/// Receives the [`AuditRecord`]s of a key store.
+///
+/// Key store operations report a failure of the sink as their own error, after the operation
+/// itself has been applied, so that a lost audit record never goes unnoticed.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, record: &AuditRecord) -> Result<(), JwkError>;
+}
+
+impl<S: AuditSink + ?Sized> AuditSink for Arc<S> {
+    fn record(&self, record: &AuditRecord) -> Result<(), JwkError> {
+        (**self).record(record)
+    }
+}
+
+/// An [`AuditSink`] appending one JSON object per line to a file.
+///
+/// Every record is flushed to disk before [`AuditSink::record`] returns.
+#[derive(Debug)]
+pub struct JsonLinesSink {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl JsonLinesSink {
+    /// Opens `path` for appending, creating it (with `0600` permissions on Unix) if needed.
+    ///
+    /// # Errors
+    /// - `JwkError::KeyStoreIo` if the file cannot be opened.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, JwkError> {
+        let path = path.as_ref().to_path_buf();
+
+        #[allow(unused_mut)]
+        let mut options = OpenOptions::new();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let file = options.append(true).create(true).open(&path).map_err(|e| io_error(&path, e))?;
+
+        Ok(Self { path, file: Mutex::new(file) })
+    }
+
+    /// Returns the path of the log.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads every record of the log at `path`, oldest first.
+    ///
+    /// # Errors
+    /// - `JwkError::KeyStoreIo` if the file cannot be read.
+    /// - `JwkError::InvalidJson` if a line is not a valid record.
+    pub fn read(path: impl AsRef<Path>) -> Result<Vec<AuditRecord>, JwkError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| io_error(path, e))?;
+
+        BufReader::new(file)
+            .lines()
+            .filter(|line| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+            .map(|line| {
+                let line = line.map_err(|e| io_error(path, e))?;
+                serde_json::from_str(&line).map_err(|e| JwkError::InvalidJson(e.to_string()))
+            })
+            .collect()
+    }
+}
+
+impl AuditSink for JsonLinesSink {
+    fn record(&self, record: &AuditRecord) -> Result<(), JwkError> {
+        let mut line = serde_json::to_vec(record).map_err(|e| JwkError::InvalidJson(e.to_string()))?;
+        line.push(b'\n');
+
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.write_all(&line).map_err(|e| io_error(&self.path, e))?;
+        file.sync_data().map_err(|e| io_error(&self.path, e))
+    }
+}
+
+fn serialize_unix<S: Serializer>(at: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(unix_seconds(*at))
+}
+
+fn deserialize_unix<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+    let seconds = u64::deserialize(deserializer)?;
+    from_unix_seconds(seconds).ok_or_else(|| D::Error::custom(format!("timestamp {} is out of range", seconds)))
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> JwkError {
+    JwkError::KeyStoreIo(format!("{}: {}", path.display(), error))
+}
@@ -0,0 +1,296 @@
+use aes_gcm::{
+    aead::{Aead, Payload},
+    Aes256Gcm, KeyInit, Nonce,
+};
+use aes_kw::{KekAes128, KekAes192, KekAes256};
+use base64::{
+    Engine,
+    engine::general_purpose::URL_SAFE_NO_PAD
+};
+use crate::error::JwkError;
+use crate::jwk::{decode_member, Jwk, KeyOperation};
+use p256::elliptic_curve::rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+/// Default number of PBKDF2 iterations used by [`PasswordKeyWrapper`].
+pub const DEFAULT_PBKDF2_ITERATIONS: u32 = 600_000;
+
+/// The range of PBKDF2 iteration counts [`PasswordKeyWrapper`] accepts, both when wrapping and
+/// when unwrapping: fewer is too cheap to brute-force, more lets a tampered store stall the reader.
+pub const PBKDF2_ITERATIONS: std::ops::RangeInclusive<u32> = 100_000..=10_000_000;
+
+const DEK_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
+
+/// Wraps and unwraps the data-encryption keys of a sealed key store with a
+/// key-encryption key (KEK).
+///
+/// Implement this trait to keep the KEK in a KMS or an HSM; [`AesKeyWrapper`] and
+/// [`PasswordKeyWrapper`] cover the common local cases.
+pub trait KeyWrapper: Send + Sync {
+    /// An identifier of the KEK, recorded next to every key it wraps.
+    fn key_id(&self) -> String;
+
+    /// Encrypts `key` under the KEK.
+    fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, JwkError>;
+
+    /// Decrypts a key produced by [`KeyWrapper::wrap_key`].
+    fn unwrap_key(&self, wrapped: &[u8]) -> Result<Vec<u8>, JwkError>;
+}
+
+/// A [`KeyWrapper`] using AES Key Wrap (RFC 3394) with an `oct` `Jwk` as the KEK.
+pub struct AesKeyWrapper {
+    kid: String,
+    kek: AesKek,
+}
+
+enum AesKek {
+    Aes128(KekAes128),
+    Aes192(KekAes192),
+    Aes256(KekAes256),
+}
+
+impl AesKeyWrapper {
+    /// Creates a wrapper from a 128, 192 or 256-bit `oct` key.
+    ///
+    /// # Errors
+    /// - `JwkError::UnsupportedKeyType` if the key is not an `oct` key.
+    /// - `JwkError::MissingKeyId` if the key has no `kid`, which identifies the KEK.
+    /// - `JwkError::UnsupportedAlgorithm` if `k` has another length, or `alg` is set to
+    ///   something other than the matching `A128KW`, `A192KW` or `A256KW`.
+    /// - `JwkError::OperationNotPermitted` if the key's `use` or `key_ops` forbid wrapping.
+    pub fn new(jwk: &Jwk) -> Result<Self, JwkError> {
+        if jwk.kty != "oct" {
+            return Err(JwkError::UnsupportedKeyType(jwk.kty.clone()));
+        }
+        let kid = jwk.kid.clone().ok_or(JwkError::MissingKeyId)?;
+        jwk.check_operation(KeyOperation::WrapKey)?;
+        jwk.check_operation(KeyOperation::UnwrapKey)?;
+
+        let k = decode_member(&jwk.k, "k", JwkError::MissingOctParams)?;
+        let (kek, alg) = match k.len() {
+            16 => (AesKek::Aes128(KekAes128::try_from(k.as_slice()).map_err(wrap_error)?), "A128KW"),
+            24 => (AesKek::Aes192(KekAes192::try_from(k.as_slice()).map_err(wrap_error)?), "A192KW"),
+            32 => (AesKek::Aes256(KekAes256::try_from(k.as_slice()).map_err(wrap_error)?), "A256KW"),
+            other => return Err(JwkError::UnsupportedAlgorithm(format!("AES key wrap with a {}-bit key", other * 8))),
+        };
+        if jwk.alg.as_deref().is_some_and(|value| value != alg) {
+            return Err(JwkError::UnsupportedAlgorithm(format!("{:?} for a {}-bit key", jwk.alg, k.len() * 8)));
+        }
+
+        Ok(Self { kid, kek })
+    }
+}
+
+impl KeyWrapper for AesKeyWrapper {
+    fn key_id(&self) -> String {
+        self.kid.clone()
+    }
+
+    fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, JwkError> {
+        match &self.kek {
+            AesKek::Aes128(kek) => kek.wrap_vec(key),
+            AesKek::Aes192(kek) => kek.wrap_vec(key),
+            AesKek::Aes256(kek) => kek.wrap_vec(key),
+        }
+        .map_err(wrap_error)
+    }
+
+    fn unwrap_key(&self, wrapped: &[u8]) -> Result<Vec<u8>, JwkError> {
+        match &self.kek {
+            AesKek::Aes128(kek) => kek.unwrap_vec(wrapped),
+            AesKek::Aes192(kek) => kek.unwrap_vec(wrapped),
+            AesKek::Aes256(kek) => kek.unwrap_vec(wrapped),
+        }
+        .map_err(wrap_error)
+    }
+}
+
+/// A [`KeyWrapper`] deriving an AES-256 KEK from a password with PBKDF2-HMAC-SHA256.
+///
+/// Every wrapped key gets a fresh random salt; the salt and the iteration count are stored
+/// with it, so changing the iteration count only affects keys wrapped afterwards.
+pub struct PasswordKeyWrapper {
+    label: String,
+    password: Vec<u8>,
+    iterations: u32,
+}
+
+impl PasswordKeyWrapper {
+    /// Creates a wrapper for `password`. `label` identifies the password in the key store
+    /// (e.g. `"master-2025"`) and must not reveal it.
+    pub fn new(password: &str, label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            password: password.as_bytes().to_vec(),
+            iterations: DEFAULT_PBKDF2_ITERATIONS,
+        }
+    }
+
+    /// Sets the PBKDF2 iteration count used for new wrapped keys.
+    ///
+    /// # Errors
+    /// Returns `JwkError::KeyWrapError` if `iterations` is outside of [`PBKDF2_ITERATIONS`].
+    pub fn set_iterations(&mut self, iterations: u32) -> Result<&mut Self, JwkError> {
+        check_iterations(iterations)?;
+        self.iterations = iterations;
+        Ok(self)
+    }
+
+    fn derive(&self, salt: &[u8], iterations: u32) -> Result<KekAes256, JwkError> {
+        check_iterations(iterations)?;
+        let mut kek = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<Sha256>(&self.password, salt, iterations, &mut kek);
+        Ok(KekAes256::from(kek))
+    }
+}
+
+impl KeyWrapper for PasswordKeyWrapper {
+    fn key_id(&self) -> String {
+        self.label.clone()
+    }
+
+    /// Returns the iteration count (4 bytes, big endian), the salt and the wrapped key.
+    fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, JwkError> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+
+        let mut wrapped = self.iterations.to_be_bytes().to_vec();
+        wrapped.extend_from_slice(&salt);
+        wrapped.extend(self.derive(&salt, self.iterations)?.wrap_vec(key).map_err(wrap_error)?);
+
+        Ok(wrapped)
+    }
+
+    fn unwrap_key(&self, wrapped: &[u8]) -> Result<Vec<u8>, JwkError> {
+        if wrapped.len() < 4 + SALT_LEN {
+            return Err(JwkError::KeyWrapError("wrapped key is truncated".into()));
+        }
+        let (iterations, rest) = wrapped.split_at(4);
+        let (salt, wrapped) = rest.split_at(SALT_LEN);
+        let iterations = u32::from_be_bytes(iterations.try_into().expect("4 bytes"));
+
+        self.derive(salt, iterations)?.unwrap_vec(wrapped).map_err(wrap_error)
+    }
+}
+
+fn check_iterations(iterations: u32) -> Result<(), JwkError> {
+    if !PBKDF2_ITERATIONS.contains(&iterations) {
+        return Err(JwkError::KeyWrapError(format!("unsupported PBKDF2 iteration count {}", iterations)));
+    }
+    Ok(())
+}
+
+/// A private `Jwk` encrypted with a random data-encryption key (DEK) using AES-256-GCM,
+/// with the DEK itself wrapped by a [`KeyWrapper`].
+///
+/// The `kid` of the key is bound to the ciphertext as additional authenticated data, so a
+/// sealed key cannot be swapped with another one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SealedKey {
+    /// The [`KeyWrapper::key_id`] of the KEK that wrapped `encrypted_key`.
+    pub kek: String,
+
+    /// The wrapped DEK, base64url-encoded.
+    pub encrypted_key: String,
+
+    /// The AES-GCM nonce, base64url-encoded.
+    pub iv: String,
+
+    /// The encrypted JSON of the `Jwk` followed by the AES-GCM tag, base64url-encoded.
+    pub ciphertext: String,
+}
+
+impl SealedKey {
+    /// Encrypts `jwk` under a fresh DEK wrapped by `wrapper`.
+    ///
+    /// # Errors
+    /// - `JwkError::MissingKeyId` if the key has no `kid`.
+    /// - `JwkError::KeyWrapError` if encryption or wrapping fails.
+    pub fn seal(jwk: &Jwk, wrapper: &dyn KeyWrapper) -> Result<SealedKey, JwkError> {
+        let kid = jwk.kid.as_deref().ok_or(JwkError::MissingKeyId)?;
+        let plaintext = serde_json::to_vec(jwk).map_err(|e| JwkError::InvalidJson(e.to_string()))?;
+
+        let mut dek = [0u8; DEK_LEN];
+        OsRng.fill_bytes(&mut dek);
+        let mut iv = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut iv);
+
+        let cipher = Aes256Gcm::new_from_slice(&dek).map_err(wrap_error)?;
+        let ciphertext = cipher
+            .encrypt(&Nonce::from(iv), Payload { msg: &plaintext, aad: kid.as_bytes() })
+            .map_err(wrap_error)?;
+
+        Ok(SealedKey {
+            kek: wrapper.key_id(),
+            encrypted_key: URL_SAFE_NO_PAD.encode(wrapper.wrap_key(&dek)?),
+            iv: URL_SAFE_NO_PAD.encode(iv),
+            ciphertext: URL_SAFE_NO_PAD.encode(ciphertext),
+        })
+    }
+
+    /// Decrypts the key, which must have the given `kid`.
+    ///
+    /// # Errors
+    /// - `JwkError::KeyWrapError` if `wrapper` is not the KEK of this key, or if the key was
+    ///   tampered with or sealed under another `kid`.
+    pub fn open(&self, kid: &str, wrapper: &dyn KeyWrapper) -> Result<Jwk, JwkError> {
+        let dek = self.unwrap_dek(wrapper)?;
+        let iv: [u8; NONCE_LEN] = decode(&self.iv, "iv")?
+            .try_into()
+            .map_err(|_| JwkError::KeyWrapError("invalid nonce length".into()))?;
+
+        let cipher = Aes256Gcm::new_from_slice(&dek).map_err(wrap_error)?;
+        let plaintext = cipher
+            .decrypt(
+                &Nonce::from(iv),
+                Payload { msg: &decode(&self.ciphertext, "ciphertext")?, aad: kid.as_bytes() },
+            )
+            .map_err(|_| JwkError::KeyWrapError(format!("key '{}' cannot be decrypted", kid)))?;
+
+        serde_json::from_slice(&plaintext).map_err(|e| JwkError::InvalidJson(e.to_string()))
+    }
+
+    /// Re-wraps the DEK under the KEK of `to`, leaving the encrypted key itself unchanged.
+    ///
+    /// # Errors
+    /// - `JwkError::KeyWrapError` if `from` is not the KEK of this key, or wrapping fails.
+    pub fn rewrap(&self, from: &dyn KeyWrapper, to: &dyn KeyWrapper) -> Result<SealedKey, JwkError> {
+        let dek = self.unwrap_dek(from)?;
+
+        Ok(SealedKey {
+            kek: to.key_id(),
+            encrypted_key: URL_SAFE_NO_PAD.encode(to.wrap_key(&dek)?),
+            ..self.clone()
+        })
+    }
+
+    fn unwrap_dek(&self, wrapper: &dyn KeyWrapper) -> Result<Vec<u8>, JwkError> {
+        if self.kek != wrapper.key_id() {
+            return Err(JwkError::KeyWrapError(format!(
+                "wrapped by '{}', not by '{}'",
+                self.kek,
+                wrapper.key_id()
+            )));
+        }
+
+        let dek = wrapper.unwrap_key(&decode(&self.encrypted_key, "encrypted_key")?)?;
+        if dek.len() != DEK_LEN {
+            return Err(JwkError::KeyWrapError("invalid data-encryption key length".into()));
+        }
+
+        Ok(dek)
+    }
+}
+
+fn decode(value: &str, name: &str) -> Result<Vec<u8>, JwkError> {
+    URL_SAFE_NO_PAD
+        .decode(value)
+        .map_err(|_| JwkError::InvalidBase64(name.to_string()))
+}
+
+fn wrap_error(error: impl std::fmt::Display) -> JwkError {
+    JwkError::KeyWrapError(error.to_string())
+}
@@ -0,0 +1,392 @@
+use crate::error::JwkError;
+use crate::jwk::{Jwk, from_unix_seconds, unix_seconds};
+use crate::keystore::envelope::{KeyWrapper, SealedKey};
+use crate::keystore::{KeyAlgorithm, KeyState, KeyStore, ManagedKey};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const LOCK_FILE: &str = ".lock";
+const EXTENSION: &str = "json";
+
+/// The on-disk representation of a [`ManagedKey`]. Timestamps are seconds since the Unix epoch.
+///
+/// In an encrypted store, `jwk` only holds the public members and the private key is in `sealed`.
+#[derive(Serialize, Deserialize)]
+struct KeyRecord {
+    jwk: Jwk,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    sealed: Option<SealedKey>,
+    state: KeyState,
+    created_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    activates_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    expires_at: Option<u64>,
+}
+
+/// # A key store persisted in a directory.
+///
+/// Every key is stored, with its private members, state and timestamps, as a JSON file named
+/// after its `kid`. Files are created with `0600` permissions (and the directory with `0700`)
+/// on Unix, and replaced atomically by writing a temporary file and renaming it, so readers
+/// never see a partially written key. Writers hold an exclusive advisory lock on a `.lock`
+/// file in the directory, and readers a shared one. Readers only open the lock file for
+/// reading, so a store mounted read-only can be loaded; when the lock file is missing and
+/// cannot be created there, keys are read without the lock.
+///
+/// Timestamps are stored with a precision of one second.
+///
+/// A store opened with [`FileKeyStore::open_encrypted`] seals every private key with envelope
+/// encryption (see [`SealedKey`]); only the public members of the keys are stored in clear.
+///
+/// ## Example
+/// ```rust
+/// use jwk_kit::keystore::fs::FileKeyStore;
+/// use jwk_kit::keystore::{KeyAlgorithm, KeyStore};
+///
+/// let dir = std::env::temp_dir().join(format!("jwk-kit-doc-{}", std::process::id()));
+/// let files = FileKeyStore::open(&dir).unwrap();
+///
+/// let mut store = KeyStore::new(KeyAlgorithm::Es256);
+/// let kid = store.rotate().unwrap().kid().to_string();
+/// files.save_all(&store).unwrap();
+///
+/// let reloaded = files.load_store(KeyAlgorithm::Es256).unwrap();
+/// assert_eq!(reloaded.current_signing_key().unwrap().kid.as_deref(), Some(kid.as_str()));
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct FileKeyStore {
+    dir: PathBuf,
+    wrapper: Option<Arc<dyn KeyWrapper>>,
+}
+
+impl fmt::Debug for FileKeyStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileKeyStore")
+            .field("dir", &self.dir)
+            .field("kek", &self.wrapper.as_ref().map(|wrapper| wrapper.key_id()))
+            .finish()
+    }
+}
+
+impl FileKeyStore {
+    /// Opens the key store in `dir`, creating the directory if it does not exist.
+    ///
+    /// # Errors
+    /// - `JwkError::KeyStoreIo` if the directory cannot be created.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, JwkError> {
+        let dir = dir.as_ref().to_path_buf();
+
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+        builder.create(&dir).map_err(|e| io_error(&dir, e))?;
+
+        Ok(Self { dir, wrapper: None })
+    }
+
+    /// Opens the key store in `dir` like [`FileKeyStore::open`], sealing the private keys it
+    /// writes under the KEK of `wrapper`.
+    ///
+    /// Keys previously written in clear can still be read, and are sealed the next time they
+    /// are saved.
+    ///
+    /// # Errors
+    /// The same errors as [`FileKeyStore::open`].
+    pub fn open_encrypted(dir: impl AsRef<Path>, wrapper: impl KeyWrapper + 'static) -> Result<Self, JwkError> {
+        let mut store = Self::open(dir)?;
+        store.wrapper = Some(Arc::new(wrapper));
+        Ok(store)
+    }
+
+    /// Re-wraps the data-encryption key of every sealed key under the KEK of `wrapper`, and
+    /// uses `wrapper` from then on. The signing keys themselves are not re-encrypted. Keys
+    /// stored in clear are sealed under `wrapper`.
+    ///
+    /// Each file is replaced atomically, and keys already wrapped by `wrapper` are skipped, so
+    /// an interrupted re-wrap can be resumed by calling this method again.
+    ///
+    /// # Errors
+    /// - `JwkError::KeyWrapError` if a key is sealed under a KEK other than the current one.
+    /// - `JwkError::KeyStoreIo` if a file cannot be read or written.
+    pub fn rewrap(&mut self, wrapper: impl KeyWrapper + 'static) -> Result<(), JwkError> {
+        let _lock = self.lock(true)?;
+
+        for path in self.key_files()? {
+            let mut record = read_record(&path)?;
+            let sealed = match (&record.sealed, &self.wrapper) {
+                (Some(sealed), _) if sealed.kek == wrapper.key_id() => continue,
+                (Some(sealed), Some(current)) => sealed.rewrap(current.as_ref(), &wrapper)?,
+                (Some(sealed), None) => {
+                    return Err(JwkError::KeyWrapError(format!(
+                        "{} is sealed under '{}', but the store has no key wrapper",
+                        path.display(),
+                        sealed.kek
+                    )));
+                }
+                (None, _) if record.jwk.is_private() => SealedKey::seal(&record.jwk, &wrapper)?,
+                (None, _) => continue,
+            };
+
+            record.jwk = record.jwk.to_public();
+            record.sealed = Some(sealed);
+            self.write_record(&path, &record)?;
+        }
+
+        self.wrapper = Some(Arc::new(wrapper));
+        Ok(())
+    }
+
+    /// Returns the directory holding the keys.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Writes `key`, replacing any previous version of it.
+    ///
+    /// # Errors
+    /// - `JwkError::InvalidKeyId` if the `kid` is not usable as a file name
+    ///   (only ASCII letters, digits, `-`, `_` and `.` are allowed, and it must not start with `.`).
+    /// - `JwkError::KeyStoreIo` if the file cannot be written.
+    pub fn save(&self, key: &ManagedKey) -> Result<(), JwkError> {
+        let _lock = self.lock(true)?;
+        self.write_key(key)
+    }
+
+    /// Writes every key of `store` while holding the lock once.
+    ///
+    /// Files of keys that are no longer in `store` are left untouched; see [`FileKeyStore::remove`].
+    ///
+    /// # Errors
+    /// The same errors as [`FileKeyStore::save`].
+    pub fn save_all(&self, store: &KeyStore) -> Result<(), JwkError> {
+        let _lock = self.lock(true)?;
+        store.keys().iter().try_for_each(|key| self.write_key(key))
+    }
+
+    /// Deletes the file of the key with the given `kid`.
+    ///
+    /// # Errors
+    /// - `JwkError::UnknownKeyId` if there is no such key.
+    /// - `JwkError::KeyStoreIo` if the file cannot be deleted.
+    pub fn remove(&self, kid: &str) -> Result<(), JwkError> {
+        let path = self.key_path(kid)?;
+        let _lock = self.lock(true)?;
+
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(JwkError::UnknownKeyId(kid.to_string())),
+            result => {
+                result.map_err(|e| io_error(&path, e))?;
+                sync_dir(&self.dir)
+            }
+        }
+    }
+
+    /// Reads the key with the given `kid`.
+    ///
+    /// # Errors
+    /// - `JwkError::UnknownKeyId` if there is no such key.
+    /// - `JwkError::KeyStoreIo` if the file cannot be read.
+    /// - `JwkError::InvalidJson` if the file is not a valid key record.
+    /// - `JwkError::KeyWrapError` if the key is sealed and cannot be decrypted with the
+    ///   store's wrapper.
+    pub fn load(&self, kid: &str) -> Result<ManagedKey, JwkError> {
+        let path = self.key_path(kid)?;
+        let _lock = self.lock(false)?;
+
+        if !path.exists() {
+            return Err(JwkError::UnknownKeyId(kid.to_string()));
+        }
+        self.read_key(&path)
+    }
+
+    /// Reads every key in the directory, ordered by creation time.
+    ///
+    /// # Errors
+    /// - `JwkError::KeyStoreIo` if the directory or a file cannot be read.
+    /// - `JwkError::InvalidJson` if a file is not a valid key record.
+    /// - `JwkError::KeyWrapError` if a sealed key cannot be decrypted with the store's wrapper.
+    pub fn load_all(&self) -> Result<Vec<ManagedKey>, JwkError> {
+        let _lock = self.lock(false)?;
+
+        let mut keys = self
+            .key_files()?
+            .iter()
+            .map(|path| self.read_key(path))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        keys.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.kid().cmp(b.kid())));
+        Ok(keys)
+    }
+
+    /// Reads every key in the directory into a new [`KeyStore`] that generates keys of kind
+    /// `algorithm` when rotating.
+    ///
+    /// # Errors
+    /// The same errors as [`FileKeyStore::load_all`], and those of [`KeyStore::restore`].
+    pub fn load_store(&self, algorithm: KeyAlgorithm) -> Result<KeyStore, JwkError> {
+        let mut store = KeyStore::new(algorithm);
+        for key in self.load_all()? {
+            store.restore(key)?;
+        }
+        Ok(store)
+    }
+
+    fn key_path(&self, kid: &str) -> Result<PathBuf, JwkError> {
+        let valid = !kid.is_empty()
+            && !kid.starts_with('.')
+            && kid.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(JwkError::InvalidKeyId(kid.to_string()));
+        }
+
+        Ok(self.dir.join(format!("{}.{}", kid, EXTENSION)))
+    }
+
+    fn lock(&self, exclusive: bool) -> Result<Option<File>, JwkError> {
+        let path = self.dir.join(LOCK_FILE);
+        let create = || private_file_options().write(true).create(true).truncate(false).open(&path);
+        let opened = if exclusive {
+            create()
+        } else {
+            match File::open(&path) {
+                // Only writers need the lock file to exist.
+                Err(e) if e.kind() == ErrorKind::NotFound => match create() {
+                    Ok(file) => Ok(file),
+                    Err(_) => return Ok(None),
+                },
+                opened => opened,
+            }
+        };
+        let file = opened.map_err(|e| io_error(&path, e))?;
+
+        let locked = if exclusive { file.lock() } else { file.lock_shared() };
+        locked.map_err(|e| io_error(&path, e))?;
+
+        // The lock is released when the file is dropped.
+        Ok(Some(file))
+    }
+
+    fn key_files(&self) -> Result<Vec<PathBuf>, JwkError> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(&self.dir).map_err(|e| io_error(&self.dir, e))? {
+            let path = entry.map_err(|e| io_error(&self.dir, e))?.path();
+            let is_key_file = path.extension().is_some_and(|ext| ext == EXTENSION)
+                && path.file_name().is_some_and(|name| !name.to_string_lossy().starts_with('.'));
+            if is_key_file {
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+
+    fn read_key(&self, path: &Path) -> Result<ManagedKey, JwkError> {
+        let record = read_record(path)?;
+
+        let jwk = match (&record.sealed, &self.wrapper) {
+            (None, _) => record.jwk,
+            (Some(sealed), Some(wrapper)) => {
+                let kid = record.jwk.kid.as_deref().ok_or(JwkError::MissingKeyId)?;
+                sealed.open(kid, wrapper.as_ref())?
+            }
+            (Some(sealed), None) => {
+                return Err(JwkError::KeyWrapError(format!(
+                    "{} is sealed under '{}'; open the store with a key wrapper",
+                    path.display(),
+                    sealed.kek
+                )));
+            }
+        };
+
+        let time = |seconds: u64| {
+            from_unix_seconds(seconds)
+                .ok_or_else(|| JwkError::KeyStoreIo(format!("{}: timestamp {} is out of range", path.display(), seconds)))
+        };
+        Ok(ManagedKey {
+            jwk,
+            state: record.state,
+            created_at: time(record.created_at)?,
+            activates_at: record.activates_at.map(time).transpose()?,
+            expires_at: record.expires_at.map(time).transpose()?,
+        })
+    }
+
+    fn write_key(&self, key: &ManagedKey) -> Result<(), JwkError> {
+        let path = self.key_path(key.kid())?;
+
+        let (jwk, sealed) = match &self.wrapper {
+            Some(wrapper) if key.jwk.is_private() => {
+                (key.jwk.to_public(), Some(SealedKey::seal(&key.jwk, wrapper.as_ref())?))
+            }
+            _ => (key.jwk.clone(), None),
+        };
+        let record = KeyRecord {
+            jwk,
+            sealed,
+            state: key.state,
+            created_at: unix_seconds(key.created_at),
+            activates_at: key.activates_at.map(unix_seconds),
+            expires_at: key.expires_at.map(unix_seconds),
+        };
+
+        self.write_record(&path, &record)
+    }
+
+    fn write_record(&self, path: &Path, record: &KeyRecord) -> Result<(), JwkError> {
+        let json = serde_json::to_vec_pretty(record).map_err(|e| JwkError::InvalidJson(e.to_string()))?;
+
+        // Writers hold the exclusive lock, so a fixed temporary name cannot clash.
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let tmp_path = self.dir.join(format!(".{}.tmp", file_name));
+        let mut file = private_file_options()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp_path)
+            .map_err(|e| io_error(&tmp_path, e))?;
+        file.write_all(&json).map_err(|e| io_error(&tmp_path, e))?;
+        file.sync_all().map_err(|e| io_error(&tmp_path, e))?;
+        drop(file);
+
+        fs::rename(&tmp_path, path).map_err(|e| io_error(path, e))?;
+        sync_dir(&self.dir)
+    }
+}
+
+fn read_record(path: &Path) -> Result<KeyRecord, JwkError> {
+    let json = fs::read(path).map_err(|e| io_error(path, e))?;
+    serde_json::from_slice(&json).map_err(|e| JwkError::InvalidJson(format!("{}: {}", path.display(), e)))
+}
+
+fn private_file_options() -> OpenOptions {
+    #[allow(unused_mut)]
+    let mut options = OpenOptions::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+}
+
+/// Makes a rename or deletion in `dir` durable.
+fn sync_dir(dir: &Path) -> Result<(), JwkError> {
+    #[cfg(unix)]
+    File::open(dir)
+        .and_then(|dir_file| dir_file.sync_all())
+        .map_err(|e| io_error(dir, e))?;
+    Ok(())
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> JwkError {
+    JwkError::KeyStoreIo(format!("{}: {}", path.display(), error))
+}
@@ -0,0 +1,526 @@
+use crate::error::JwkError;
+use crate::generator::ecdsa::generate_es256_keypair_pem;
+use crate::generator::rsa::generate_rsa_keypair_pem;
+use crate::jwk::{Jwk, Jwks};
+use crate::loader::load_key;
+use audit::{AuditRecord, AuditSink, KeyEvent};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// # Recording key lifecycle events
+///
+/// This module describes what happens to the keys of a [`KeyStore`] as [`KeyEvent`]s, and
+/// delivers them to a pluggable sink, so that the creation, use and retirement of every `kid`
+/// can be proven afterwards.
+///
+/// ## Key functionalities:
+/// - Receive every lifecycle event of a store through the `AuditSink` trait.
+/// - Append events to a JSON-lines file, and read them back.
+pub mod audit;
+
+/// # Persisting keys in a directory
+///
+/// This module stores the keys of a [`KeyStore`] on disk, one file per key, with restrictive
+/// permissions, atomic writes and an advisory lock against concurrent writers.
+///
+/// ## Key functionalities:
+/// - Save and remove individual keys, or a whole `KeyStore`.
+/// - Reload the keys, with their states and timestamps, into a `KeyStore`.
+pub mod fs;
+
+/// # Encrypting stored keys with a master key
+///
+/// This module protects private keys at rest with envelope encryption: each key is encrypted
+/// with its own data-encryption key, which is in turn wrapped by a key-encryption key.
+///
+/// ## Key functionalities:
+/// - Wrap data-encryption keys with an AES-KW `oct` key, a password, or a custom `KeyWrapper`.
+/// - Seal and open individual private keys.
+/// - Re-wrap sealed keys under a new master key without re-encrypting them.
+pub mod envelope;
+
+/// # Persisting keys in a SQLite database
+///
+/// This module stores the keys of a [`KeyStore`] and the history of their states in a SQLite
+/// database, which can be shared with the application. Requires the `sqlite` feature.
+///
+/// ## Key functionalities:
+/// - Create and migrate the schema when the database is opened.
+/// - Save, load and remove keys, and rotate them in a single transaction.
+/// - Read the audit history of every key's states.
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+/// # Rotating keys automatically according to a policy
+///
+/// This module drives a [`KeyStore`] through scheduled rotations, using an injectable
+/// [`Clock`](rotation::Clock) so that rotation schedules can be tested without waiting.
+///
+/// ## Key functionalities:
+/// - Describe how often keys rotate, how early they are published and how long they are kept.
+/// - Apply the policy at any instant, and obtain the JWKS to publish with a safe cache lifetime.
+pub mod rotation;
+
+/// The lifecycle state of a key held in a [`KeyStore`].
+///
+/// Keys move forward through `Pending` → `Active` → `Retiring` → `Retired`, and may be
+/// moved to `Revoked` from any other state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyState {
+    /// Generated, but not used yet.
+    Pending,
+
+    /// Used to sign new tokens, and published for verification.
+    Active,
+
+    /// No longer used to sign, but still published so that tokens it signed keep verifying.
+    Retiring,
+
+    /// No longer published. Kept only for the record.
+    Retired,
+
+    /// Compromised or withdrawn. Never published or used again.
+    Revoked,
+}
+
+impl KeyState {
+    /// Returns `true` if a key may move from this state to `next`.
+    pub fn can_transition_to(&self, next: KeyState) -> bool {
+        use KeyState::*;
+
+        matches!(
+            (self, next),
+            (Pending, Active)
+                | (Active, Retiring)
+                | (Pending | Active | Retiring, Retired)
+                | (Pending | Active | Retiring | Retired, Revoked)
+        )
+    }
+}
+
+/// The kind of key a [`KeyStore`] generates when rotating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAlgorithm {
+    /// RSA keys of the given size, used with `RS256`.
+    Rs256 { bits: usize },
+
+    /// P-256 keys, used with `ES256`.
+    Es256,
+}
+
+impl KeyAlgorithm {
+    /// Returns the JWA `alg` the generated keys are tagged with.
+    pub fn alg(&self) -> &'static str {
+        match self {
+            KeyAlgorithm::Rs256 { .. } => "RS256",
+            KeyAlgorithm::Es256 => "ES256",
+        }
+    }
+
+    /// Generates a new private key, tagged with `alg`, `use: "sig"` and its RFC 7638
+    /// thumbprint as `kid`.
+    pub fn generate(&self) -> Result<Jwk, JwkError> {
+        let (private_pem, _) = match self {
+            KeyAlgorithm::Rs256 { bits } => generate_rsa_keypair_pem(*bits)?,
+            KeyAlgorithm::Es256 => generate_es256_keypair_pem()?,
+        };
+
+        let mut jwk = load_key(private_pem.as_bytes())?;
+        jwk.kid = Some(jwk.thumbprint()?);
+        jwk.alg = Some(self.alg().to_string());
+        jwk.use_ = Some("sig".to_string());
+
+        Ok(jwk)
+    }
+}
+
+/// A key held in a [`KeyStore`], with its lifecycle state and timestamps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManagedKey {
+    /// The key, including its private members. Its `kid` is always set.
+    pub jwk: Jwk,
+
+    pub state: KeyState,
+
+    /// When the key was added to the store.
+    pub created_at: SystemTime,
+
+    /// When the key became (or is scheduled to become) active.
+    pub activates_at: Option<SystemTime>,
+
+    /// When the key stopped (or is scheduled to stop) being trusted for verification.
+    pub expires_at: Option<SystemTime>,
+}
+
+impl ManagedKey {
+    /// Returns the key's `kid`.
+    pub fn kid(&self) -> &str {
+        self.jwk.kid.as_deref().unwrap_or_default()
+    }
+}
+
+/// # An in-memory store of signing keys and their lifecycle.
+///
+/// The store tracks every key through the states of [`KeyState`], answers which key should
+/// sign new tokens and which keys verifiers should trust, and rotates keys by generating new
+/// ones with the `generator` module.
+///
+/// ## Example
+/// ```rust
+/// use jwk_kit::keystore::{KeyAlgorithm, KeyState, KeyStore};
+///
+/// let mut store = KeyStore::new(KeyAlgorithm::Es256);
+/// let first = store.rotate().unwrap().kid().to_string();
+/// let second = store.rotate().unwrap().kid().to_string();
+///
+/// assert_eq!(store.current_signing_key().unwrap().kid.as_deref(), Some(second.as_str()));
+/// assert_eq!(store.get(&first).unwrap().state, KeyState::Retiring);
+/// assert_eq!(store.verification_keys().keys.len(), 2);
+/// ```
+#[derive(Clone)]
+pub struct KeyStore {
+    algorithm: KeyAlgorithm,
+    keys: Vec<ManagedKey>,
+    audit: Option<Arc<dyn AuditSink>>,
+}
+
+impl fmt::Debug for KeyStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyStore")
+            .field("algorithm", &self.algorithm)
+            .field("keys", &self.keys)
+            .field("audited", &self.audit.is_some())
+            .finish()
+    }
+}
+
+impl KeyStore {
+    /// Creates an empty store that generates keys of kind `algorithm` when rotating.
+    pub fn new(algorithm: KeyAlgorithm) -> Self {
+        Self { algorithm, keys: Vec::new(), audit: None }
+    }
+
+    /// Sends a [`KeyEvent`] to `sink` for every key generated, imported, activated, retired,
+    /// revoked, exported or accessed from now on.
+    ///
+    /// Keys added with [`KeyStore::restore`] are not reported, since they are already known.
+    /// Only [`KeyStore::access_signing_key`] and [`KeyStore::export`] report reads of private
+    /// keys; [`KeyStore::keys`], [`KeyStore::get`] and [`KeyStore::current_signing_key`] do not.
+    /// Clones of the store share the sink.
+    pub fn set_audit_sink(&mut self, sink: impl AuditSink + 'static) -> &mut Self {
+        self.audit = Some(Arc::new(sink));
+        self
+    }
+
+    /// Returns the kind of key generated by [`KeyStore::rotate`].
+    pub fn algorithm(&self) -> KeyAlgorithm {
+        self.algorithm
+    }
+
+    /// Returns every key in the store, in insertion order.
+    ///
+    /// The keys include their private members, and reading them is not audited.
+    pub fn keys(&self) -> &[ManagedKey] {
+        &self.keys
+    }
+
+    /// Returns the key with the given `kid`, including its private members. Reading it is not
+    /// audited; see [`KeyStore::export`].
+    pub fn get(&self, kid: &str) -> Option<&ManagedKey> {
+        self.keys.iter().find(|key| key.kid() == kid)
+    }
+
+    /// Adds an existing key to the store in the given state.
+    ///
+    /// # Errors
+    /// - `JwkError::MissingKeyId` if the key has no `kid`.
+    /// - `JwkError::DuplicateKeyId` if the store already holds a key with the same `kid`.
+    /// - Any error returned by [`Jwk::validate`].
+    /// - Any error returned by the audit sink, in which case the key is still added.
+    pub fn insert(&mut self, jwk: Jwk, state: KeyState) -> Result<&ManagedKey, JwkError> {
+        self.insert_at(jwk, state, SystemTime::now())
+    }
+
+    /// Same as [`KeyStore::insert`], with an explicit current time.
+    pub fn insert_at(&mut self, jwk: Jwk, state: KeyState, now: SystemTime) -> Result<&ManagedKey, JwkError> {
+        self.add(jwk, state, now, false)
+    }
+
+    /// Adds a key exactly as given, with its state and timestamps, e.g. when reloading a
+    /// store from persistent storage. No audit event is recorded.
+    ///
+    /// # Errors
+    /// - `JwkError::MissingKeyId` if the key has no `kid`.
+    /// - `JwkError::DuplicateKeyId` if the store already holds a key with the same `kid`.
+    /// - Any error returned by [`Jwk::validate`].
+    pub fn restore(&mut self, key: ManagedKey) -> Result<(), JwkError> {
+        key.jwk.validate()?;
+        let kid = key.jwk.kid.clone().ok_or(JwkError::MissingKeyId)?;
+        if self.get(&kid).is_some() {
+            return Err(JwkError::DuplicateKeyId(kid));
+        }
+
+        self.keys.push(key);
+
+        Ok(())
+    }
+
+    /// Moves the key with the given `kid` to `state`.
+    ///
+    /// Becoming `Active` records `activates_at`, and becoming `Retired` or `Revoked` records
+    /// `expires_at`, unless they were already set to an earlier time.
+    ///
+    /// # Errors
+    /// - `JwkError::UnknownKeyId` if no key has this `kid`.
+    /// - `JwkError::InvalidStateTransition` if the key cannot move to `state`
+    ///   (see [`KeyState::can_transition_to`]).
+    /// - Any error returned by the audit sink, in which case the key still moves to `state`.
+    pub fn transition(&mut self, kid: &str, state: KeyState) -> Result<(), JwkError> {
+        self.transition_at(kid, state, SystemTime::now())
+    }
+
+    /// Same as [`KeyStore::transition`], with an explicit current time.
+    pub fn transition_at(&mut self, kid: &str, state: KeyState, now: SystemTime) -> Result<(), JwkError> {
+        let key = self
+            .keys
+            .iter_mut()
+            .find(|key| key.kid() == kid)
+            .ok_or_else(|| JwkError::UnknownKeyId(kid.to_string()))?;
+
+        if !key.state.can_transition_to(state) {
+            return Err(JwkError::InvalidStateTransition(format!("{:?} to {:?}", key.state, state)));
+        }
+
+        key.state = state;
+        stamp(key, state, now);
+
+        match state_event(kid, state) {
+            Some(event) => self.emit(event, now),
+            None => Ok(()),
+        }
+    }
+
+    /// Sets the scheduled activation and expiry of the key with the given `kid`, without
+    /// changing its state.
+    ///
+    /// # Errors
+    /// - `JwkError::UnknownKeyId` if no key has this `kid`.
+    pub fn schedule(
+        &mut self,
+        kid: &str,
+        activates_at: Option<SystemTime>,
+        expires_at: Option<SystemTime>,
+    ) -> Result<(), JwkError> {
+        let key = self
+            .keys
+            .iter_mut()
+            .find(|key| key.kid() == kid)
+            .ok_or_else(|| JwkError::UnknownKeyId(kid.to_string()))?;
+
+        key.activates_at = activates_at;
+        key.expires_at = expires_at;
+
+        Ok(())
+    }
+
+    /// Revokes the key with the given `kid`; it is withdrawn from every published set.
+    ///
+    /// # Errors
+    /// The same errors as [`KeyStore::transition`].
+    pub fn revoke(&mut self, kid: &str) -> Result<(), JwkError> {
+        self.transition(kid, KeyState::Revoked)
+    }
+
+    /// Generates a new key and adds it to the store in the given state.
+    ///
+    /// # Errors
+    /// Any error produced while generating the key, or returned by the audit sink.
+    pub fn generate_at(&mut self, state: KeyState, now: SystemTime) -> Result<&ManagedKey, JwkError> {
+        let jwk = self.algorithm.generate()?;
+        self.add(jwk, state, now, true)
+    }
+
+    /// Generates a new active key, and moves every previously active key to `Retiring`.
+    ///
+    /// # Returns
+    /// The new key, which is now the [current signing key](KeyStore::current_signing_key).
+    ///
+    /// # Errors
+    /// Any error produced while generating the key, or returned by the audit sink. A failing
+    /// sink does not prevent the rotation: the new key is active and the previous ones are
+    /// retiring, and the first error of the sink is returned once every event was sent.
+    pub fn rotate(&mut self) -> Result<&ManagedKey, JwkError> {
+        self.rotate_at(SystemTime::now())
+    }
+
+    /// Same as [`KeyStore::rotate`], with an explicit current time.
+    pub fn rotate_at(&mut self, now: SystemTime) -> Result<&ManagedKey, JwkError> {
+        let jwk = self.algorithm.generate()?;
+        let mut added = self.push(jwk, KeyState::Active, now, true)?;
+        let new = self.keys.len() - 1;
+
+        let mut events = Vec::new();
+        for key in self.keys[..new].iter_mut().filter(|key| key.state == KeyState::Active) {
+            key.state = KeyState::Retiring;
+            events.push(KeyEvent::Retiring { kid: key.kid().to_string() });
+        }
+        events.append(&mut added);
+        self.emit_all(events, now)?;
+
+        Ok(&self.keys[new])
+    }
+
+    /// Returns the key new tokens should be signed with: the most recently activated
+    /// `Active` key.
+    ///
+    /// Reading it is not audited; sign with [`KeyStore::access_signing_key`] when the store
+    /// has an audit sink.
+    pub fn current_signing_key(&self) -> Option<&Jwk> {
+        self.keys
+            .iter()
+            .filter(|key| key.state == KeyState::Active)
+            .max_by_key(|key| key.activates_at)
+            .map(|key| &key.jwk)
+    }
+
+    /// Returns the [current signing key](KeyStore::current_signing_key) to sign with, and
+    /// records a [`KeyEvent::PrivateKeyAccessed`] event for it.
+    ///
+    /// # Errors
+    /// - `JwkError::NoActiveKey` if the store has no active key.
+    /// - Any error returned by the audit sink.
+    pub fn access_signing_key(&self) -> Result<&Jwk, JwkError> {
+        let jwk = self.current_signing_key().ok_or(JwkError::NoActiveKey)?;
+        let kid = jwk.kid.clone().unwrap_or_default();
+        self.emit(KeyEvent::PrivateKeyAccessed { kid }, SystemTime::now())?;
+
+        Ok(jwk)
+    }
+
+    /// Returns a copy of the key with the given `kid`, including its private members, e.g. to
+    /// back it up, and records a [`KeyEvent::Exported`] event for it.
+    ///
+    /// # Errors
+    /// - `JwkError::UnknownKeyId` if no key has this `kid`.
+    /// - Any error returned by the audit sink.
+    pub fn export(&self, kid: &str) -> Result<Jwk, JwkError> {
+        let jwk = self.get(kid).ok_or_else(|| JwkError::UnknownKeyId(kid.to_string()))?.jwk.clone();
+        self.emit(KeyEvent::Exported { kid: kid.to_string() }, SystemTime::now())?;
+
+        Ok(jwk)
+    }
+
+    /// Returns the public keys verifiers should trust: every `Active` and `Retiring` key.
+    pub fn verification_keys(&self) -> Jwks {
+        Jwks {
+            keys: self
+                .keys
+                .iter()
+                .filter(|key| matches!(key.state, KeyState::Active | KeyState::Retiring))
+                .map(|key| key.jwk.to_public())
+                .collect(),
+        }
+    }
+
+    /// Returns the public keys to publish in a JWKS document: every `Active` and `Retiring`
+    /// key, and every `Pending` key with a scheduled activation.
+    ///
+    /// Unlike [`KeyStore::verification_keys`], this includes pending keys, so that caches
+    /// of the published document learn about a key before tokens signed with it appear.
+    /// Every key carries its validity as `iat` (creation), `nbf` (activation) and `exp`
+    /// (expiry) members when they are known, so that verifiers selecting keys with
+    /// [`Jwks::valid_at`] ignore a pending key until it activates. Pending keys without an
+    /// activation time (see [`KeyStore::schedule`]) would have no `nbf`, and are left out.
+    pub fn published_keys(&self) -> Jwks {
+        Jwks {
+            keys: self
+                .keys
+                .iter()
+                .filter(|key| match key.state {
+                    KeyState::Pending => key.activates_at.is_some(),
+                    KeyState::Active | KeyState::Retiring => true,
+                    KeyState::Retired | KeyState::Revoked => false,
+                })
+                .map(|key| {
+                    let mut jwk = key.jwk.to_public();
+                    jwk.set_issued_at(Some(key.created_at))
+                        .set_not_before(key.activates_at)
+                        .set_expires_at(key.expires_at);
+                    jwk
+                })
+                .collect(),
+        }
+    }
+
+    fn add(&mut self, jwk: Jwk, state: KeyState, now: SystemTime, generated: bool) -> Result<&ManagedKey, JwkError> {
+        let events = self.push(jwk, state, now, generated)?;
+        self.emit_all(events, now)?;
+
+        Ok(self.keys.last().expect("key was just pushed"))
+    }
+
+    /// Adds a key without reporting it, and returns the events to report.
+    fn push(&mut self, jwk: Jwk, state: KeyState, now: SystemTime, generated: bool) -> Result<Vec<KeyEvent>, JwkError> {
+        jwk.validate()?;
+        let kid = jwk.kid.clone().ok_or(JwkError::MissingKeyId)?;
+        if self.get(&kid).is_some() {
+            return Err(JwkError::DuplicateKeyId(kid));
+        }
+
+        let added = if generated {
+            KeyEvent::Generated { kid: kid.clone(), alg: jwk.alg.clone().unwrap_or_default() }
+        } else {
+            KeyEvent::Imported { kid: kid.clone() }
+        };
+
+        let mut key = ManagedKey { jwk, state, created_at: now, activates_at: None, expires_at: None };
+        stamp(&mut key, state, now);
+        self.keys.push(key);
+
+        Ok([Some(added), state_event(&kid, state)].into_iter().flatten().collect())
+    }
+
+    /// Sends every event to the sink, even after a failure, and returns the first error.
+    fn emit_all(&self, events: Vec<KeyEvent>, at: SystemTime) -> Result<(), JwkError> {
+        let mut result = Ok(());
+        for event in events {
+            result = result.and(self.emit(event, at));
+        }
+        result
+    }
+
+    fn emit(&self, event: KeyEvent, at: SystemTime) -> Result<(), JwkError> {
+        match &self.audit {
+            Some(sink) => sink.record(&AuditRecord { at, event }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the event reported when a key enters `state`.
+fn state_event(kid: &str, state: KeyState) -> Option<KeyEvent> {
+    let kid = kid.to_string();
+    match state {
+        KeyState::Pending => None,
+        KeyState::Active => Some(KeyEvent::Activated { kid }),
+        KeyState::Retiring => Some(KeyEvent::Retiring { kid }),
+        KeyState::Retired => Some(KeyEvent::Retired { kid }),
+        KeyState::Revoked => Some(KeyEvent::Revoked { kid }),
+    }
+}
+
+/// Records the timestamps implied by a key entering `state` at `now`.
+fn stamp(key: &mut ManagedKey, state: KeyState, now: SystemTime) {
+    match state {
+        KeyState::Active => {
+            key.activates_at = Some(key.activates_at.map_or(now, |at| at.min(now)));
+        }
+        KeyState::Retired | KeyState::Revoked => {
+            key.expires_at = Some(key.expires_at.map_or(now, |at| at.min(now)));
+        }
+        KeyState::Pending | KeyState::Retiring => {}
+    }
+}
@@ -0,0 +1,340 @@
+use crate::error::JwkError;
+use crate::jwk::Jwks;
+use crate::keystore::{KeyState, KeyStore};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// A source of the current time.
+///
+/// The rotation driver reads the time only through this trait, so tests can substitute a
+/// [`FakeClock`] for the [`SystemClock`].
+pub trait Clock {
+    fn now(&self) -> SystemTime;
+}
+
+/// The real wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock that only moves when told to.
+#[derive(Debug)]
+pub struct FakeClock {
+    now: Mutex<SystemTime>,
+}
+
+impl FakeClock {
+    /// Creates a clock stopped at `start`.
+    pub fn new(start: SystemTime) -> Self {
+        Self { now: Mutex::new(start) }
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) += duration;
+    }
+
+    /// Sets the clock to `now`.
+    pub fn set(&self, now: SystemTime) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) = now;
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> SystemTime {
+        (**self).now()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> SystemTime {
+        (**self).now()
+    }
+}
+
+/// When keys are rotated, published and dropped.
+///
+/// A duration too long to be added to the current time, such as `Duration::MAX`, means
+/// "never": keys are then never rotated, or retiring keys never retired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotationPolicy {
+    /// How long each key stays the active signing key.
+    pub rotate_every: Duration,
+
+    /// How long before its activation a new key is generated and published, so that cached
+    /// copies of the JWKS already contain it when it starts signing.
+    pub publish_ahead: Duration,
+
+    /// How long a key stays published after it stops signing. This should be at least the
+    /// maximum lifetime of the tokens it signed.
+    pub keep_retired_for: Duration,
+
+    /// The longest time a JWKS may be cached, so that revocations and keys added outside of
+    /// the driver reach relying parties in bounded time. [`RotationPolicy::new`] sets one day.
+    pub max_cache_age: Duration,
+}
+
+impl RotationPolicy {
+    pub fn new(rotate_every: Duration, publish_ahead: Duration, keep_retired_for: Duration) -> Self {
+        Self { rotate_every, publish_ahead, keep_retired_for, max_cache_age: Duration::from_secs(24 * 60 * 60) }
+    }
+}
+
+/// What to publish after a [`RotationDriver::tick`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RotationStep {
+    /// The JWKS to publish (see [`KeyStore::published_keys`]).
+    pub jwks: Jwks,
+
+    /// How long the JWKS may be cached: until the next key that is not part of `jwks` yet
+    /// is published, so that every cached copy contains it before it becomes active, and at
+    /// most [`RotationPolicy::max_cache_age`].
+    pub max_age: Duration,
+
+    /// The instant `max_age` runs out, to compute the remaining `max_age` of later responses.
+    pub fresh_until: SystemTime,
+
+    /// When the driver next has something to do.
+    pub next_tick_at: SystemTime,
+
+    /// Whether this tick changed the key store.
+    pub changed: bool,
+}
+
+impl RotationStep {
+    /// Returns a `Cache-Control` header value for `jwks`, e.g. `public, max-age=3600`.
+    pub fn cache_control(&self) -> String {
+        format!("public, max-age={}", self.max_age.as_secs())
+    }
+}
+
+/// # Applies a [`RotationPolicy`] to a [`KeyStore`].
+///
+/// Each call to [`RotationDriver::tick`] performs whatever the policy requires at the
+/// current time of the clock:
+/// - generate the first key if the store has no usable key,
+/// - generate and publish the next key `publish_ahead` before the active key is due for rotation,
+/// - activate pending keys whose activation time has come, moving the previous key to `Retiring`,
+/// - retire keys that have been retiring for `keep_retired_for`.
+///
+/// ## Example
+/// ```rust
+/// use std::time::{Duration, SystemTime};
+/// use jwk_kit::keystore::rotation::{FakeClock, RotationDriver, RotationPolicy};
+/// use jwk_kit::keystore::{KeyAlgorithm, KeyStore};
+///
+/// const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+///
+/// let policy = RotationPolicy::new(30 * DAY, DAY, 7 * DAY);
+/// let clock = FakeClock::new(SystemTime::UNIX_EPOCH + 20_000 * DAY);
+/// let mut driver = RotationDriver::new(KeyStore::new(KeyAlgorithm::Es256), policy, clock);
+///
+/// let step = driver.tick().unwrap();
+/// assert_eq!(step.jwks.keys.len(), 1);
+/// assert_eq!(step.cache_control(), "public, max-age=86400");
+///
+/// driver.clock().advance(29 * DAY);
+/// assert_eq!(driver.tick().unwrap().jwks.keys.len(), 2);
+/// ```
+#[derive(Debug)]
+pub struct RotationDriver<C: Clock> {
+    store: KeyStore,
+    policy: RotationPolicy,
+    clock: C,
+}
+
+impl<C: Clock> RotationDriver<C> {
+    pub fn new(store: KeyStore, policy: RotationPolicy, clock: C) -> Self {
+        Self { store, policy, clock }
+    }
+
+    pub fn store(&self) -> &KeyStore {
+        &self.store
+    }
+
+    /// Gives access to the store, e.g. to revoke a key.
+    pub fn store_mut(&mut self) -> &mut KeyStore {
+        &mut self.store
+    }
+
+    pub fn into_store(self) -> KeyStore {
+        self.store
+    }
+
+    pub fn policy(&self) -> &RotationPolicy {
+        &self.policy
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Applies the policy at the current time of the clock.
+    ///
+    /// # Returns
+    /// The JWKS to publish, how long it may be cached, and when to tick next.
+    ///
+    /// # Errors
+    /// Any error produced while generating a key. The store is left consistent: the actions
+    /// performed before the error are kept.
+    pub fn tick(&mut self) -> Result<RotationStep, JwkError> {
+        let now = self.clock.now();
+        let mut changed = false;
+
+        // Activate due pending keys, or the earliest pending key if nothing is active.
+        let mut due: Vec<String> = self
+            .pending_keys()
+            .into_iter()
+            .filter(|(_, activates_at)| *activates_at <= now)
+            .map(|(kid, _)| kid)
+            .collect();
+        if due.is_empty() && self.active_activation().is_none() {
+            due.extend(self.pending_keys().into_iter().map(|(kid, _)| kid).take(1));
+        }
+        for kid in due {
+            self.activate(&kid, now)?;
+            changed = true;
+        }
+
+        // Bootstrap an empty store, and prepare the next key ahead of rotation.
+        match self.active_activation() {
+            None => {
+                self.store.generate_at(KeyState::Active, now)?;
+                changed = true;
+            }
+            Some(activated_at) if self.pending_keys().is_empty() => {
+                if let Some(rotation_at) = activated_at.checked_add(self.policy.rotate_every)
+                    && now.checked_add(self.policy.publish_ahead).is_none_or(|publish_until| publish_until >= rotation_at)
+                {
+                    let kid = self.store.generate_at(KeyState::Pending, now)?.kid().to_string();
+                    if rotation_at <= now {
+                        self.activate(&kid, now)?;
+                    } else {
+                        self.store.schedule(&kid, Some(rotation_at), None)?;
+                    }
+                    changed = true;
+                }
+            }
+            Some(_) => {}
+        }
+
+        // Retire keys once tokens they signed can no longer be valid. Keys moved to
+        // `Retiring` outside of the driver start their retention period now.
+        let retiring: Vec<(String, Option<SystemTime>, Option<SystemTime>)> = self
+            .store
+            .keys()
+            .iter()
+            .filter(|key| key.state == KeyState::Retiring)
+            .map(|key| (key.kid().to_string(), key.activates_at, key.expires_at))
+            .collect();
+        for (kid, activates_at, expires_at) in retiring {
+            match expires_at {
+                None => match now.checked_add(self.policy.keep_retired_for) {
+                    Some(retire_at) => self.store.schedule(&kid, activates_at, Some(retire_at))?,
+                    None => continue,
+                },
+                Some(at) if at <= now => self.store.transition_at(&kid, KeyState::Retired, now)?,
+                Some(_) => continue,
+            }
+            changed = true;
+        }
+
+        Ok(self.step(now, changed))
+    }
+
+    fn step(&self, now: SystemTime, changed: bool) -> RotationStep {
+        let next_activation = match (self.pending_keys().last(), self.active_activation()) {
+            (Some((_, activates_at)), _) => Some(activates_at.checked_add(self.policy.rotate_every)),
+            (None, Some(activated_at)) => Some(activated_at.checked_add(self.policy.rotate_every)),
+            (None, None) => None,
+        };
+        let max_age = match next_activation {
+            None => Duration::ZERO,
+            // The next rotation never happens.
+            Some(None) => self.policy.max_cache_age,
+            Some(Some(at)) => at.checked_sub(self.policy.publish_ahead).unwrap_or(now).duration_since(now).unwrap_or_default(),
+        }
+        .min(self.policy.max_cache_age);
+
+        let mut events: Vec<SystemTime> = self
+            .store
+            .keys()
+            .iter()
+            .filter_map(|key| match key.state {
+                KeyState::Pending => key.activates_at,
+                KeyState::Retiring => key.expires_at,
+                _ => None,
+            })
+            .collect();
+        if let (true, Some(activated_at)) = (self.pending_keys().is_empty(), self.active_activation())
+            && let Some(rotation_at) = activated_at.checked_add(self.policy.rotate_every)
+        {
+            events.push(rotation_at.checked_sub(self.policy.publish_ahead).unwrap_or(now));
+        }
+        let next_tick_at = events.into_iter().filter(|at| *at > now).min().unwrap_or(now);
+
+        RotationStep {
+            jwks: self.store.published_keys(),
+            max_age,
+            fresh_until: now.checked_add(max_age).unwrap_or(now),
+            next_tick_at,
+            changed,
+        }
+    }
+
+    /// Activates `kid`, moving every other active key to `Retiring` for the retention period.
+    fn activate(&mut self, kid: &str, now: SystemTime) -> Result<(), JwkError> {
+        let previous: Vec<(String, Option<SystemTime>)> = self
+            .store
+            .keys()
+            .iter()
+            .filter(|key| key.state == KeyState::Active)
+            .map(|key| (key.kid().to_string(), key.activates_at))
+            .collect();
+
+        // A failing audit sink must not leave the store without an active key: apply every
+        // transition, then report the first error.
+        let mut audited = Ok(());
+        for (previous_kid, activated_at) in previous {
+            audited = audited.and(self.store.transition_at(&previous_kid, KeyState::Retiring, now));
+            self.store.schedule(&previous_kid, activated_at, now.checked_add(self.policy.keep_retired_for))?;
+        }
+
+        audited.and(self.store.transition_at(kid, KeyState::Active, now))
+    }
+
+    /// Returns the `kid` and scheduled activation of every pending key, earliest first.
+    /// Pending keys without a scheduled activation are due immediately.
+    fn pending_keys(&self) -> Vec<(String, SystemTime)> {
+        let mut pending: Vec<(String, SystemTime)> = self
+            .store
+            .keys()
+            .iter()
+            .filter(|key| key.state == KeyState::Pending)
+            .map(|key| (key.kid().to_string(), key.activates_at.unwrap_or(SystemTime::UNIX_EPOCH)))
+            .collect();
+        pending.sort_by_key(|(_, activates_at)| *activates_at);
+        pending
+    }
+
+    /// Returns when the current signing key became active.
+    fn active_activation(&self) -> Option<SystemTime> {
+        self.store
+            .keys()
+            .iter()
+            .filter(|key| key.state == KeyState::Active)
+            .filter_map(|key| key.activates_at)
+            .max()
+    }
+}
@@ -0,0 +1,520 @@
+use crate::error::JwkError;
+use crate::jwk::{Jwk, from_unix_seconds, unix_seconds};
+use crate::keystore::audit::{AuditRecord, AuditSink, KeyEvent};
+use crate::keystore::envelope::{KeyWrapper, SealedKey};
+use crate::keystore::{KeyAlgorithm, KeyState, KeyStore, ManagedKey};
+use rusqlite::{Connection, OptionalExtension, Transaction, TransactionBehavior, params};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// The schema migrations, applied in order. The schema version is the number of applied
+/// migrations; released migrations must never be edited, only appended to.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE jwk_kit_keys (
+        kid TEXT PRIMARY KEY NOT NULL,
+        jwk TEXT NOT NULL,
+        state TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        activates_at INTEGER,
+        expires_at INTEGER
+    );
+    CREATE TABLE jwk_kit_key_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        kid TEXT NOT NULL,
+        from_state TEXT,
+        to_state TEXT NOT NULL,
+        at INTEGER NOT NULL
+    );
+    CREATE INDEX jwk_kit_key_history_kid ON jwk_kit_key_history (kid, id);",
+    "ALTER TABLE jwk_kit_keys ADD COLUMN sealed TEXT;",
+];
+
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A change of state of a key, as recorded in the audit history of a [`SqliteKeyStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyHistoryEntry {
+    pub kid: String,
+
+    /// The previous state, or `None` when the key was added.
+    pub from: Option<KeyState>,
+
+    pub to: KeyState,
+
+    /// When the change happened: the time given to the [`KeyStore`] operation that made it
+    /// within [`SqliteKeyStore::update`], or the time of the save otherwise.
+    pub at: SystemTime,
+}
+
+/// # A key store persisted in a SQLite database.
+///
+/// Keys are stored with their private members, states and timestamps in the `jwk_kit_keys`
+/// table, and every change of state is appended to `jwk_kit_key_history`. All tables are
+/// prefixed with `jwk_kit_`, so the store can live in a database the application already uses;
+/// the schema is created and migrated when the store is opened.
+///
+/// [`SqliteKeyStore::update`] and [`SqliteKeyStore::rotate`] load, modify and save the keys in
+/// a single transaction, so concurrent processes rotating the same database cannot lose
+/// each other's keys.
+///
+/// Timestamps are stored with a precision of one second.
+///
+/// A store opened with [`SqliteKeyStore::open_encrypted`], or given a wrapper with
+/// [`SqliteKeyStore::set_key_wrapper`], seals every private key with envelope encryption
+/// (see [`SealedKey`]); only the public members of the keys are stored in clear.
+///
+/// ## Example
+/// ```rust
+/// use jwk_kit::keystore::sqlite::SqliteKeyStore;
+/// use jwk_kit::keystore::{KeyAlgorithm, KeyState};
+///
+/// let mut db = SqliteKeyStore::open_in_memory().unwrap();
+/// let first = db.rotate(KeyAlgorithm::Es256).unwrap().kid().to_string();
+/// let second = db.rotate(KeyAlgorithm::Es256).unwrap().kid().to_string();
+///
+/// let store = db.load_store(KeyAlgorithm::Es256).unwrap();
+/// assert_eq!(store.current_signing_key().unwrap().kid.as_deref(), Some(second.as_str()));
+///
+/// let history = db.history(&first).unwrap();
+/// assert_eq!(history.iter().map(|entry| entry.to).collect::<Vec<_>>(), [KeyState::Active, KeyState::Retiring]);
+/// ```
+pub struct SqliteKeyStore {
+    conn: Connection,
+    wrapper: Option<Arc<dyn KeyWrapper>>,
+}
+
+impl fmt::Debug for SqliteKeyStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SqliteKeyStore")
+            .field("conn", &self.conn)
+            .field("kek", &self.wrapper.as_ref().map(|wrapper| wrapper.key_id()))
+            .finish()
+    }
+}
+
+impl SqliteKeyStore {
+    /// Opens (or creates) the database at `path` and migrates its schema.
+    ///
+    /// # Errors
+    /// - `JwkError::KeyStoreDatabase` if the database cannot be opened or migrated.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, JwkError> {
+        Self::from_connection(Connection::open(path).map_err(db_error)?)
+    }
+
+    /// Opens the database at `path` like [`SqliteKeyStore::open`], sealing the private keys it
+    /// writes under the KEK of `wrapper`.
+    ///
+    /// Keys previously written in clear can still be read, and are sealed the next time they
+    /// are saved.
+    ///
+    /// # Errors
+    /// The same errors as [`SqliteKeyStore::open`].
+    pub fn open_encrypted(path: impl AsRef<Path>, wrapper: impl KeyWrapper + 'static) -> Result<Self, JwkError> {
+        let mut store = Self::open(path)?;
+        store.set_key_wrapper(wrapper);
+        Ok(store)
+    }
+
+    /// Opens a private in-memory database, e.g. for tests.
+    ///
+    /// # Errors
+    /// The same errors as [`SqliteKeyStore::open`].
+    pub fn open_in_memory() -> Result<Self, JwkError> {
+        Self::from_connection(Connection::open_in_memory().map_err(db_error)?)
+    }
+
+    /// Uses an existing connection, migrating the schema of its database.
+    ///
+    /// # Errors
+    /// The same errors as [`SqliteKeyStore::open`].
+    pub fn from_connection(mut conn: Connection) -> Result<Self, JwkError> {
+        conn.busy_timeout(BUSY_TIMEOUT).map_err(db_error)?;
+        migrate(&mut conn)?;
+        Ok(Self { conn, wrapper: None })
+    }
+
+    /// Seals the private keys written from now on under the KEK of `wrapper`, and uses it to
+    /// open sealed keys.
+    pub fn set_key_wrapper(&mut self, wrapper: impl KeyWrapper + 'static) -> &mut Self {
+        self.wrapper = Some(Arc::new(wrapper));
+        self
+    }
+
+    /// Returns the connection, e.g. to run the application's own queries.
+    pub fn into_connection(self) -> Connection {
+        self.conn
+    }
+
+    /// Returns the version of the schema, i.e. the number of applied migrations.
+    ///
+    /// # Errors
+    /// - `JwkError::KeyStoreDatabase` if the query fails.
+    pub fn schema_version(&self) -> Result<usize, JwkError> {
+        schema_version(&self.conn)
+    }
+
+    /// Writes `key`, replacing any previous version of it, and records its state in the history
+    /// if it changed.
+    ///
+    /// # Errors
+    /// - `JwkError::MissingKeyId` if the key has no `kid`.
+    /// - `JwkError::KeyWrapError` if the private key cannot be sealed.
+    /// - `JwkError::KeyStoreDatabase` if the key cannot be written.
+    pub fn save(&mut self, key: &ManagedKey) -> Result<(), JwkError> {
+        let wrapper = self.wrapper.clone();
+        let tx = self.transaction()?;
+        write_key(&tx, key, wrapper.as_deref(), SystemTime::now())?;
+        tx.commit().map_err(db_error)
+    }
+
+    /// Writes every key of `store` in a single transaction.
+    ///
+    /// Keys that are no longer in `store` are left untouched; see [`SqliteKeyStore::remove`].
+    ///
+    /// # Errors
+    /// The same errors as [`SqliteKeyStore::save`]. Nothing is written if any key fails.
+    pub fn save_all(&mut self, store: &KeyStore) -> Result<(), JwkError> {
+        let wrapper = self.wrapper.clone();
+        let tx = self.transaction()?;
+        write_store(&tx, store, wrapper.as_deref(), &HashMap::new(), SystemTime::now())?;
+        tx.commit().map_err(db_error)
+    }
+
+    /// Deletes the key with the given `kid`. Its history is kept.
+    ///
+    /// # Errors
+    /// - `JwkError::UnknownKeyId` if there is no such key.
+    /// - `JwkError::KeyStoreDatabase` if the key cannot be deleted.
+    pub fn remove(&mut self, kid: &str) -> Result<(), JwkError> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM jwk_kit_keys WHERE kid = ?1", params![kid])
+            .map_err(db_error)?;
+
+        if deleted == 0 {
+            return Err(JwkError::UnknownKeyId(kid.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Reads the key with the given `kid`.
+    ///
+    /// # Errors
+    /// - `JwkError::UnknownKeyId` if there is no such key.
+    /// - `JwkError::KeyStoreDatabase` if the query fails or a row is invalid.
+    /// - `JwkError::KeyWrapError` if the key is sealed and cannot be decrypted with the
+    ///   store's wrapper.
+    pub fn load(&self, kid: &str) -> Result<ManagedKey, JwkError> {
+        read_keys(&self.conn, Some(kid), self.wrapper.as_deref())?
+            .pop()
+            .ok_or_else(|| JwkError::UnknownKeyId(kid.to_string()))
+    }
+
+    /// Reads every key, ordered by creation time.
+    ///
+    /// # Errors
+    /// - `JwkError::KeyStoreDatabase` if the query fails or a row is invalid.
+    /// - `JwkError::KeyWrapError` if a sealed key cannot be decrypted with the store's wrapper.
+    pub fn load_all(&self) -> Result<Vec<ManagedKey>, JwkError> {
+        read_keys(&self.conn, None, self.wrapper.as_deref())
+    }
+
+    /// Reads every key into a new [`KeyStore`] that generates keys of kind `algorithm` when
+    /// rotating.
+    ///
+    /// # Errors
+    /// The same errors as [`SqliteKeyStore::load_all`], and those of [`KeyStore::restore`].
+    pub fn load_store(&self, algorithm: KeyAlgorithm) -> Result<KeyStore, JwkError> {
+        read_store(&self.conn, algorithm, self.wrapper.as_deref())
+    }
+
+    /// Loads the keys, applies `f` to them and saves the result, in a single transaction
+    /// that holds the database's write lock throughout.
+    ///
+    /// # Returns
+    /// The value returned by `f`.
+    ///
+    /// # Errors
+    /// Any error returned by `f`, or produced while loading or saving the keys. Nothing is
+    /// written in that case.
+    pub fn update<T>(
+        &mut self,
+        algorithm: KeyAlgorithm,
+        f: impl FnOnce(&mut KeyStore) -> Result<T, JwkError>,
+    ) -> Result<T, JwkError> {
+        self.update_at(algorithm, SystemTime::now(), f)
+    }
+
+    /// Generates a new active key and retires the current one, as [`KeyStore::rotate`] does,
+    /// in a single transaction.
+    ///
+    /// # Returns
+    /// The new key.
+    ///
+    /// # Errors
+    /// The same errors as [`SqliteKeyStore::update`].
+    pub fn rotate(&mut self, algorithm: KeyAlgorithm) -> Result<ManagedKey, JwkError> {
+        self.rotate_at(algorithm, SystemTime::now())
+    }
+
+    /// Same as [`SqliteKeyStore::rotate`], with an explicit current time.
+    pub fn rotate_at(&mut self, algorithm: KeyAlgorithm, now: SystemTime) -> Result<ManagedKey, JwkError> {
+        self.update_at(algorithm, now, |store| store.rotate_at(now).cloned())
+    }
+
+    /// Returns the recorded changes of state of the key with the given `kid`, oldest first.
+    ///
+    /// # Errors
+    /// - `JwkError::KeyStoreDatabase` if the query fails or a row is invalid.
+    pub fn history(&self, kid: &str) -> Result<Vec<KeyHistoryEntry>, JwkError> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT from_state, to_state, at FROM jwk_kit_key_history WHERE kid = ?1 ORDER BY id")
+            .map_err(db_error)?;
+        let rows = statement
+            .query_map(params![kid], |row| {
+                Ok((row.get::<_, Option<String>>(0)?, row.get::<_, String>(1)?, row.get::<_, u64>(2)?))
+            })
+            .map_err(db_error)?;
+
+        rows.map(|row| {
+            let (from, to, at) = row.map_err(db_error)?;
+            Ok(KeyHistoryEntry {
+                kid: kid.to_string(),
+                from: from.as_deref().map(parse_state).transpose()?,
+                to: parse_state(&to)?,
+                at: timestamp(at)?,
+            })
+        })
+        .collect()
+    }
+
+    fn update_at<T>(
+        &mut self,
+        algorithm: KeyAlgorithm,
+        now: SystemTime,
+        f: impl FnOnce(&mut KeyStore) -> Result<T, JwkError>,
+    ) -> Result<T, JwkError> {
+        let wrapper = self.wrapper.clone();
+        let tx = self.transaction()?;
+        let mut store = read_store(&tx, algorithm, wrapper.as_deref())?;
+        let transitions = Arc::new(TransitionTimes::default());
+        store.set_audit_sink(transitions.clone());
+        let value = f(&mut store)?;
+        let changed_at = std::mem::take(&mut *transitions.0.lock().unwrap_or_else(|e| e.into_inner()));
+        write_store(&tx, &store, wrapper.as_deref(), &changed_at, now)?;
+        tx.commit().map_err(db_error)?;
+
+        Ok(value)
+    }
+
+    /// Starts a transaction that takes the write lock immediately, so that a concurrent
+    /// writer waits instead of failing when it tries to upgrade its lock.
+    fn transaction(&mut self) -> Result<Transaction<'_>, JwkError> {
+        self.conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .map_err(db_error)
+    }
+}
+
+/// Records when each key last changed state during an update, from the events of the store.
+#[derive(Default)]
+struct TransitionTimes(Mutex<HashMap<String, SystemTime>>);
+
+impl AuditSink for TransitionTimes {
+    fn record(&self, record: &AuditRecord) -> Result<(), JwkError> {
+        if !matches!(record.event, KeyEvent::Exported { .. } | KeyEvent::PrivateKeyAccessed { .. }) {
+            let mut times = self.0.lock().unwrap_or_else(|e| e.into_inner());
+            times.insert(record.event.kid().to_string(), record.at);
+        }
+        Ok(())
+    }
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), JwkError> {
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(db_error)?;
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS jwk_kit_migrations (
+            version INTEGER PRIMARY KEY NOT NULL,
+            applied_at INTEGER NOT NULL
+        );",
+    )
+    .map_err(db_error)?;
+
+    let version = schema_version(&tx)?;
+    if version > MIGRATIONS.len() {
+        return Err(JwkError::KeyStoreDatabase(format!(
+            "schema version {} is newer than the supported version {}",
+            version,
+            MIGRATIONS.len()
+        )));
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        tx.execute_batch(migration).map_err(db_error)?;
+        tx.execute(
+            "INSERT INTO jwk_kit_migrations (version, applied_at) VALUES (?1, ?2)",
+            params![index + 1, unix_seconds(SystemTime::now())],
+        )
+        .map_err(db_error)?;
+    }
+
+    tx.commit().map_err(db_error)
+}
+
+fn schema_version(conn: &Connection) -> Result<usize, JwkError> {
+    conn.query_row("SELECT COALESCE(MAX(version), 0) FROM jwk_kit_migrations", [], |row| row.get(0))
+        .map_err(db_error)
+}
+
+fn read_keys(conn: &Connection, kid: Option<&str>, wrapper: Option<&dyn KeyWrapper>) -> Result<Vec<ManagedKey>, JwkError> {
+    let mut statement = conn
+        .prepare(
+            "SELECT jwk, state, created_at, activates_at, expires_at, sealed FROM jwk_kit_keys
+             WHERE ?1 IS NULL OR kid = ?1 ORDER BY created_at, kid",
+        )
+        .map_err(db_error)?;
+    let rows = statement
+        .query_map(params![kid], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u64>(2)?,
+                row.get::<_, Option<u64>>(3)?,
+                row.get::<_, Option<u64>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+            ))
+        })
+        .map_err(db_error)?;
+
+    rows.map(|row| {
+        let (jwk, state, created_at, activates_at, expires_at, sealed) = row.map_err(db_error)?;
+        let jwk: Jwk = serde_json::from_str(&jwk).map_err(|e| JwkError::KeyStoreDatabase(e.to_string()))?;
+        let jwk = match (sealed, wrapper) {
+            (None, _) => jwk,
+            (Some(sealed), wrapper) => {
+                let sealed: SealedKey =
+                    serde_json::from_str(&sealed).map_err(|e| JwkError::KeyStoreDatabase(e.to_string()))?;
+                let kid = jwk.kid.as_deref().ok_or(JwkError::MissingKeyId)?;
+                match wrapper {
+                    Some(wrapper) => sealed.open(kid, wrapper)?,
+                    None => {
+                        return Err(JwkError::KeyWrapError(format!(
+                            "{} is sealed under '{}'; open the store with a key wrapper",
+                            kid, sealed.kek
+                        )));
+                    }
+                }
+            }
+        };
+        Ok(ManagedKey {
+            jwk,
+            state: parse_state(&state)?,
+            created_at: timestamp(created_at)?,
+            activates_at: activates_at.map(timestamp).transpose()?,
+            expires_at: expires_at.map(timestamp).transpose()?,
+        })
+    })
+    .collect()
+}
+
+fn read_store(conn: &Connection, algorithm: KeyAlgorithm, wrapper: Option<&dyn KeyWrapper>) -> Result<KeyStore, JwkError> {
+    let mut store = KeyStore::new(algorithm);
+    for key in read_keys(conn, None, wrapper)? {
+        store.restore(key)?;
+    }
+    Ok(store)
+}
+
+/// Writes every key of `store`. A change of state is recorded at the time found in
+/// `changed_at` for its `kid`, or at `now`.
+fn write_store(
+    tx: &Transaction<'_>,
+    store: &KeyStore,
+    wrapper: Option<&dyn KeyWrapper>,
+    changed_at: &HashMap<String, SystemTime>,
+    now: SystemTime,
+) -> Result<(), JwkError> {
+    store
+        .keys()
+        .iter()
+        .try_for_each(|key| write_key(tx, key, wrapper, changed_at.get(key.kid()).copied().unwrap_or(now)))
+}
+
+fn write_key(tx: &Transaction<'_>, key: &ManagedKey, wrapper: Option<&dyn KeyWrapper>, changed_at: SystemTime) -> Result<(), JwkError> {
+    let kid = key.jwk.kid.as_deref().ok_or(JwkError::MissingKeyId)?;
+    let (jwk, sealed) = match wrapper {
+        Some(wrapper) if key.jwk.is_private() => {
+            let sealed = SealedKey::seal(&key.jwk, wrapper)?;
+            (key.jwk.to_public(), Some(serde_json::to_string(&sealed).map_err(|e| JwkError::InvalidJson(e.to_string()))?))
+        }
+        _ => (key.jwk.clone(), None),
+    };
+    let jwk = serde_json::to_string(&jwk).map_err(|e| JwkError::InvalidJson(e.to_string()))?;
+    let state = state_name(key.state);
+
+    let previous: Option<String> = tx
+        .query_row("SELECT state FROM jwk_kit_keys WHERE kid = ?1", params![kid], |row| row.get(0))
+        .optional()
+        .map_err(db_error)?;
+
+    tx.execute(
+        "INSERT INTO jwk_kit_keys (kid, jwk, state, created_at, activates_at, expires_at, sealed)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT (kid) DO UPDATE SET
+             jwk = excluded.jwk,
+             state = excluded.state,
+             created_at = excluded.created_at,
+             activates_at = excluded.activates_at,
+             expires_at = excluded.expires_at,
+             sealed = excluded.sealed",
+        params![
+            kid,
+            jwk,
+            state,
+            unix_seconds(key.created_at),
+            key.activates_at.map(unix_seconds),
+            key.expires_at.map(unix_seconds),
+            sealed
+        ],
+    )
+    .map_err(db_error)?;
+
+    if previous.as_deref() != Some(state) {
+        tx.execute(
+            "INSERT INTO jwk_kit_key_history (kid, from_state, to_state, at) VALUES (?1, ?2, ?3, ?4)",
+            params![kid, previous, state, unix_seconds(changed_at)],
+        )
+        .map_err(db_error)?;
+    }
+
+    Ok(())
+}
+
+fn state_name(state: KeyState) -> &'static str {
+    match state {
+        KeyState::Pending => "pending",
+        KeyState::Active => "active",
+        KeyState::Retiring => "retiring",
+        KeyState::Retired => "retired",
+        KeyState::Revoked => "revoked",
+    }
+}
+
+fn parse_state(name: &str) -> Result<KeyState, JwkError> {
+    serde_json::from_value(serde_json::Value::String(name.to_string()))
+        .map_err(|_| JwkError::KeyStoreDatabase(format!("invalid key state '{}'", name)))
+}
+
+fn timestamp(seconds: u64) -> Result<SystemTime, JwkError> {
+    from_unix_seconds(seconds).ok_or_else(|| JwkError::KeyStoreDatabase(format!("timestamp {} is out of range", seconds)))
+}
+
+fn db_error(error: rusqlite::Error) -> JwkError {
+    JwkError::KeyStoreDatabase(error.to_string())
+}
@@ -42,7 +42,7 @@
 //!
 //! - **Native Rust API** — No need for external shell commands or subprocess management.
 //! - **Key Generation and Management** — Easily generate RSA (2048/4096 bits) and ES256 (P-256) key pairs, and convert them into JWK format.
-//! - **Base64URL-safe encoding** (no padding) and export keys in PEM (PKCS#8) format.
+//! - **Base64URL-safe encoding** (no padding) and export keys in PEM (PKCS#8, PKCS#1 or SEC1) format.
 //! - **Web Standards Integration** — Ideal for use with JSON Web Tokens (JWT), OAuth2, and OpenID Connect.
 //! - **Testing and Dynamic Key Rotation** — Useful for key management in secure applications, including key rotation for compliance.
 //! - **Lightweight and Ergonomic** — Minimal dependencies, built with ergonomics in mind, and simple to use in your Rust projects.
@@ -223,3 +223,141 @@ pub mod error;
 /// - ECDSA keypair generation and parsing (supports P-256 for ES256).
 /// - Functions for exporting keys to PEM (PKCS#8) format.
 pub mod generator;
+
+/// # Loading keys from any supported format
+///
+/// This module provides a single entry point for turning key material into `Jwk`s without
+/// knowing its format in advance. It detects PEM, DER, JWK and JWKS JSON, and OpenSSH public
+/// keys, and returns normalized `Jwk`s.
+///
+/// ## Key functionalities:
+/// - Load a single key with `load_key`.
+/// - Load every key from a multi-block PEM file, a JWKS or an `authorized_keys` file with `load_keys`.
+/// - Load every key file in a directory into a `Jwks` with `load_dir`.
+pub mod loader;
+
+/// # Reloading keys when a directory changes
+///
+/// This module keeps an in-memory `Jwks` in sync with a directory of key files, such as a
+/// mounted Kubernetes secret, so that rotated keys are picked up without a restart.
+///
+/// ## Key functionalities:
+/// - Poll a directory and swap in the new keys atomically when its files change.
+/// - Validate the whole directory before swapping, keeping the current keys on error.
+/// - Report files that cannot be loaded to an error handler.
+pub mod watch;
+
+/// # Converting between JWK and OpenSSH key formats
+///
+/// This module converts `Jwk`s to and from the key formats used by OpenSSH, so that SSH keys
+/// and JWT signing keys can be managed with the same tooling.
+///
+/// ## Key functionalities:
+/// - Read and write `authorized_keys` lines for `ssh-rsa`, `ecdsa-sha2-nistp256` and `ssh-ed25519`.
+/// - Read and write unencrypted `openssh-key-v1` private keys (`OPENSSH PRIVATE KEY`).
+pub mod ssh;
+
+/// # Working with X.509 certificates in JWKs
+///
+/// This module connects `Jwk`s with the X.509 certificates that are often published alongside
+/// them (e.g. by ADFS or Azure AD) through the `x5c`, `x5t` and `x5t#S256` members.
+///
+/// ## Key functionalities:
+/// - Build a `Jwk` from a PEM or DER certificate chain, with thumbprints computed.
+/// - Check that `x5c` and its thumbprints are consistent with the key in the `Jwk`.
+/// - Issue self-signed certificates for generated keys, replacing `openssl req -x509`.
+pub mod x509;
+
+/// # Linting JWKS for conformance and safety
+///
+/// This module checks a `Jwks` before it is published or trusted, and reports every problem it
+/// finds instead of stopping at the first one, so it can be run in CI.
+///
+/// ## Key functionalities:
+/// - Report duplicate or missing `kid`s, and missing `alg` or `use`.
+/// - Report private key material, weak RSA keys, unknown curves and invalid base64url members.
+/// - Report `x5c` chains and thumbprints that do not match the key.
+pub mod lint;
+
+/// # Exchanging JWKs with WebCrypto
+///
+/// This module converts keys to and from the JWK profile used by the browser WebCrypto API
+/// (`crypto.subtle.importKey` / `exportKey`), which relies on `key_ops` and `ext` rather than
+/// `use`, and names RSA algorithms after their hash (e.g. `PS256`, `RSA-OAEP-256`).
+///
+/// ## Key functionalities:
+/// - Export a generated RSA or EC key for import by a browser with `to_webcrypto_jwk`.
+/// - Import and check a key exported by a browser with `from_webcrypto_jwk`.
+pub mod webcrypto;
+
+/// # Managing signing keys and their rotation
+///
+/// This module keeps track of a service's signing keys through their lifecycle (pending,
+/// active, retiring, retired, revoked), so that key rotation does not have to be
+/// reimplemented by every application.
+///
+/// ## Key functionalities:
+/// - Select the key to sign new tokens with, and the set of keys to publish for verification.
+/// - Rotate keys, generating new RSA or ES256 keys with the `generator` module.
+/// - Revoke compromised keys.
+pub mod keystore;
+
+/// # Publishing OpenID Connect discovery metadata
+///
+/// This module builds the OpenID Provider metadata served at
+/// `/.well-known/openid-configuration`, deriving the supported algorithms from the keys
+/// actually published, so that the document cannot drift from the JWKS.
+///
+/// ## Key functionalities:
+/// - Build typed provider metadata with defaults for the required members.
+/// - Derive `id_token_signing_alg_values_supported` and related members from a `Jwks`.
+/// - Check that the issuer and endpoints are `https` URLs.
+pub mod oidc;
+
+/// # Serving a JWKS over HTTP
+///
+/// This module exposes a `Jwks` at `/.well-known/jwks.json` as an axum router, with the
+/// headers verifiers and caches rely on. Requires the `server` feature.
+///
+/// ## Key functionalities:
+/// - Serve only the public keys, as `application/jwk-set+json`.
+/// - Set a strong `ETag` and a `Cache-Control` lifetime taken from the rotation schedule.
+/// - Answer conditional requests with `304 Not Modified`.
+/// - Serve OpenID Connect discovery metadata alongside the JWKS, rebuilt whenever the keys change.
+#[cfg(feature = "server")]
+pub mod server;
+
+/// # Fetching a remote JWKS
+///
+/// This module downloads and caches the JWKS of an authorization server, for services that
+/// verify the tokens it issues. Requests go through a pluggable transport; the `client`
+/// feature provides one based on `ureq`.
+///
+/// ## Key functionalities:
+/// - Cache the keys according to the `Cache-Control` and `ETag` headers of the response.
+/// - Download the keys again when a token names an unknown `kid`, at a bounded rate.
+/// - Serve expired keys for a bounded time when the server cannot be reached.
+/// - Run on any blocking or asynchronous HTTP client.
+pub mod client;
+
+/// # Trusting tokens from several issuers
+///
+/// This module selects the key a token must be verified with, for services that accept
+/// tokens from several identity providers, each with its own keys and policy.
+///
+/// ## Key functionalities:
+/// - Map each trusted issuer to a static `Jwks`, a directory of key files, or a remote JWKS.
+/// - Resolve the verification key from a token's `iss` claim and its `alg` and `kid` header.
+/// - Enforce per-issuer algorithm allowlists and expected audiences.
+pub mod trust;
+
+/// # Signing and verifying JWKS documents
+///
+/// This module publishes a `Jwks` as a JWS signed by a long-lived key, as in OpenID
+/// Federation, so that relying parties can pin that one key while the keys in the set rotate.
+///
+/// ## Key functionalities:
+/// - Sign a key set with an RSA (`RS256`) or P-256 (`ES256`) key, with `iss`, `sub`, `iat` and `exp` claims.
+/// - Verify a signed key set against pinned keys, honoring their `use` and `key_ops`.
+/// - Reject expired documents, documents of another issuer, and documents holding private keys.
+pub mod signed;
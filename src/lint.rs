@@ -0,0 +1,309 @@
+use base64::{
+    Engine,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD}
+};
+use crate::error::JwkError;
+use crate::jwk::{Jwk, Jwks};
+use crate::x509::check_x509_consistency;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Minimum RSA modulus size, in bits, that is not reported as weak.
+pub const MIN_RSA_BITS: usize = 2048;
+
+/// How serious a lint finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintSeverity {
+    /// The key set is usable, but deviates from best practice.
+    Warning,
+
+    /// The key set is broken, unsafe to publish, or unsafe to trust.
+    Error,
+}
+
+/// The specific problem reported by a lint finding.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LintKind {
+    /// Several keys share the same `kid`.
+    DuplicateKid(String),
+
+    /// The key has no `kid`, so verifiers cannot select it unambiguously.
+    MissingKid,
+
+    /// The key has no `alg`.
+    MissingAlg,
+
+    /// The key has neither `use` nor `key_ops`.
+    MissingUse,
+
+    /// The key has both `use` and `key_ops`, which RFC 7517 advises against.
+    UseAndKeyOps,
+
+    /// The `key_ops` member is invalid, or contradicts `kty` or `use`.
+    InvalidKeyOps(String),
+
+    /// The key's `use` is neither `sig` nor `enc`.
+    UnknownUse(String),
+
+    /// The key carries private members that must not be published.
+    PrivateKeyMaterial,
+
+    /// The `alg` cannot be used with the key's `kty` or `crv`.
+    AlgorithmMismatch(String),
+
+    /// The RSA modulus is smaller than [`MIN_RSA_BITS`].
+    WeakRsaKey(usize),
+
+    /// The `crv` is not a registered curve for the key's `kty`.
+    UnknownCurve(String),
+
+    /// The named member is not valid base64url (or base64 for `x5c`).
+    InvalidBase64(String),
+
+    /// The `x5c` chain or its thumbprints do not match the key.
+    X509Mismatch(String),
+
+    /// The key is structurally invalid (missing required members or unsupported `kty`).
+    InvalidKey(JwkError),
+}
+
+/// A single problem found in a key set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintFinding {
+    /// Position of the offending key in `Jwks::keys`.
+    pub index: usize,
+
+    /// The `kid` of the offending key, if it has one.
+    pub kid: Option<String>,
+
+    pub severity: LintSeverity,
+
+    pub kind: LintKind,
+}
+
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            LintSeverity::Warning => "warning",
+            LintSeverity::Error => "error",
+        };
+        write!(f, "{}: key #{}", severity, self.index)?;
+        if let Some(kid) = &self.kid {
+            write!(f, " (kid {:?})", kid)?;
+        }
+        match &self.kind {
+            LintKind::DuplicateKid(kid) => write!(f, ": duplicate kid {:?}", kid),
+            LintKind::MissingKid => write!(f, ": missing 'kid'"),
+            LintKind::MissingAlg => write!(f, ": missing 'alg'"),
+            LintKind::MissingUse => write!(f, ": missing 'use' and 'key_ops'"),
+            LintKind::UseAndKeyOps => write!(f, ": both 'use' and 'key_ops' are set"),
+            LintKind::InvalidKeyOps(reason) => write!(f, ": invalid 'key_ops': {}", reason),
+            LintKind::UnknownUse(value) => write!(f, ": unknown 'use' value {:?}", value),
+            LintKind::PrivateKeyMaterial => write!(f, ": private key material present"),
+            LintKind::AlgorithmMismatch(alg) => write!(f, ": 'alg' {:?} does not match the key type", alg),
+            LintKind::WeakRsaKey(bits) => write!(f, ": RSA key is only {} bits", bits),
+            LintKind::UnknownCurve(crv) => write!(f, ": unknown curve {:?}", crv),
+            LintKind::InvalidBase64(member) => write!(f, ": invalid base64 in '{}'", member),
+            LintKind::X509Mismatch(reason) => write!(f, ": {}", reason),
+            LintKind::InvalidKey(error) => write!(f, ": {}", error),
+        }
+    }
+}
+
+/// The result of linting a key set with [`Jwks::lint`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LintReport {
+    pub findings: Vec<LintFinding>,
+}
+
+impl LintReport {
+    /// Returns `true` if there are no findings at all.
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Returns `true` if any finding has [`LintSeverity::Error`].
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Returns an iterator over the findings with [`LintSeverity::Error`].
+    pub fn errors(&self) -> impl Iterator<Item = &LintFinding> {
+        self.findings.iter().filter(|f| f.severity == LintSeverity::Error)
+    }
+
+    /// Returns an iterator over the findings with [`LintSeverity::Warning`].
+    pub fn warnings(&self) -> impl Iterator<Item = &LintFinding> {
+        self.findings.iter().filter(|f| f.severity == LintSeverity::Warning)
+    }
+}
+
+impl Jwks {
+    /// Checks this key set for conformance and safety problems.
+    ///
+    /// The following are reported as errors:
+    /// - duplicate `kid`s, private members, structurally invalid keys,
+    /// - `alg` or `key_ops` values that do not fit the key type, unknown curves,
+    /// - RSA moduli smaller than [`MIN_RSA_BITS`],
+    /// - invalid base64url members and `x5c` chains that do not match the key.
+    ///
+    /// Missing `kid` or `alg`, keys with neither `use` nor `key_ops` or with both, and unknown
+    /// `use` values, are reported as warnings.
+    ///
+    /// # Example
+    /// ```rust
+    /// use jwk_kit::jwk::{create_jwks, JwkBuilder};
+    ///
+    /// let jwk = JwkBuilder::new("EC")
+    ///     .set_curve_type("P-256")
+    ///     .set_x_coordinate("not+base64url")
+    ///     .set_y_coordinate("AAAA")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let report = create_jwks(vec![jwk]).lint();
+    /// assert!(report.has_errors());
+    /// for finding in &report.findings {
+    ///     println!("{}", finding);
+    /// }
+    /// ```
+    pub fn lint(&self) -> LintReport {
+        let mut findings = Vec::new();
+
+        let mut kid_counts: HashMap<&str, usize> = HashMap::new();
+        for kid in self.keys.iter().filter_map(|jwk| jwk.kid.as_deref()) {
+            *kid_counts.entry(kid).or_default() += 1;
+        }
+
+        for (index, jwk) in self.keys.iter().enumerate() {
+            let mut report = |severity, kind| {
+                findings.push(LintFinding { index, kid: jwk.kid.clone(), severity, kind });
+            };
+
+            match jwk.kid.as_deref() {
+                Some(kid) if kid_counts[kid] > 1 => {
+                    report(LintSeverity::Error, LintKind::DuplicateKid(kid.to_string()))
+                }
+                Some(_) => {}
+                None => report(LintSeverity::Warning, LintKind::MissingKid),
+            }
+
+            if jwk.alg.is_none() {
+                report(LintSeverity::Warning, LintKind::MissingAlg);
+            }
+            match jwk.use_.as_deref() {
+                None if jwk.key_ops.is_none() => report(LintSeverity::Warning, LintKind::MissingUse),
+                None | Some("sig") | Some("enc") => {}
+                Some(other) => report(LintSeverity::Warning, LintKind::UnknownUse(other.to_string())),
+            }
+            if jwk.use_.is_some() && jwk.key_ops.is_some() {
+                report(LintSeverity::Warning, LintKind::UseAndKeyOps);
+            }
+
+            if jwk.is_private() {
+                report(LintSeverity::Error, LintKind::PrivateKeyMaterial);
+            }
+
+            for member in invalid_base64_members(jwk) {
+                report(LintSeverity::Error, LintKind::InvalidBase64(member));
+            }
+
+            if let Err(error) = jwk.validate_members() {
+                report(LintSeverity::Error, LintKind::InvalidKey(error));
+                continue;
+            }
+
+            if let Err(JwkError::InvalidKeyOps(reason)) = jwk.validate_key_ops() {
+                report(LintSeverity::Error, LintKind::InvalidKeyOps(reason));
+            }
+            if let Err(error) = jwk.validate_validity() {
+                report(LintSeverity::Error, LintKind::InvalidKey(error));
+            }
+
+            match check_x509_consistency(jwk) {
+                Ok(()) => {}
+                Err(JwkError::X509Mismatch(reason)) => report(LintSeverity::Error, LintKind::X509Mismatch(reason)),
+                // Already reported member by member above.
+                Err(JwkError::InvalidBase64(_)) => {}
+                Err(_) => report(
+                    LintSeverity::Error,
+                    LintKind::X509Mismatch("x5c leaf is not a valid certificate".into()),
+                ),
+            }
+
+            if let Some(crv) = jwk.crv.as_deref() {
+                let known = match jwk.kty.as_str() {
+                    "EC" => ["P-256", "P-384", "P-521", "secp256k1"].contains(&crv),
+                    "OKP" => ["Ed25519", "Ed448", "X25519", "X448"].contains(&crv),
+                    _ => true,
+                };
+                if !known {
+                    report(LintSeverity::Error, LintKind::UnknownCurve(crv.to_string()));
+                }
+            }
+
+            if let Some(alg) = jwk.alg.as_deref()
+                && !alg_matches_key(alg, jwk)
+            {
+                report(LintSeverity::Error, LintKind::AlgorithmMismatch(alg.to_string()));
+            }
+
+            if jwk.kty == "RSA"
+                && let Some(bits) = rsa_modulus_bits(jwk)
+                && bits < MIN_RSA_BITS
+            {
+                report(LintSeverity::Error, LintKind::WeakRsaKey(bits));
+            }
+        }
+
+        LintReport { findings }
+    }
+}
+
+fn invalid_base64_members(jwk: &Jwk) -> Vec<String> {
+    let members = [
+        ("n", &jwk.n), ("e", &jwk.e), ("x", &jwk.x), ("y", &jwk.y), ("d", &jwk.d), ("k", &jwk.k),
+        ("p", &jwk.p), ("q", &jwk.q), ("dp", &jwk.dp), ("dq", &jwk.dq), ("qi", &jwk.qi),
+        ("x5t", &jwk.x5t), ("x5t#S256", &jwk.x5t_s256),
+    ];
+
+    let mut invalid: Vec<String> = members
+        .into_iter()
+        .filter(|(_, value)| value.as_deref().is_some_and(|v| URL_SAFE_NO_PAD.decode(v).is_err()))
+        .map(|(name, _)| name.to_string())
+        .collect();
+
+    if jwk.x5c.iter().flatten().any(|cert| STANDARD.decode(cert).is_err()) {
+        invalid.push("x5c".to_string());
+    }
+
+    invalid
+}
+
+fn rsa_modulus_bits(jwk: &Jwk) -> Option<usize> {
+    let n = URL_SAFE_NO_PAD.decode(jwk.n.as_deref()?).ok()?;
+    let n: Vec<u8> = n.into_iter().skip_while(|&b| b == 0).collect();
+    let first = *n.first()?;
+    Some(n.len() * 8 - first.leading_zeros() as usize)
+}
+
+/// Checks that a JWA `alg` is applicable to the key's `kty` (and `crv` for EC and OKP keys).
+/// Unregistered algorithms are not reported.
+pub(crate) fn alg_matches_key(alg: &str, jwk: &Jwk) -> bool {
+    let crv = jwk.crv.as_deref();
+    match alg {
+        "RS256" | "RS384" | "RS512" | "PS256" | "PS384" | "PS512" | "RSA1_5" | "RSA-OAEP"
+        | "RSA-OAEP-256" | "RSA-OAEP-384" | "RSA-OAEP-512" => jwk.kty == "RSA",
+        "ES256" => jwk.kty == "EC" && crv == Some("P-256"),
+        "ES384" => jwk.kty == "EC" && crv == Some("P-384"),
+        "ES512" => jwk.kty == "EC" && crv == Some("P-521"),
+        "ES256K" => jwk.kty == "EC" && crv == Some("secp256k1"),
+        "EdDSA" | "Ed25519" | "Ed448" => jwk.kty == "OKP",
+        "HS256" | "HS384" | "HS512" | "A128KW" | "A192KW" | "A256KW" | "A128GCMKW"
+        | "A192GCMKW" | "A256GCMKW" | "dir" => jwk.kty == "oct",
+        "ECDH-ES" | "ECDH-ES+A128KW" | "ECDH-ES+A192KW" | "ECDH-ES+A256KW" => {
+            jwk.kty == "EC" || jwk.kty == "OKP"
+        }
+        _ => true,
+    }
+}
@@ -0,0 +1,308 @@
+use crate::error::JwkError;
+use crate::generator::ecdsa::{es256_public_key_to_jwk, es256_secret_key_to_jwk};
+use crate::generator::rsa::{rsa_private_key_to_jwk, rsa_public_key_to_jwk};
+use crate::jwk::{Jwk, JwkBuilder, Jwks};
+use crate::ssh::{from_openssh_private_key, from_openssh_public_key};
+use crate::x509::jwk_from_certificate;
+use base64::{
+    Engine,
+    engine::general_purpose::URL_SAFE_NO_PAD
+};
+use p256::{PublicKey, SecretKey};
+use std::fs;
+use std::path::{Path, PathBuf};
+use rsa::{
+    pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey},
+    pkcs8::der::{pem, Decode},
+    pkcs8::spki::{ObjectIdentifier, SubjectPublicKeyInfoRef},
+    pkcs8::{DecodePrivateKey, DecodePublicKey},
+    RsaPrivateKey, RsaPublicKey,
+};
+
+const ED25519_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.112");
+
+/// Loads a single key from `bytes`, detecting its format automatically.
+///
+/// The input may be any of the formats accepted by [`load_keys`]. Exactly one key must be
+/// present; inputs that contain several keys (e.g. a JWKS or a multi-block PEM file) should
+/// be loaded with [`load_keys`] instead.
+///
+/// # Errors
+/// - `JwkError::UnrecognizedKeyFormat` if the input is not in any supported format.
+/// - `JwkError::UnexpectedKeyCount` if the input does not contain exactly one key.
+/// - Any error produced while parsing the detected format.
+///
+/// # Example
+/// ```rust
+/// use jwk_kit::loader::load_key;
+///
+/// let pem_data = std::fs::read("./examples/rsa/rsa-access-public.pem").unwrap();
+/// let jwk = load_key(&pem_data).unwrap();
+/// assert_eq!(jwk.kty, "RSA");
+/// ```
+pub fn load_key(bytes: &[u8]) -> Result<Jwk, JwkError> {
+    let mut keys = load_keys(bytes)?;
+
+    if keys.len() != 1 {
+        return Err(JwkError::UnexpectedKeyCount(keys.len()));
+    }
+
+    Ok(keys.remove(0))
+}
+
+/// Loads every key found in `bytes`, detecting the format automatically.
+///
+/// The following formats are recognized:
+/// - PEM, with one or more `PUBLIC KEY`, `RSA PUBLIC KEY`, `PRIVATE KEY`, `RSA PRIVATE KEY`,
+///   `EC PRIVATE KEY` or unencrypted `OPENSSH PRIVATE KEY` blocks. Consecutive `CERTIFICATE`
+///   blocks are treated as one certificate chain and yield a single key with `x5c` populated.
+/// - Raw DER in any of the key encodings above, or a DER-encoded certificate.
+/// - A single JWK as JSON.
+/// - A JWKS (`{"keys": [...]}`) as JSON.
+/// - OpenSSH public keys (`ssh-rsa`, `ecdsa-sha2-nistp256`, `ssh-ed25519`), one per line as in `authorized_keys`.
+///
+/// Private keys are returned with their private members (`d`, and for RSA the CRT parameters)
+/// populated. The returned keys carry no `kid`, `alg` or `use` unless the input did.
+///
+/// # Errors
+/// - `JwkError::UnrecognizedKeyFormat` if the input is not in any supported format.
+/// - Any error produced while parsing the detected format.
+pub fn load_keys(bytes: &[u8]) -> Result<Vec<Jwk>, JwkError> {
+    let text = std::str::from_utf8(bytes).ok().map(str::trim);
+
+    match text {
+        Some(text) if text.starts_with('{') => load_json(text),
+        Some(text) if text.contains("-----BEGIN ") => load_pem_blocks(text),
+        Some(text) if is_openssh_public_key(text) => text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(from_openssh_public_key)
+            .collect(),
+        _ => load_der(bytes).map(|jwk| vec![jwk]),
+    }
+}
+
+/// Loads every key from the files in `dir`, detecting the format of each file as [`load_keys`] does.
+///
+/// Hidden entries (whose names start with `.`) and subdirectories are skipped, and symbolic
+/// links are followed, so that a Kubernetes secret mounted as a volume can be loaded directly.
+/// Keys without a `kid` are given their RFC 7638 thumbprint as `kid`, and a public key found
+/// next to its private key is merged into it. Files are read in name order.
+///
+/// # Errors
+/// - `JwkError::InvalidKeyFile` with the path of the first file that cannot be read or parsed.
+/// - `JwkError::DuplicateKeyId` if two different keys share a `kid`.
+/// - `JwkError::UnexpectedKeyCount` if the directory holds no key at all.
+///
+/// # Example
+/// ```rust
+/// use jwk_kit::loader::load_dir;
+///
+/// let jwks = load_dir("./examples/webcrypto").unwrap();
+/// assert!(jwks.keys.iter().all(|jwk| jwk.kid.is_some()));
+/// assert_eq!(jwks.keys.iter().filter(|jwk| jwk.is_private()).count(), 2);
+/// ```
+pub fn load_dir(dir: impl AsRef<Path>) -> Result<Jwks, JwkError> {
+    let mut keys: Vec<Jwk> = Vec::new();
+    for path in key_files(dir.as_ref())? {
+        let bytes = fs::read(&path).map_err(|e| file_io_error(&path, e))?;
+        for mut jwk in load_keys(&bytes).map_err(|e| file_error(&path, e))? {
+            if jwk.kid.is_none() {
+                jwk.kid = Some(jwk.thumbprint().map_err(|e| file_error(&path, e))?);
+            }
+            match keys.iter_mut().find(|key| key.kid == jwk.kid) {
+                None => keys.push(jwk),
+                // A private key and its public key, e.g. `tls.key` and `tls.pub`.
+                Some(key) if key.thumbprint().ok() == jwk.thumbprint().ok() => {
+                    if jwk.is_private() {
+                        *key = jwk;
+                    }
+                }
+                Some(_) => return Err(JwkError::DuplicateKeyId(jwk.kid.unwrap_or_default())),
+            }
+        }
+    }
+
+    if keys.is_empty() {
+        return Err(JwkError::UnexpectedKeyCount(0));
+    }
+
+    Ok(Jwks { keys })
+}
+
+/// Returns the paths of the files [`load_dir`] reads in `dir`, sorted by name.
+pub(crate) fn key_files(dir: &Path) -> Result<Vec<PathBuf>, JwkError> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| file_io_error(dir, e))? {
+        let path = entry.map_err(|e| file_io_error(dir, e))?.path();
+        let hidden = path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if !hidden && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    Ok(paths)
+}
+
+fn file_error(path: &Path, error: JwkError) -> JwkError {
+    JwkError::InvalidKeyFile(path.display().to_string(), Box::new(error))
+}
+
+pub(crate) fn file_io_error(path: &Path, error: std::io::Error) -> JwkError {
+    file_error(path, JwkError::KeyStoreIo(error.to_string()))
+}
+
+fn load_json(text: &str) -> Result<Vec<Jwk>, JwkError> {
+    let value: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| JwkError::InvalidJson(e.to_string()))?;
+
+    let keys = if value.get("keys").is_some() {
+        serde_json::from_value::<Jwks>(value)
+            .map_err(|e| JwkError::InvalidJson(e.to_string()))?
+            .keys
+    } else {
+        vec![serde_json::from_value::<Jwk>(value)
+            .map_err(|e| JwkError::InvalidJson(e.to_string()))?]
+    };
+
+    for jwk in &keys {
+        jwk.validate()?;
+    }
+
+    Ok(keys)
+}
+
+fn load_pem_blocks(text: &str) -> Result<Vec<Jwk>, JwkError> {
+    let mut blocks = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("-----BEGIN ") {
+        let block = &rest[start..];
+        let label_end = block["-----BEGIN ".len()..]
+            .find("-----")
+            .ok_or(JwkError::PemReadError)?;
+        let label = &block["-----BEGIN ".len().."-----BEGIN ".len() + label_end];
+        let footer = format!("-----END {}-----", label);
+        let end = block.find(&footer).ok_or(JwkError::PemReadError)? + footer.len();
+
+        blocks.push((label, &block[..end]));
+        rest = &block[end..];
+    }
+
+    let mut keys = Vec::new();
+    let mut index = 0;
+
+    while index < blocks.len() {
+        let (label, block) = blocks[index];
+        index += 1;
+
+        match label {
+            "OPENSSH PRIVATE KEY" => keys.push(from_openssh_private_key(block)?),
+            "CERTIFICATE" => {
+                // Consecutive certificates form a single chain, leaf first.
+                let mut chain = block.to_string();
+                while let Some(("CERTIFICATE", next)) = blocks.get(index) {
+                    chain.push('\n');
+                    chain.push_str(next);
+                    index += 1;
+                }
+                keys.push(jwk_from_certificate(chain.as_bytes())?);
+            }
+            _ => keys.push(load_pem(block)?),
+        }
+    }
+
+    Ok(keys)
+}
+
+/// Converts a single PEM block into a `Jwk`, dispatching on its label.
+pub(crate) fn load_pem(pem_data: &str) -> Result<Jwk, JwkError> {
+    let (label, der) = pem::decode_vec(pem_data.as_bytes())
+        .map_err(|_| JwkError::PemReadError)?;
+
+    match label {
+        "PUBLIC KEY" => load_spki_der(&der),
+        "PRIVATE KEY" => load_pkcs8_der(&der),
+        "RSA PUBLIC KEY" => RsaPublicKey::from_pkcs1_der(&der)
+            .map_err(|_| JwkError::MissingRsaParams)
+            .and_then(|key| rsa_public_key_to_jwk(&key)),
+        "RSA PRIVATE KEY" => RsaPrivateKey::from_pkcs1_der(&der)
+            .map_err(|_| JwkError::MissingRsaParams)
+            .and_then(|key| rsa_private_key_to_jwk(&key)),
+        "EC PRIVATE KEY" => SecretKey::from_sec1_der(&der)
+            .map_err(|_| JwkError::MissingEcParams)
+            .and_then(|key| es256_secret_key_to_jwk(&key)),
+        _ => Err(JwkError::UnrecognizedKeyFormat),
+    }
+}
+
+/// Converts a DER-encoded `SubjectPublicKeyInfo` (RSA, P-256 or Ed25519) into a public `Jwk`.
+pub(crate) fn load_spki_der(der: &[u8]) -> Result<Jwk, JwkError> {
+    if let Ok(key) = RsaPublicKey::from_public_key_der(der) {
+        return rsa_public_key_to_jwk(&key);
+    }
+    if let Ok(key) = PublicKey::from_public_key_der(der) {
+        return es256_public_key_to_jwk(&key);
+    }
+    if let Ok(spki) = SubjectPublicKeyInfoRef::from_der(der)
+        && spki.algorithm.oid == ED25519_OID
+    {
+        return JwkBuilder::new("OKP")
+            .set_curve_type("Ed25519")
+            .set_x_coordinate(&URL_SAFE_NO_PAD.encode(spki.subject_public_key.raw_bytes()))
+            .build();
+    }
+    Err(JwkError::UnrecognizedKeyFormat)
+}
+
+fn load_pkcs8_der(der: &[u8]) -> Result<Jwk, JwkError> {
+    if let Ok(key) = RsaPrivateKey::from_pkcs8_der(der) {
+        return rsa_private_key_to_jwk(&key);
+    }
+    if let Ok(key) = SecretKey::from_pkcs8_der(der) {
+        return es256_secret_key_to_jwk(&key);
+    }
+    Err(JwkError::UnrecognizedKeyFormat)
+}
+
+fn load_der(der: &[u8]) -> Result<Jwk, JwkError> {
+    // Every supported DER encoding starts with a SEQUENCE tag.
+    if der.first() != Some(&0x30) {
+        return Err(JwkError::UnrecognizedKeyFormat);
+    }
+
+    if let Ok(jwk) = load_spki_der(der) {
+        return Ok(jwk);
+    }
+    if let Ok(jwk) = load_pkcs8_der(der) {
+        return Ok(jwk);
+    }
+    if let Ok(key) = RsaPrivateKey::from_pkcs1_der(der) {
+        return rsa_private_key_to_jwk(&key);
+    }
+    if let Ok(key) = RsaPublicKey::from_pkcs1_der(der) {
+        return rsa_public_key_to_jwk(&key);
+    }
+    if let Ok(key) = SecretKey::from_sec1_der(der) {
+        return es256_secret_key_to_jwk(&key);
+    }
+    if let Ok(jwk) = jwk_from_certificate(der) {
+        return Ok(jwk);
+    }
+
+    Err(JwkError::UnrecognizedKeyFormat)
+}
+
+fn is_openssh_public_key(text: &str) -> bool {
+    let first_line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .unwrap_or_default();
+
+    ["ssh-rsa ", "ecdsa-sha2-nistp256 ", "ssh-ed25519 "]
+        .iter()
+        .any(|prefix| first_line.starts_with(prefix))
+}
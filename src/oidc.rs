@@ -0,0 +1,348 @@
+use crate::error::JwkError;
+use crate::jwk::{Jwk, Jwks};
+use crate::lint::alg_matches_key;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The path the OpenID Provider metadata is served at, relative to the issuer.
+pub const OPENID_CONFIGURATION_PATH: &str = "/.well-known/openid-configuration";
+
+/// The members of [`ProviderMetadata`] that have a dedicated field.
+const METADATA_MEMBERS: &[&str] = &[
+    "issuer",
+    "authorization_endpoint",
+    "token_endpoint",
+    "userinfo_endpoint",
+    "jwks_uri",
+    "registration_endpoint",
+    "scopes_supported",
+    "response_types_supported",
+    "grant_types_supported",
+    "subject_types_supported",
+    "id_token_signing_alg_values_supported",
+    "id_token_encryption_alg_values_supported",
+    "userinfo_signing_alg_values_supported",
+    "token_endpoint_auth_methods_supported",
+    "claims_supported",
+];
+
+/// OpenID Provider metadata, as served at `/.well-known/openid-configuration`
+/// ([OpenID Connect Discovery 1.0, section 3](https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderMetadata)).
+///
+/// Build it with [`ProviderMetadataBuilder`], which derives the algorithm lists from the
+/// published keys. Members without a dedicated field are kept in `extra`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+
+    pub authorization_endpoint: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_endpoint: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub userinfo_endpoint: Option<String>,
+
+    pub jwks_uri: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registration_endpoint: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scopes_supported: Option<Vec<String>>,
+
+    pub response_types_supported: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grant_types_supported: Option<Vec<String>>,
+
+    pub subject_types_supported: Vec<String>,
+
+    pub id_token_signing_alg_values_supported: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_token_encryption_alg_values_supported: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub userinfo_signing_alg_values_supported: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_endpoint_auth_methods_supported: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claims_supported: Option<Vec<String>>,
+
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+/// # Builds [`ProviderMetadata`] that stays in sync with the published keys.
+///
+/// `id_token_signing_alg_values_supported` is derived from the signing keys of the JWKS
+/// passed to [`ProviderMetadataBuilder::set_keys`], and so are
+/// `userinfo_signing_alg_values_supported` (when a userinfo endpoint is set) and
+/// `id_token_encryption_alg_values_supported` (when the JWKS holds encryption keys with an
+/// `alg`). Keys without an `alg` are assumed to be used with `RS256` for RSA, the `ES*`
+/// algorithm of their curve for EC, and `EdDSA` for OKP keys.
+///
+/// Unless set explicitly, `jwks_uri` is the issuer followed by `/.well-known/jwks.json`,
+/// `response_types_supported` is `["code"]` and `subject_types_supported` is `["public"]`.
+///
+/// Discovery requires `RS256` among the ID token signing algorithms, so the keys must include
+/// an RSA signing key unless [`ProviderMetadataBuilder::set_require_rs256`] turns this off.
+///
+/// ## Example
+/// ```rust
+/// use jwk_kit::keystore::{KeyAlgorithm, KeyStore};
+/// use jwk_kit::oidc::ProviderMetadataBuilder;
+///
+/// let mut store = KeyStore::new(KeyAlgorithm::Es256);
+/// store.rotate().unwrap();
+///
+/// let metadata = ProviderMetadataBuilder::new("https://auth.example.com")
+///     .set_authorization_endpoint("https://auth.example.com/authorize")
+///     .set_token_endpoint("https://auth.example.com/token")
+///     .set_keys(&store.published_keys())
+///     .set_require_rs256(false)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(metadata.jwks_uri, "https://auth.example.com/.well-known/jwks.json");
+/// assert_eq!(metadata.id_token_signing_alg_values_supported, ["ES256"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProviderMetadataBuilder {
+    issuer: String,
+    authorization_endpoint: Option<String>,
+    token_endpoint: Option<String>,
+    userinfo_endpoint: Option<String>,
+    jwks_uri: Option<String>,
+    registration_endpoint: Option<String>,
+    scopes_supported: Option<Vec<String>>,
+    response_types_supported: Option<Vec<String>>,
+    grant_types_supported: Option<Vec<String>>,
+    subject_types_supported: Option<Vec<String>>,
+    token_endpoint_auth_methods_supported: Option<Vec<String>>,
+    claims_supported: Option<Vec<String>>,
+    keys: Option<Jwks>,
+    require_rs256: bool,
+    extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl ProviderMetadataBuilder {
+    pub fn new(issuer: &str) -> Self {
+        Self {
+            issuer: issuer.to_string(),
+            authorization_endpoint: None,
+            token_endpoint: None,
+            userinfo_endpoint: None,
+            jwks_uri: None,
+            registration_endpoint: None,
+            scopes_supported: None,
+            response_types_supported: None,
+            grant_types_supported: None,
+            subject_types_supported: None,
+            token_endpoint_auth_methods_supported: None,
+            claims_supported: None,
+            keys: None,
+            require_rs256: true,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    pub fn set_authorization_endpoint(&mut self, value: &str) -> &mut Self {
+        self.authorization_endpoint = Some(value.to_string());
+        self
+    }
+
+    pub fn set_token_endpoint(&mut self, value: &str) -> &mut Self {
+        self.token_endpoint = Some(value.to_string());
+        self
+    }
+
+    pub fn set_userinfo_endpoint(&mut self, value: &str) -> &mut Self {
+        self.userinfo_endpoint = Some(value.to_string());
+        self
+    }
+
+    pub fn set_jwks_uri(&mut self, value: &str) -> &mut Self {
+        self.jwks_uri = Some(value.to_string());
+        self
+    }
+
+    pub fn set_registration_endpoint(&mut self, value: &str) -> &mut Self {
+        self.registration_endpoint = Some(value.to_string());
+        self
+    }
+
+    pub fn set_scopes_supported(&mut self, value: &[&str]) -> &mut Self {
+        self.scopes_supported = Some(to_strings(value));
+        self
+    }
+
+    pub fn set_response_types_supported(&mut self, value: &[&str]) -> &mut Self {
+        self.response_types_supported = Some(to_strings(value));
+        self
+    }
+
+    pub fn set_grant_types_supported(&mut self, value: &[&str]) -> &mut Self {
+        self.grant_types_supported = Some(to_strings(value));
+        self
+    }
+
+    pub fn set_subject_types_supported(&mut self, value: &[&str]) -> &mut Self {
+        self.subject_types_supported = Some(to_strings(value));
+        self
+    }
+
+    pub fn set_token_endpoint_auth_methods_supported(&mut self, value: &[&str]) -> &mut Self {
+        self.token_endpoint_auth_methods_supported = Some(to_strings(value));
+        self
+    }
+
+    pub fn set_claims_supported(&mut self, value: &[&str]) -> &mut Self {
+        self.claims_supported = Some(to_strings(value));
+        self
+    }
+
+    /// Sets the keys the algorithm lists are derived from, normally the published JWKS.
+    pub fn set_keys(&mut self, value: &Jwks) -> &mut Self {
+        self.keys = Some(value.clone());
+        self
+    }
+
+    /// Requires (the default) or not that the keys can sign ID tokens with `RS256`, as
+    /// OpenID Connect Discovery mandates. Turn it off for providers that only sign with other
+    /// algorithms and whose relying parties accept them.
+    pub fn set_require_rs256(&mut self, value: bool) -> &mut Self {
+        self.require_rs256 = value;
+        self
+    }
+
+    pub fn set_extra_member(&mut self, name: &str, value: serde_json::Value) -> &mut Self {
+        self.extra.insert(name.to_string(), value);
+        self
+    }
+
+    /// Builds the metadata.
+    ///
+    /// # Errors
+    /// - `JwkError::InvalidMetadata` if the issuer or an endpoint is not an `https` URL
+    ///   (`http` is accepted for `localhost`), the issuer has a query or fragment, the
+    ///   authorization endpoint or the keys are missing, no key can sign ID tokens, or none
+    ///   can sign them with `RS256` (see [`ProviderMetadataBuilder::set_require_rs256`]).
+    /// - `JwkError::DuplicateMember` if an extra member has a dedicated field.
+    pub fn build(&self) -> Result<ProviderMetadata, JwkError> {
+        if let Some(name) = self.extra.keys().find(|name| METADATA_MEMBERS.contains(&name.as_str())) {
+            return Err(JwkError::DuplicateMember(name.clone()));
+        }
+        check_url("issuer", &self.issuer)?;
+        if self.issuer.contains(['?', '#']) {
+            return Err(JwkError::InvalidMetadata("the issuer must not have a query or fragment".into()));
+        }
+
+        let authorization_endpoint = self
+            .authorization_endpoint
+            .clone()
+            .ok_or_else(|| JwkError::InvalidMetadata("authorization_endpoint is required".into()))?;
+        let jwks_uri = self
+            .jwks_uri
+            .clone()
+            .unwrap_or_else(|| format!("{}/.well-known/jwks.json", self.issuer.trim_end_matches('/')));
+
+        for (name, url) in [
+            ("authorization_endpoint", Some(&authorization_endpoint)),
+            ("token_endpoint", self.token_endpoint.as_ref()),
+            ("userinfo_endpoint", self.userinfo_endpoint.as_ref()),
+            ("jwks_uri", Some(&jwks_uri)),
+            ("registration_endpoint", self.registration_endpoint.as_ref()),
+        ] {
+            if let Some(url) = url {
+                check_url(name, url)?;
+            }
+        }
+
+        let keys = self
+            .keys
+            .as_ref()
+            .ok_or_else(|| JwkError::InvalidMetadata("the keys are required to derive the algorithms".into()))?;
+        let signing_algs: Vec<String> = algorithms(keys.signing_keys()).into_iter().filter(|alg| is_signature_alg(alg)).collect();
+        if signing_algs.is_empty() {
+            return Err(JwkError::InvalidMetadata("no key can sign ID tokens".into()));
+        }
+        if self.require_rs256 && !signing_algs.iter().any(|alg| alg == "RS256") {
+            return Err(JwkError::InvalidMetadata("id_token_signing_alg_values_supported must include RS256".into()));
+        }
+        let encryption_algs: Vec<String> = algorithms(keys.encryption_keys().filter(|jwk| jwk.alg.is_some()))
+            .into_iter()
+            .filter(|alg| !is_signature_alg(alg))
+            .collect();
+
+        Ok(ProviderMetadata {
+            issuer: self.issuer.clone(),
+            authorization_endpoint,
+            token_endpoint: self.token_endpoint.clone(),
+            userinfo_endpoint: self.userinfo_endpoint.clone(),
+            jwks_uri,
+            registration_endpoint: self.registration_endpoint.clone(),
+            scopes_supported: self.scopes_supported.clone(),
+            response_types_supported: self.response_types_supported.clone().unwrap_or_else(|| to_strings(&["code"])),
+            grant_types_supported: self.grant_types_supported.clone(),
+            subject_types_supported: self.subject_types_supported.clone().unwrap_or_else(|| to_strings(&["public"])),
+            userinfo_signing_alg_values_supported: self.userinfo_endpoint.as_ref().map(|_| signing_algs.clone()),
+            id_token_signing_alg_values_supported: signing_algs,
+            id_token_encryption_alg_values_supported: (!encryption_algs.is_empty()).then_some(encryption_algs),
+            token_endpoint_auth_methods_supported: self.token_endpoint_auth_methods_supported.clone(),
+            claims_supported: self.claims_supported.clone(),
+            extra: self.extra.clone(),
+        })
+    }
+}
+
+/// Returns the algorithms the keys are used with, sorted and without duplicates.
+fn algorithms<'a>(keys: impl Iterator<Item = &'a Jwk>) -> Vec<String> {
+    let mut algs: Vec<String> = keys
+        .filter_map(|jwk| match jwk.alg.as_deref() {
+            Some(alg) => alg_matches_key(alg, jwk).then(|| alg.to_string()),
+            None => default_alg(jwk).map(str::to_string),
+        })
+        .collect();
+    algs.sort();
+    algs.dedup();
+    algs
+}
+
+fn is_signature_alg(alg: &str) -> bool {
+    matches!(
+        alg,
+        "RS256" | "RS384" | "RS512" | "PS256" | "PS384" | "PS512" | "ES256" | "ES384" | "ES512"
+            | "ES256K" | "HS256" | "HS384" | "HS512" | "EdDSA"
+    )
+}
+
+fn default_alg(jwk: &Jwk) -> Option<&'static str> {
+    match (jwk.kty.as_str(), jwk.crv.as_deref()) {
+        ("RSA", _) => Some("RS256"),
+        ("EC", Some("P-256")) => Some("ES256"),
+        ("EC", Some("P-384")) => Some("ES384"),
+        ("EC", Some("P-521")) => Some("ES512"),
+        ("OKP", Some("Ed25519" | "Ed448")) => Some("EdDSA"),
+        _ => None,
+    }
+}
+
+fn check_url(name: &str, url: &str) -> Result<(), JwkError> {
+    let local = ["http://localhost", "http://127.0.0.1", "http://[::1]"]
+        .iter()
+        .any(|prefix| url.strip_prefix(prefix).is_some_and(|rest| rest.is_empty() || rest.starts_with([':', '/'])));
+
+    if (url.starts_with("https://") && url.len() > "https://".len()) || local {
+        Ok(())
+    } else {
+        Err(JwkError::InvalidMetadata(format!("{} must be an https URL, got '{}'", name, url)))
+    }
+}
+
+fn to_strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
@@ -0,0 +1,423 @@
+use crate::error::JwkError;
+use crate::jwk::Jwks;
+use crate::keystore::rotation::RotationStep;
+use crate::oidc::{OPENID_CONFIGURATION_PATH, ProviderMetadata, ProviderMetadataBuilder};
+use axum::Router;
+use axum::body::Body;
+use axum::http::{HeaderMap, HeaderValue, Response, StatusCode, header};
+use axum::routing::get;
+use base64::{
+    Engine,
+    engine::general_purpose::URL_SAFE_NO_PAD
+};
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// The path a JWKS is conventionally served at.
+pub const JWKS_PATH: &str = "/.well-known/jwks.json";
+
+/// The media type of a JWKS ([RFC 7517, section 8.5](https://datatracker.ietf.org/doc/html/rfc7517#section-8.5)).
+pub const JWK_SET_CONTENT_TYPE: &str = "application/jwk-set+json";
+
+/// A serialized document with the headers it is served with.
+#[derive(Debug)]
+struct Document {
+    body: Vec<u8>,
+    etag: HeaderValue,
+    freshness: Freshness,
+}
+
+/// How long a document may be cached.
+#[derive(Debug, Clone, Copy)]
+enum Freshness {
+    /// The same `max-age` on every response.
+    MaxAge(Duration),
+
+    /// A `max-age` counting down to an instant, e.g. [`RotationStep::fresh_until`].
+    Until(SystemTime),
+}
+
+impl Freshness {
+    fn cache_control(self, now: SystemTime) -> HeaderValue {
+        let max_age = match self {
+            Freshness::MaxAge(max_age) => max_age,
+            Freshness::Until(at) => at.duration_since(now).unwrap_or_default(),
+        };
+        HeaderValue::from_str(&format!("public, max-age={}", max_age.as_secs())).expect("ASCII is a valid header value")
+    }
+}
+
+/// # Serves a JWKS over HTTP.
+///
+/// The endpoint holds the public projection of a `Jwks`, serialized once, and answers `GET`
+/// and `HEAD` requests with:
+/// - `Content-Type: application/jwk-set+json`,
+/// - a strong `ETag` derived from the content of the document,
+/// - `Cache-Control: public, max-age=...`; for the JWKS of a [`RotationStep`], the max-age
+///   counts down to [`RotationStep::fresh_until`], so that no cache outlives the step,
+/// - `304 Not Modified` when `If-None-Match` matches the current `ETag`.
+///
+/// Private members are always removed, and symmetric keys are never served. The endpoint is
+/// cheap to clone, and every clone serves the document most recently set with
+/// [`JwksEndpoint::update`].
+///
+/// Requires the `server` feature.
+///
+/// ## Example
+/// ```rust
+/// use std::time::{Duration, SystemTime};
+/// use jwk_kit::keystore::{KeyAlgorithm, KeyStore};
+/// use jwk_kit::server::JwksEndpoint;
+///
+/// let mut store = KeyStore::new(KeyAlgorithm::Es256);
+/// store.rotate().unwrap();
+///
+/// let endpoint = JwksEndpoint::new(&store.published_keys(), Duration::from_secs(3600)).unwrap();
+/// let app: axum::Router = endpoint.router();
+/// ```
+#[derive(Debug, Clone)]
+pub struct JwksEndpoint {
+    document: SharedDocument,
+}
+
+impl JwksEndpoint {
+    /// Creates an endpoint serving the public keys of `jwks`, cacheable for `max_age`.
+    ///
+    /// # Errors
+    /// - `JwkError::InvalidJson` if the key set cannot be serialized.
+    pub fn new(jwks: &Jwks, max_age: Duration) -> Result<Self, JwkError> {
+        Ok(Self { document: SharedDocument::new(jwks_document(jwks, Freshness::MaxAge(max_age))?) })
+    }
+
+    /// Creates an endpoint serving the JWKS of a rotation step, cacheable until its
+    /// `fresh_until`.
+    ///
+    /// # Errors
+    /// The same errors as [`JwksEndpoint::new`].
+    pub fn from_step(step: &RotationStep) -> Result<Self, JwkError> {
+        Ok(Self { document: SharedDocument::new(jwks_document(&step.jwks, Freshness::Until(step.fresh_until))?) })
+    }
+
+    /// Replaces the served keys.
+    ///
+    /// # Errors
+    /// The same errors as [`JwksEndpoint::new`]. The previous keys are still served.
+    pub fn update(&self, jwks: &Jwks, max_age: Duration) -> Result<(), JwkError> {
+        self.document.set(jwks_document(jwks, Freshness::MaxAge(max_age))?);
+        Ok(())
+    }
+
+    /// Replaces the served keys with the JWKS of a rotation step, cacheable until its
+    /// `fresh_until`.
+    ///
+    /// # Errors
+    /// The same errors as [`JwksEndpoint::new`].
+    pub fn update_from_step(&self, step: &RotationStep) -> Result<(), JwkError> {
+        self.document.set(jwks_document(&step.jwks, Freshness::Until(step.fresh_until))?);
+        Ok(())
+    }
+
+    /// Returns the current `ETag`, quotes included.
+    pub fn etag(&self) -> String {
+        self.document.etag()
+    }
+
+    /// Returns a router serving the keys at [`JWKS_PATH`].
+    ///
+    /// Merge it into the application's router, or serve it on its own; as an axum `Router`
+    /// it is also a tower `Service`.
+    pub fn router(&self) -> Router {
+        self.route(Router::new())
+    }
+
+    /// Adds the route serving the keys at [`JWKS_PATH`] to `router`.
+    pub fn route<S: Clone + Send + Sync + 'static>(&self, router: Router<S>) -> Router<S> {
+        let endpoint = self.clone();
+        router.route(JWKS_PATH, get(move |headers: HeaderMap| async move { endpoint.respond(&headers) }))
+    }
+
+    /// Answers a request with the given headers: `200 OK` with the document, or
+    /// `304 Not Modified` when `If-None-Match` matches.
+    pub fn respond(&self, request_headers: &HeaderMap) -> Response<Body> {
+        self.respond_at(request_headers, SystemTime::now())
+    }
+
+    /// Same as [`JwksEndpoint::respond`], with an explicit current time.
+    pub fn respond_at(&self, request_headers: &HeaderMap, now: SystemTime) -> Response<Body> {
+        respond(&self.document.get(), JWK_SET_CONTENT_TYPE, request_headers, now)
+    }
+}
+
+/// # Serves OpenID Connect discovery metadata over HTTP.
+///
+/// The endpoint answers requests for [`OPENID_CONFIGURATION_PATH`] the way [`JwksEndpoint`]
+/// answers requests for the JWKS, with `Content-Type: application/json`. The advertised
+/// algorithms must follow the keys: serve both documents with [`ProviderEndpoints`], which
+/// rebuilds the metadata whenever the JWKS changes.
+///
+/// Requires the `server` feature.
+///
+/// ## Example
+/// ```rust
+/// use std::time::{Duration, SystemTime};
+/// use jwk_kit::keystore::{KeyAlgorithm, KeyStore};
+/// use jwk_kit::oidc::ProviderMetadataBuilder;
+/// use jwk_kit::server::{DiscoveryEndpoint, JwksEndpoint};
+///
+/// let mut store = KeyStore::new(KeyAlgorithm::Es256);
+/// store.rotate().unwrap();
+/// let jwks = store.published_keys();
+///
+/// let metadata = ProviderMetadataBuilder::new("https://auth.example.com")
+///     .set_authorization_endpoint("https://auth.example.com/authorize")
+///     .set_keys(&jwks)
+///     .set_require_rs256(false)
+///     .build()
+///     .unwrap();
+///
+/// let max_age = Duration::from_secs(3600);
+/// let jwks_endpoint = JwksEndpoint::new(&jwks, max_age).unwrap();
+/// let discovery = DiscoveryEndpoint::new(&metadata, max_age).unwrap();
+/// let app: axum::Router = discovery.route(jwks_endpoint.router());
+/// ```
+#[derive(Debug, Clone)]
+pub struct DiscoveryEndpoint {
+    document: SharedDocument,
+}
+
+impl DiscoveryEndpoint {
+    /// Creates an endpoint serving `metadata`, cacheable for `max_age`.
+    ///
+    /// # Errors
+    /// - `JwkError::InvalidJson` if the metadata cannot be serialized.
+    pub fn new(metadata: &ProviderMetadata, max_age: Duration) -> Result<Self, JwkError> {
+        Ok(Self { document: SharedDocument::new(metadata_document(metadata, Freshness::MaxAge(max_age))?) })
+    }
+
+    /// Replaces the served metadata.
+    ///
+    /// # Errors
+    /// The same errors as [`DiscoveryEndpoint::new`]. The previous metadata is still served.
+    pub fn update(&self, metadata: &ProviderMetadata, max_age: Duration) -> Result<(), JwkError> {
+        self.document.set(metadata_document(metadata, Freshness::MaxAge(max_age))?);
+        Ok(())
+    }
+
+    /// Returns the current `ETag`, quotes included.
+    pub fn etag(&self) -> String {
+        self.document.etag()
+    }
+
+    /// Returns a router serving the metadata at [`OPENID_CONFIGURATION_PATH`].
+    pub fn router(&self) -> Router {
+        self.route(Router::new())
+    }
+
+    /// Adds the route serving the metadata at [`OPENID_CONFIGURATION_PATH`] to `router`.
+    pub fn route<S: Clone + Send + Sync + 'static>(&self, router: Router<S>) -> Router<S> {
+        let endpoint = self.clone();
+        router.route(OPENID_CONFIGURATION_PATH, get(move |headers: HeaderMap| async move { endpoint.respond(&headers) }))
+    }
+
+    /// Answers a request with the given headers: `200 OK` with the metadata, or
+    /// `304 Not Modified` when `If-None-Match` matches.
+    pub fn respond(&self, request_headers: &HeaderMap) -> Response<Body> {
+        self.respond_at(request_headers, SystemTime::now())
+    }
+
+    /// Same as [`DiscoveryEndpoint::respond`], with an explicit current time.
+    pub fn respond_at(&self, request_headers: &HeaderMap, now: SystemTime) -> Response<Body> {
+        respond(&self.document.get(), "application/json", request_headers, now)
+    }
+}
+
+/// # Serves a JWKS together with the discovery metadata describing it.
+///
+/// Every update of the keys rebuilds the metadata from the [`ProviderMetadataBuilder`] and the
+/// new JWKS, and replaces both documents, so that `id_token_signing_alg_values_supported`
+/// never lags behind the published keys. If the metadata cannot be built, neither document
+/// changes.
+///
+/// Requires the `server` feature.
+///
+/// ## Example
+/// ```rust
+/// use jwk_kit::keystore::rotation::{RotationDriver, RotationPolicy, SystemClock};
+/// use jwk_kit::keystore::{KeyAlgorithm, KeyStore};
+/// use jwk_kit::oidc::ProviderMetadataBuilder;
+/// use jwk_kit::server::ProviderEndpoints;
+/// use std::time::Duration;
+///
+/// const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+///
+/// let policy = RotationPolicy::new(30 * DAY, DAY, 7 * DAY);
+/// let mut driver = RotationDriver::new(KeyStore::new(KeyAlgorithm::Rs256 { bits: 2048 }), policy, SystemClock);
+///
+/// let mut metadata = ProviderMetadataBuilder::new("https://auth.example.com");
+/// metadata.set_authorization_endpoint("https://auth.example.com/authorize");
+///
+/// let endpoints = ProviderEndpoints::from_step(&metadata, &driver.tick().unwrap()).unwrap();
+/// let app: axum::Router = endpoints.router();
+///
+/// // After every tick:
+/// endpoints.update_from_step(&driver.tick().unwrap()).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProviderEndpoints {
+    metadata: ProviderMetadataBuilder,
+    jwks: JwksEndpoint,
+    discovery: DiscoveryEndpoint,
+}
+
+impl ProviderEndpoints {
+    /// Creates the endpoints serving `jwks` and the metadata built from `metadata` and `jwks`,
+    /// both cacheable for `max_age`.
+    ///
+    /// # Errors
+    /// - Any error returned by [`ProviderMetadataBuilder::build`].
+    /// - `JwkError::InvalidJson` if a document cannot be serialized.
+    pub fn new(metadata: &ProviderMetadataBuilder, jwks: &Jwks, max_age: Duration) -> Result<Self, JwkError> {
+        Self::with_documents(metadata, jwks, Freshness::MaxAge(max_age))
+    }
+
+    /// Creates the endpoints serving the JWKS of a rotation step and the metadata built from
+    /// it, both cacheable until its `fresh_until`.
+    ///
+    /// # Errors
+    /// The same errors as [`ProviderEndpoints::new`].
+    pub fn from_step(metadata: &ProviderMetadataBuilder, step: &RotationStep) -> Result<Self, JwkError> {
+        Self::with_documents(metadata, &step.jwks, Freshness::Until(step.fresh_until))
+    }
+
+    /// Replaces the served keys, and the metadata with one rebuilt from them.
+    ///
+    /// # Errors
+    /// The same errors as [`ProviderEndpoints::new`]. The previous documents are still served.
+    pub fn update(&self, jwks: &Jwks, max_age: Duration) -> Result<(), JwkError> {
+        self.set_documents(jwks, Freshness::MaxAge(max_age))
+    }
+
+    /// Replaces the served keys with the JWKS of a rotation step, and the metadata with one
+    /// rebuilt from them.
+    ///
+    /// # Errors
+    /// The same errors as [`ProviderEndpoints::new`]. The previous documents are still served.
+    pub fn update_from_step(&self, step: &RotationStep) -> Result<(), JwkError> {
+        self.set_documents(&step.jwks, Freshness::Until(step.fresh_until))
+    }
+
+    pub fn jwks_endpoint(&self) -> &JwksEndpoint {
+        &self.jwks
+    }
+
+    pub fn discovery_endpoint(&self) -> &DiscoveryEndpoint {
+        &self.discovery
+    }
+
+    /// Returns a router serving the keys at [`JWKS_PATH`] and the metadata at
+    /// [`OPENID_CONFIGURATION_PATH`].
+    pub fn router(&self) -> Router {
+        self.route(Router::new())
+    }
+
+    /// Adds the routes serving the keys and the metadata to `router`.
+    pub fn route<S: Clone + Send + Sync + 'static>(&self, router: Router<S>) -> Router<S> {
+        self.discovery.route(self.jwks.route(router))
+    }
+
+    fn with_documents(metadata: &ProviderMetadataBuilder, jwks: &Jwks, freshness: Freshness) -> Result<Self, JwkError> {
+        let (jwks_document, metadata_document) = documents(metadata, jwks, freshness)?;
+        Ok(Self {
+            metadata: metadata.clone(),
+            jwks: JwksEndpoint { document: SharedDocument::new(jwks_document) },
+            discovery: DiscoveryEndpoint { document: SharedDocument::new(metadata_document) },
+        })
+    }
+
+    fn set_documents(&self, jwks: &Jwks, freshness: Freshness) -> Result<(), JwkError> {
+        let (jwks_document, metadata_document) = documents(&self.metadata, jwks, freshness)?;
+        self.jwks.document.set(jwks_document);
+        self.discovery.document.set(metadata_document);
+        Ok(())
+    }
+}
+
+/// Serializes `jwks` and the metadata built from `metadata` and `jwks`.
+fn documents(metadata: &ProviderMetadataBuilder, jwks: &Jwks, freshness: Freshness) -> Result<(Document, Document), JwkError> {
+    let mut metadata = metadata.clone();
+    let metadata = metadata.set_keys(jwks).build()?;
+    Ok((jwks_document(jwks, freshness)?, metadata_document(&metadata, freshness)?))
+}
+
+/// A document shared by the clones of an endpoint, and replaced atomically.
+#[derive(Debug, Clone)]
+struct SharedDocument(Arc<RwLock<Arc<Document>>>);
+
+impl SharedDocument {
+    fn new(document: Document) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(document))))
+    }
+
+    fn get(&self) -> Arc<Document> {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn set(&self, document: Document) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(document);
+    }
+
+    fn etag(&self) -> String {
+        self.get().etag.to_str().unwrap_or_default().to_string()
+    }
+}
+
+fn jwks_document(jwks: &Jwks, freshness: Freshness) -> Result<Document, JwkError> {
+    let body = jwks.to_public().serialize_public()?.into_bytes();
+    Ok(document(body, freshness))
+}
+
+fn metadata_document(metadata: &ProviderMetadata, freshness: Freshness) -> Result<Document, JwkError> {
+    let body = serde_json::to_vec(metadata).map_err(|e| JwkError::InvalidJson(e.to_string()))?;
+    Ok(document(body, freshness))
+}
+
+fn document(body: Vec<u8>, freshness: Freshness) -> Document {
+    let etag = format!("\"{}\"", URL_SAFE_NO_PAD.encode(Sha256::digest(&body)));
+
+    Document {
+        body,
+        etag: HeaderValue::from_str(&etag).expect("base64url is a valid header value"),
+        freshness,
+    }
+}
+
+fn respond(document: &Document, content_type: &'static str, request_headers: &HeaderMap, now: SystemTime) -> Response<Body> {
+    let not_modified = request_headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|tag| etag_matches(tag.trim(), &document.etag));
+
+    let builder = Response::builder()
+        .header(header::ETAG, document.etag.clone())
+        .header(header::CACHE_CONTROL, document.freshness.cache_control(now));
+
+    let response = if not_modified {
+        builder.status(StatusCode::NOT_MODIFIED).body(Body::empty())
+    } else {
+        builder
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(document.body.clone()))
+    };
+
+    response.expect("static headers are valid")
+}
+
+/// Compares an entity tag from `If-None-Match` with `etag`, using the weak comparison
+/// required for this header ([RFC 9110, section 13.1.2](https://www.rfc-editor.org/rfc/rfc9110#section-13.1.2)).
+fn etag_matches(tag: &str, etag: &HeaderValue) -> bool {
+    tag == "*" || tag.strip_prefix("W/").unwrap_or(tag).as_bytes() == etag.as_bytes()
+}
@@ -0,0 +1,379 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use crate::error::JwkError;
+use crate::generator::ecdsa::{jwk_to_es256_public_key, jwk_to_es256_secret_key};
+use crate::generator::rsa::{jwk_to_rsa_private_key, jwk_to_rsa_public_key};
+use crate::jwk::{Jwk, Jwks, KeyOperation, from_unix_seconds, unix_seconds};
+use crate::lint::alg_matches_key;
+use rsa::pkcs1v15;
+use rsa::signature::{SignatureEncoding, Signer, Verifier};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::time::{Duration, SystemTime};
+
+/// The `typ` header of a signed JWKS, as used by OpenID Federation.
+pub const SIGNED_JWKS_TYPE: &str = "jwk-set+jwt";
+
+/// # Signs a `Jwks` as a JWS.
+///
+/// The result is a compact JWS whose payload is the key set, with the optional `iss`, `sub`,
+/// `iat` and `exp` claims, and whose header has `typ` set to [`SIGNED_JWKS_TYPE`] and `kid`
+/// set to the `kid` of the signing key. The signing key is usually a long-lived key pinned by
+/// the relying parties, separate from the keys it signs, which can then rotate freely.
+///
+/// RSA keys sign with `RS256` and P-256 keys with `ES256`.
+///
+/// ## Example
+/// ```rust
+/// use std::time::Duration;
+/// use jwk_kit::keystore::{KeyAlgorithm, KeyStore};
+/// use jwk_kit::jwk::create_jwks;
+/// use jwk_kit::signed::{JwksSigner, JwksVerifier};
+///
+/// let root = KeyAlgorithm::Es256.generate().unwrap();
+/// let mut store = KeyStore::new(KeyAlgorithm::Es256);
+/// store.rotate().unwrap();
+///
+/// let token = JwksSigner::new(&root)
+///     .unwrap()
+///     .set_issuer("https://auth.example.com")
+///     .set_lifetime(Duration::from_secs(24 * 3600))
+///     .sign(&store.published_keys())
+///     .unwrap();
+///
+/// // Relying parties pin the public root key.
+/// let verified = JwksVerifier::new(&create_jwks(vec![root.to_public()])).verify(&token).unwrap();
+/// assert_eq!(verified.jwks, store.published_keys());
+/// ```
+#[derive(Debug, Clone)]
+pub struct JwksSigner {
+    key: Jwk,
+    alg: &'static str,
+    issuer: Option<String>,
+    subject: Option<String>,
+    lifetime: Option<Duration>,
+}
+
+impl JwksSigner {
+    /// Creates a signer using the private key `key`.
+    ///
+    /// # Errors
+    /// - `JwkError::SigningFailed` if `key` has no private members.
+    /// - `JwkError::OperationNotPermitted` if the key's `use` or `key_ops` do not allow `sign`.
+    /// - `JwkError::UnsupportedAlgorithm` if the key is neither an RSA nor a P-256 key, or its
+    ///   `alg` is not the algorithm it would sign with.
+    pub fn new(key: &Jwk) -> Result<Self, JwkError> {
+        if !key.is_private() {
+            return Err(JwkError::SigningFailed("a private key is required".into()));
+        }
+        key.check_operation(KeyOperation::Sign)?;
+        let alg = signing_alg(key)?;
+
+        Ok(Self { key: key.clone(), alg, issuer: None, subject: None, lifetime: None })
+    }
+
+    /// Sets the `iss` claim.
+    pub fn set_issuer(&mut self, value: &str) -> &mut Self {
+        self.issuer = Some(value.to_string());
+        self
+    }
+
+    /// Sets the `sub` claim.
+    pub fn set_subject(&mut self, value: &str) -> &mut Self {
+        self.subject = Some(value.to_string());
+        self
+    }
+
+    /// Sets the `exp` claim to this long after the time of signing.
+    pub fn set_lifetime(&mut self, value: Duration) -> &mut Self {
+        self.lifetime = Some(value);
+        self
+    }
+
+    /// Signs `jwks` now.
+    ///
+    /// # Errors
+    /// The same errors as [`JwksSigner::sign_at`].
+    pub fn sign(&self, jwks: &Jwks) -> Result<String, JwkError> {
+        self.sign_at(jwks, SystemTime::now())
+    }
+
+    /// Signs `jwks`, with `iat` set to `now`.
+    ///
+    /// # Errors
+    /// - `JwkError::PrivateKeyMaterial` if `jwks` holds private keys; sign its
+    ///   [public projection](Jwks::to_public) instead.
+    /// - `JwkError::SigningFailed` if the lifetime ends past the range of `exp`, or signing fails.
+    /// - Any key conversion error.
+    pub fn sign_at(&self, jwks: &Jwks, now: SystemTime) -> Result<String, JwkError> {
+        jwks.serialize_public()?;
+        let expires_at = match self.lifetime {
+            Some(lifetime) => Some(now.checked_add(lifetime).ok_or_else(|| JwkError::SigningFailed("the lifetime is too long".into()))?),
+            None => None,
+        };
+
+        let header = Header { alg: self.alg.to_string(), typ: Some(SIGNED_JWKS_TYPE.to_string()), kid: self.key.kid.clone(), crit: None };
+        let payload = Payload {
+            keys: jwks.keys.clone(),
+            iss: self.issuer.clone(),
+            sub: self.subject.clone(),
+            iat: Some(unix_seconds(now)),
+            exp: expires_at.map(unix_seconds),
+        };
+
+        let signing_input = format!("{}.{}", encode(&header)?, encode(&payload)?);
+        let signature = match self.alg {
+            "RS256" => {
+                let signer = pkcs1v15::SigningKey::<Sha256>::new(jwk_to_rsa_private_key(&self.key)?);
+                signer.try_sign(signing_input.as_bytes()).map_err(signature_error)?.to_vec()
+            }
+            _ => {
+                let signer = p256::ecdsa::SigningKey::from(jwk_to_es256_secret_key(&self.key)?);
+                let signature: p256::ecdsa::Signature = signer.try_sign(signing_input.as_bytes()).map_err(signature_error)?;
+                signature.to_vec()
+            }
+        };
+
+        Ok(format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature)))
+    }
+}
+
+/// A signed JWKS whose signature has been verified.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedJwks {
+    /// The signed key set.
+    pub jwks: Jwks,
+
+    /// The `kid` of the key that signed it, if the header names one.
+    pub signed_by: Option<String>,
+
+    pub issuer: Option<String>,
+    pub subject: Option<String>,
+    pub issued_at: Option<SystemTime>,
+    pub expires_at: Option<SystemTime>,
+}
+
+/// # Verifies signed JWKS documents against pinned keys.
+///
+/// A document is accepted only if it is signed with `RS256` or `ES256` by one of the trusted
+/// keys, selected by the `kid` of its header (or tried in turn when there is none). A trusted
+/// key must be valid at the time of verification (see [`Jwk::is_valid_at`]), accept `verify`
+/// (see [`Jwk::permits`]) and, if it has an `alg`, match the document's. The `typ` header, when present, must be [`SIGNED_JWKS_TYPE`], so that other
+/// kinds of tokens signed by the same key are not mistaken for key sets. No header extension
+/// is supported, so documents with a `crit` header are rejected (RFC 7515, section 4.1.11).
+///
+/// Documents without an `exp` claim or past it, documents issued in the future, documents from
+/// another issuer than the one set with [`JwksVerifier::set_issuer`], and documents holding
+/// invalid or private keys are rejected. Documents without `exp` can be accepted with
+/// [`JwksVerifier::set_require_expiry`], preferably bounding their age with
+/// [`JwksVerifier::set_max_age`].
+#[derive(Debug, Clone)]
+pub struct JwksVerifier {
+    trusted: Jwks,
+    issuer: Option<String>,
+    require_expiry: bool,
+    max_age: Option<Duration>,
+}
+
+impl JwksVerifier {
+    /// Creates a verifier accepting documents signed by one of the `trusted` keys.
+    pub fn new(trusted: &Jwks) -> Self {
+        Self { trusted: trusted.clone(), issuer: None, require_expiry: true, max_age: None }
+    }
+
+    /// Requires the `iss` claim of documents to equal `value`.
+    pub fn set_issuer(&mut self, value: &str) -> &mut Self {
+        self.issuer = Some(value.to_string());
+        self
+    }
+
+    /// Sets whether documents must have an `exp` claim. Defaults to `true`.
+    pub fn set_require_expiry(&mut self, value: bool) -> &mut Self {
+        self.require_expiry = value;
+        self
+    }
+
+    /// Requires documents to have an `iat` claim, and rejects them once they are older than
+    /// `value`, whatever their `exp`.
+    pub fn set_max_age(&mut self, value: Duration) -> &mut Self {
+        self.max_age = Some(value);
+        self
+    }
+
+    /// Verifies a signed JWKS now.
+    ///
+    /// # Errors
+    /// The same errors as [`JwksVerifier::verify_at`].
+    pub fn verify(&self, token: &str) -> Result<SignedJwks, JwkError> {
+        self.verify_at(token, SystemTime::now())
+    }
+
+    /// Verifies a signed JWKS at `now`.
+    ///
+    /// # Errors
+    /// - `JwkError::InvalidToken` if the document is not a compact JWS of a key set, has
+    ///   another `typ` or a `crit` header, has no `exp` when one is required, has expired,
+    ///   was issued after `now`, or is older than the maximum age.
+    /// - `JwkError::UnsupportedAlgorithm` if its `alg` is neither `RS256` nor `ES256`.
+    /// - `JwkError::UnknownKeyId` if no trusted key valid at `now` has the `kid` of its header.
+    /// - `JwkError::InvalidSignature` if no trusted key can verify its signature.
+    /// - `JwkError::UntrustedIssuer` if its `iss` is not the expected issuer.
+    /// - `JwkError::PrivateKeyMaterial` or any validation error of the keys it holds.
+    pub fn verify_at(&self, token: &str, now: SystemTime) -> Result<SignedJwks, JwkError> {
+        let mut parts = token.trim().split('.');
+        let (Some(header_b64), Some(payload_b64), Some(signature_b64), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(JwkError::InvalidToken("expected a compact JWS with three parts".into()));
+        };
+
+        let header: Header = decode(header_b64, "header")?;
+        if !matches!(header.alg.as_str(), "RS256" | "ES256") {
+            return Err(JwkError::UnsupportedAlgorithm(header.alg));
+        }
+        if header.typ.as_deref().is_some_and(|typ| !typ.eq_ignore_ascii_case(SIGNED_JWKS_TYPE)) {
+            return Err(JwkError::InvalidToken(format!("unexpected typ '{}'", header.typ.unwrap_or_default())));
+        }
+        if let Some(crit) = &header.crit {
+            return Err(JwkError::InvalidToken(format!("unsupported critical header parameters {:?}", crit)));
+        }
+
+        let candidates: Vec<&Jwk> = match &header.kid {
+            Some(kid) => vec![self.trusted.find_by_kid_at(kid, now).ok_or_else(|| JwkError::UnknownKeyId(kid.clone()))?],
+            None => self.trusted.valid_at(now).collect(),
+        };
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature_b64)
+            .map_err(|_| JwkError::InvalidToken("the signature is not base64url".into()))?;
+        let signing_input = &token.trim()[..header_b64.len() + 1 + payload_b64.len()];
+        if !candidates.into_iter().any(|key| verifies(key, &header.alg, signing_input.as_bytes(), &signature)) {
+            return Err(JwkError::InvalidSignature);
+        }
+
+        let payload: Payload = decode(payload_b64, "payload")?;
+        let issued_at = payload.iat.map(|iat| claim_time(iat, "iat")).transpose()?;
+        let expires_at = payload.exp.map(|exp| claim_time(exp, "exp")).transpose()?;
+        if self.require_expiry && expires_at.is_none() {
+            return Err(JwkError::InvalidToken("the signed JWKS has no 'exp'".into()));
+        }
+        if expires_at.is_some_and(|expires_at| now >= expires_at) {
+            return Err(JwkError::InvalidToken("the signed JWKS has expired".into()));
+        }
+        if issued_at.is_some_and(|issued_at| issued_at > now) {
+            return Err(JwkError::InvalidToken("the signed JWKS was issued in the future".into()));
+        }
+        if let Some(max_age) = self.max_age {
+            let issued_at = issued_at.ok_or_else(|| JwkError::InvalidToken("the signed JWKS has no 'iat'".into()))?;
+            if now.duration_since(issued_at).is_ok_and(|age| age >= max_age) {
+                return Err(JwkError::InvalidToken("the signed JWKS is too old".into()));
+            }
+        }
+        if let Some(expected) = &self.issuer
+            && payload.iss.as_ref() != Some(expected)
+        {
+            return Err(JwkError::UntrustedIssuer(payload.iss.unwrap_or_default()));
+        }
+
+        let jwks = Jwks { keys: payload.keys };
+        jwks.serialize_public()?;
+        jwks.keys.iter().try_for_each(Jwk::validate)?;
+
+        Ok(SignedJwks {
+            jwks,
+            signed_by: header.kid,
+            issuer: payload.iss,
+            subject: payload.sub,
+            issued_at,
+            expires_at,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    alg: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    typ: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    crit: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Payload {
+    keys: Vec<Jwk>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iss: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sub: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iat: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exp: Option<u64>,
+}
+
+/// Returns the algorithm `key` signs with, checking it against the key's own `alg`.
+fn signing_alg(key: &Jwk) -> Result<&'static str, JwkError> {
+    let alg = match (key.kty.as_str(), key.crv.as_deref()) {
+        ("RSA", _) => "RS256",
+        ("EC", Some("P-256")) => "ES256",
+        _ => return Err(JwkError::UnsupportedAlgorithm(format!("cannot sign a JWKS with a {} key", key.kty))),
+    };
+
+    match key.alg.as_deref() {
+        Some(key_alg) if key_alg != alg => Err(JwkError::UnsupportedAlgorithm(key_alg.to_string())),
+        _ => Ok(alg),
+    }
+}
+
+/// Whether `key` may verify `alg` signatures, and verifies `signature` over `message`.
+fn verifies(key: &Jwk, alg: &str, message: &[u8], signature: &[u8]) -> bool {
+    if !key.permits(KeyOperation::Verify)
+        || !alg_matches_key(alg, key)
+        || key.alg.as_deref().is_some_and(|key_alg| key_alg != alg)
+    {
+        return false;
+    }
+
+    match alg {
+        "RS256" => {
+            let (Ok(public_key), Ok(signature)) = (jwk_to_rsa_public_key(key), pkcs1v15::Signature::try_from(signature)) else {
+                return false;
+            };
+            pkcs1v15::VerifyingKey::<Sha256>::new(public_key).verify(message, &signature).is_ok()
+        }
+        _ => {
+            let (Ok(public_key), Ok(signature)) = (jwk_to_es256_public_key(key), p256::ecdsa::Signature::from_slice(signature)) else {
+                return false;
+            };
+            p256::ecdsa::VerifyingKey::from(public_key).verify(message, &signature).is_ok()
+        }
+    }
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<String, JwkError> {
+    let json = serde_json::to_vec(value).map_err(|e| JwkError::InvalidJson(e.to_string()))?;
+    Ok(URL_SAFE_NO_PAD.encode(json))
+}
+
+fn decode<T: for<'de> Deserialize<'de>>(part: &str, name: &str) -> Result<T, JwkError> {
+    let json = URL_SAFE_NO_PAD
+        .decode(part)
+        .map_err(|_| JwkError::InvalidToken(format!("the {} is not base64url", name)))?;
+    serde_json::from_slice(&json).map_err(|e| JwkError::InvalidToken(format!("invalid {}: {}", name, e)))
+}
+
+fn claim_time(seconds: u64, name: &str) -> Result<SystemTime, JwkError> {
+    from_unix_seconds(seconds).ok_or_else(|| JwkError::InvalidToken(format!("'{}' is out of range", name)))
+}
+
+fn signature_error(error: rsa::signature::Error) -> JwkError {
+    JwkError::SigningFailed(error.to_string())
+}
@@ -0,0 +1,344 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use crate::client::JwksClient;
+use crate::error::JwkError;
+use crate::jwk::{Jwk, Jwks, KeyOperation};
+use crate::lint::alg_matches_key;
+use crate::watch::JwksWatcher;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// The algorithms accepted from an issuer unless [`TrustedIssuer::set_algorithms`] is called:
+/// every asymmetric signature algorithm, so that a public key can never be used as an HMAC secret.
+pub const DEFAULT_ALGORITHMS: [&str; 10] =
+    ["RS256", "RS384", "RS512", "PS256", "PS384", "PS512", "ES256", "ES384", "ES512", "EdDSA"];
+
+/// # Provides the keys of an issuer.
+///
+/// It is implemented for a static [`Jwks`], for a [`JwksWatcher`] (key files in a directory),
+/// and for a [`JwksClient`] (a remote JWKS, downloaded again when a `kid` is unknown), as well
+/// as for an `Arc` of any of them.
+pub trait KeySource: Send + Sync {
+    /// Returns every key of the issuer.
+    ///
+    /// # Errors
+    /// Any error preventing the keys from being obtained.
+    fn keys_at(&self, now: SystemTime) -> Result<Arc<Jwks>, JwkError>;
+
+    /// Returns the key with the given `kid` that may be used at `now`.
+    ///
+    /// # Errors
+    /// - `JwkError::UnknownKeyId` if there is no such key.
+    /// - Any error preventing the keys from being obtained.
+    fn find_key_at(&self, kid: &str, now: SystemTime) -> Result<Jwk, JwkError> {
+        self.keys_at(now)?
+            .find_by_kid_at(kid, now)
+            .cloned()
+            .ok_or_else(|| JwkError::UnknownKeyId(kid.to_string()))
+    }
+}
+
+impl KeySource for Jwks {
+    fn keys_at(&self, _now: SystemTime) -> Result<Arc<Jwks>, JwkError> {
+        Ok(Arc::new(self.clone()))
+    }
+
+    fn find_key_at(&self, kid: &str, now: SystemTime) -> Result<Jwk, JwkError> {
+        self.find_by_kid_at(kid, now).cloned().ok_or_else(|| JwkError::UnknownKeyId(kid.to_string()))
+    }
+}
+
+impl KeySource for JwksWatcher {
+    fn keys_at(&self, _now: SystemTime) -> Result<Arc<Jwks>, JwkError> {
+        Ok(self.keys())
+    }
+}
+
+impl KeySource for JwksClient {
+    fn keys_at(&self, now: SystemTime) -> Result<Arc<Jwks>, JwkError> {
+        JwksClient::keys_at(self, now)
+    }
+
+    fn find_key_at(&self, kid: &str, now: SystemTime) -> Result<Jwk, JwkError> {
+        JwksClient::find_key_at(self, kid, now)
+    }
+}
+
+impl<T: KeySource + ?Sized> KeySource for Arc<T> {
+    fn keys_at(&self, now: SystemTime) -> Result<Arc<Jwks>, JwkError> {
+        (**self).keys_at(now)
+    }
+
+    fn find_key_at(&self, kid: &str, now: SystemTime) -> Result<Jwk, JwkError> {
+        (**self).find_key_at(kid, now)
+    }
+}
+
+/// An issuer accepted by a [`TrustStore`], with the source of its keys and its policy.
+pub struct TrustedIssuer {
+    issuer: String,
+    source: Arc<dyn KeySource>,
+    algorithms: Vec<String>,
+    audiences: Vec<String>,
+}
+
+impl fmt::Debug for TrustedIssuer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrustedIssuer")
+            .field("issuer", &self.issuer)
+            .field("algorithms", &self.algorithms)
+            .field("audiences", &self.audiences)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TrustedIssuer {
+    /// Trusts the keys of `source` for tokens whose `iss` is exactly `issuer`.
+    ///
+    /// The issuer accepts the [`DEFAULT_ALGORITHMS`] and any audience.
+    pub fn new(issuer: &str, source: impl KeySource + 'static) -> Self {
+        Self {
+            issuer: issuer.to_string(),
+            source: Arc::new(source),
+            algorithms: DEFAULT_ALGORITHMS.iter().map(|alg| alg.to_string()).collect(),
+            audiences: Vec::new(),
+        }
+    }
+
+    /// Restricts the `alg`s accepted from this issuer. `none` is never accepted.
+    pub fn set_algorithms(&mut self, algorithms: &[&str]) -> &mut Self {
+        self.algorithms = algorithms.iter().map(|alg| alg.to_string()).collect();
+        self
+    }
+
+    /// Requires the `aud` of the issuer's tokens to contain at least one of `audiences`.
+    pub fn set_audiences(&mut self, audiences: &[&str]) -> &mut Self {
+        self.audiences = audiences.iter().map(|aud| aud.to_string()).collect();
+        self
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    pub fn algorithms(&self) -> &[String] {
+        &self.algorithms
+    }
+
+    pub fn audiences(&self) -> &[String] {
+        &self.audiences
+    }
+
+    /// Returns the key to verify a token of this issuer with, given its header and audience.
+    ///
+    /// # Errors
+    /// The same errors as [`TrustStore::resolve`], except `JwkError::UntrustedIssuer`.
+    pub fn resolve_at(&self, header: &TokenHeader, audience: &[String], now: SystemTime) -> Result<Jwk, JwkError> {
+        if header.alg == "none" || !self.algorithms.contains(&header.alg) {
+            return Err(JwkError::UnsupportedAlgorithm(format!(
+                "'{}' is not accepted from issuer '{}'",
+                header.alg, self.issuer
+            )));
+        }
+        if !self.audiences.is_empty() && !audience.iter().any(|aud| self.audiences.contains(aud)) {
+            return Err(JwkError::AudienceMismatch(format!(
+                "expected one of {:?}, got {:?}",
+                self.audiences, audience
+            )));
+        }
+
+        match &header.kid {
+            Some(kid) => {
+                let key = self.source.find_key_at(kid, now)?;
+                if verifies(&key, &header.alg) {
+                    Ok(key)
+                } else {
+                    Err(JwkError::UnsupportedAlgorithm(format!("key '{}' cannot verify '{}'", kid, header.alg)))
+                }
+            }
+            None => {
+                let keys = self.source.keys_at(now)?;
+                let mut candidates = keys.signing_keys_at(now).filter(|key| verifies(key, &header.alg));
+                match (candidates.next(), candidates.next()) {
+                    (Some(key), None) => Ok(key.clone()),
+                    (Some(_), Some(_)) => Err(JwkError::MissingKeyId),
+                    (None, _) => Err(JwkError::UnsupportedAlgorithm(format!(
+                        "no key of issuer '{}' can verify '{}'",
+                        self.issuer, header.alg
+                    ))),
+                }
+            }
+        }
+    }
+}
+
+/// The members of a JOSE header used to select a verification key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenHeader {
+    pub alg: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+}
+
+/// The key a token must be verified with, as resolved by a [`TrustStore`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedKey {
+    /// The `iss` of the token, which is a trusted issuer.
+    pub issuer: String,
+
+    /// The header of the token.
+    pub header: TokenHeader,
+
+    /// The verification key. Its `alg`, if any, equals `header.alg`.
+    pub key: Jwk,
+}
+
+/// # Selects the key to verify a token with, among several trusted issuers.
+///
+/// Each [`TrustedIssuer`] has its own source of keys, algorithm allowlist and expected
+/// audiences. [`TrustStore::resolve`] reads the `iss` and `aud` claims and the header of a
+/// compact JWS, applies the policy of the issuer, and returns the key the signature must be
+/// verified with. The key must accept `verify` (see [`Jwk::permits`]) and suit the `alg`.
+///
+/// The store does not verify signatures, nor claims other than `iss` and `aud`. Since they
+/// are read before the signature is verified, nothing in the token may be trusted until the
+/// signature has been verified with the returned key.
+///
+/// ## Example
+/// ```rust
+/// use jwk_kit::jwk::create_jwks;
+/// use jwk_kit::keystore::KeyAlgorithm;
+/// use jwk_kit::trust::{TrustStore, TrustedIssuer};
+///
+/// let mut key = KeyAlgorithm::Es256.generate().unwrap().to_public();
+/// key.kid = Some("k1".to_string());
+///
+/// let mut issuer = TrustedIssuer::new("https://idp.example.com", create_jwks(vec![key]));
+/// issuer.set_algorithms(&["ES256"]).set_audiences(&["api"]);
+///
+/// let mut store = TrustStore::new();
+/// store.add_issuer(issuer);
+///
+/// // {"alg":"ES256","kid":"k1"}.{"iss":"https://idp.example.com","aud":"api"}.signature
+/// let token = "eyJhbGciOiJFUzI1NiIsImtpZCI6ImsxIn0.\
+///              eyJpc3MiOiJodHRwczovL2lkcC5leGFtcGxlLmNvbSIsImF1ZCI6ImFwaSJ9.c2ln";
+/// let resolved = store.resolve(token).unwrap();
+/// assert_eq!(resolved.key.kid.as_deref(), Some("k1"));
+/// ```
+#[derive(Debug, Default)]
+pub struct TrustStore {
+    issuers: BTreeMap<String, TrustedIssuer>,
+}
+
+impl TrustStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an issuer, replacing any issuer with the same identifier.
+    pub fn add_issuer(&mut self, issuer: TrustedIssuer) -> &mut Self {
+        self.issuers.insert(issuer.issuer.clone(), issuer);
+        self
+    }
+
+    /// Removes an issuer, returning it if it was trusted.
+    pub fn remove_issuer(&mut self, issuer: &str) -> Option<TrustedIssuer> {
+        self.issuers.remove(issuer)
+    }
+
+    /// Returns the trusted issuer with the given identifier.
+    pub fn issuer(&self, issuer: &str) -> Option<&TrustedIssuer> {
+        self.issuers.get(issuer)
+    }
+
+    /// Returns the trusted issuers, ordered by identifier.
+    pub fn issuers(&self) -> impl Iterator<Item = &TrustedIssuer> {
+        self.issuers.values()
+    }
+
+    /// Returns the key to verify a compact JWS `token` with.
+    ///
+    /// # Errors
+    /// - `JwkError::InvalidToken` if the token is not a compact JWS with a JSON header and
+    ///   payload, or has no `iss`.
+    /// - `JwkError::UntrustedIssuer` if its `iss` is not a trusted issuer.
+    /// - `JwkError::UnsupportedAlgorithm` if its `alg` is not accepted from the issuer, or no
+    ///   key can verify it.
+    /// - `JwkError::AudienceMismatch` if the issuer expects audiences and its `aud` has none of them.
+    /// - `JwkError::UnknownKeyId` if the issuer has no key with its `kid`.
+    /// - `JwkError::MissingKeyId` if it has no `kid` and several keys could verify it.
+    /// - Any error of the issuer's [`KeySource`].
+    pub fn resolve(&self, token: &str) -> Result<ResolvedKey, JwkError> {
+        self.resolve_at(token, SystemTime::now())
+    }
+
+    /// Same as [`TrustStore::resolve`], selecting keys valid at `now`.
+    ///
+    /// # Errors
+    /// The same errors as [`TrustStore::resolve`].
+    pub fn resolve_at(&self, token: &str, now: SystemTime) -> Result<ResolvedKey, JwkError> {
+        let (header, claims) = decode_token(token)?;
+        let issuer = self
+            .issuers
+            .get(&claims.iss)
+            .ok_or_else(|| JwkError::UntrustedIssuer(claims.iss.clone()))?;
+        let key = issuer.resolve_at(&header, &claims.aud.0, now)?;
+
+        Ok(ResolvedKey { issuer: claims.iss, header, key })
+    }
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    iss: String,
+
+    #[serde(default)]
+    aud: Audience,
+}
+
+/// The `aud` claim, which is a string or an array of strings.
+#[derive(Default)]
+struct Audience(Vec<String>);
+
+impl<'de> Deserialize<'de> for Audience {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Value {
+            One(String),
+            Many(Vec<String>),
+        }
+
+        Ok(match Value::deserialize(deserializer)? {
+            Value::One(aud) => Audience(vec![aud]),
+            Value::Many(aud) => Audience(aud),
+        })
+    }
+}
+
+fn decode_token(token: &str) -> Result<(TokenHeader, Claims), JwkError> {
+    let mut parts = token.trim().split('.');
+    let (Some(header), Some(payload), Some(_), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+        return Err(JwkError::InvalidToken("expected a compact JWS with three parts".into()));
+    };
+
+    Ok((decode_part(header, "header")?, decode_part(payload, "payload")?))
+}
+
+fn decode_part<T: for<'de> Deserialize<'de>>(part: &str, name: &str) -> Result<T, JwkError> {
+    let json = URL_SAFE_NO_PAD
+        .decode(part)
+        .map_err(|_| JwkError::InvalidToken(format!("the {} is not base64url", name)))?;
+    serde_json::from_slice(&json).map_err(|e| JwkError::InvalidToken(format!("invalid {}: {}", name, e)))
+}
+
+/// Whether `key` may verify signatures made with `alg`.
+fn verifies(key: &Jwk, alg: &str) -> bool {
+    key.permits(KeyOperation::Verify) && alg_matches_key(alg, key) && key.alg.as_deref().is_none_or(|key_alg| key_alg == alg)
+}
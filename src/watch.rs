@@ -0,0 +1,175 @@
+use crate::error::JwkError;
+use crate::jwk::Jwks;
+use crate::loader::{file_io_error, key_files, load_dir};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+type ErrorHandler = Box<dyn Fn(&JwkError) + Send + Sync>;
+
+/// # A `Jwks` kept in sync with a directory of key files.
+///
+/// The watcher loads the directory with [`load_dir`], and reloads it whenever the files in it
+/// change. A new `Jwks` replaces the current one only if the whole directory loads
+/// successfully; otherwise the current keys are kept and the error handler is called, once
+/// per change of the directory.
+///
+/// Readers obtain the current keys with [`JwksWatcher::keys`], which returns a shared snapshot
+/// and never blocks on a reload in progress. The directory is polled, either explicitly with
+/// [`JwksWatcher::check`] or by a background thread started with [`JwksWatcher::watch`]; this
+/// also picks up Kubernetes secret volumes, whose files are swapped by replacing a symbolic link.
+///
+/// ## Example
+/// ```rust
+/// use std::time::Duration;
+/// use jwk_kit::watch::JwksWatcher;
+///
+/// let watcher = JwksWatcher::new("./examples/webcrypto").unwrap();
+/// watcher.set_error_handler(|error| eprintln!("keeping the current keys: {}", error));
+/// let _handle = watcher.watch(Duration::from_secs(10));
+///
+/// assert!(!watcher.keys().keys.is_empty());
+/// ```
+#[derive(Clone)]
+pub struct JwksWatcher {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    dir: PathBuf,
+    current: RwLock<Arc<Jwks>>,
+    fingerprint: Mutex<[u8; 32]>,
+    on_error: Mutex<Option<ErrorHandler>>,
+}
+
+impl fmt::Debug for JwksWatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwksWatcher")
+            .field("dir", &self.inner.dir)
+            .field("keys", &self.keys().keys.len())
+            .finish()
+    }
+}
+
+impl JwksWatcher {
+    /// Loads the keys in `dir`.
+    ///
+    /// # Errors
+    /// Any error returned by [`load_dir`]: a watcher always starts with a valid set of keys.
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, JwkError> {
+        let dir = dir.as_ref().to_path_buf();
+        let fingerprint = fingerprint(&dir)?;
+        let jwks = load_dir(&dir)?;
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                dir,
+                current: RwLock::new(Arc::new(jwks)),
+                fingerprint: Mutex::new(fingerprint),
+                on_error: Mutex::new(None),
+            }),
+        })
+    }
+
+    /// Returns the watched directory.
+    pub fn dir(&self) -> &Path {
+        &self.inner.dir
+    }
+
+    /// Returns the current keys.
+    pub fn keys(&self) -> Arc<Jwks> {
+        self.inner.current.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Sets the function called when the directory changes but cannot be loaded.
+    pub fn set_error_handler(&self, handler: impl Fn(&JwkError) + Send + Sync + 'static) {
+        *self.inner.on_error.lock().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(handler));
+    }
+
+    /// Reloads the directory if its files changed since the last check.
+    ///
+    /// # Returns
+    /// `true` if the keys were replaced, `false` if nothing changed.
+    ///
+    /// # Errors
+    /// Any error returned by [`load_dir`], after passing it to the error handler. The current
+    /// keys are kept, and the same files are not reported again.
+    pub fn check(&self) -> Result<bool, JwkError> {
+        let result = self.reload();
+        if let Err(error) = &result
+            && let Some(handler) = self.inner.on_error.lock().unwrap_or_else(|e| e.into_inner()).as_ref()
+        {
+            handler(error);
+        }
+        result
+    }
+
+    /// Starts a thread that calls [`JwksWatcher::check`] every `interval`, until the returned
+    /// handle is dropped.
+    pub fn watch(&self, interval: Duration) -> WatchHandle {
+        let watcher = self.clone();
+        let (stop, stopped) = mpsc::channel::<()>();
+
+        let thread = thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                // Errors are reported to the error handler.
+                let _ = watcher.check();
+            }
+        });
+
+        WatchHandle { stop: Some(stop), thread: Some(thread) }
+    }
+
+    fn reload(&self) -> Result<bool, JwkError> {
+        let fingerprint = fingerprint(&self.inner.dir)?;
+        {
+            let mut last = self.inner.fingerprint.lock().unwrap_or_else(|e| e.into_inner());
+            if *last == fingerprint {
+                return Ok(false);
+            }
+            *last = fingerprint;
+        }
+
+        let jwks = load_dir(&self.inner.dir)?;
+        *self.inner.current.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(jwks);
+
+        Ok(true)
+    }
+}
+
+/// Stops the thread started by [`JwksWatcher::watch`] when dropped.
+#[derive(Debug)]
+pub struct WatchHandle {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Hashes the names and contents of the files [`load_dir`] would read.
+fn fingerprint(dir: &Path) -> Result<[u8; 32], JwkError> {
+    let mut hasher = Sha256::new();
+    for path in key_files(dir)? {
+        let contents = fs::read(&path).map_err(|e| file_io_error(&path, e))?;
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+        hasher.update((name.len() as u64).to_be_bytes());
+        hasher.update(name.as_bytes());
+        hasher.update((contents.len() as u64).to_be_bytes());
+        hasher.update(&contents);
+    }
+
+    Ok(hasher.finalize().into())
+}
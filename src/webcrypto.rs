@@ -0,0 +1,254 @@
+use crate::error::JwkError;
+use crate::generator::rsa::{jwk_to_rsa_private_key, rsa_private_key_to_jwk};
+use crate::jwk::{Jwk, KeyOperation};
+use crate::lint::alg_matches_key;
+
+/// The hash function parameter of a WebCrypto algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebCryptoHash {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl WebCryptoHash {
+    fn bits(&self) -> &'static str {
+        match self {
+            WebCryptoHash::Sha1 => "1",
+            WebCryptoHash::Sha256 => "256",
+            WebCryptoHash::Sha384 => "384",
+            WebCryptoHash::Sha512 => "512",
+        }
+    }
+}
+
+/// A WebCrypto algorithm, as passed to `crypto.subtle.importKey` / `generateKey`.
+///
+/// WebCrypto identifies RSA keys by a JWA `alg` that encodes both the scheme and the hash
+/// (`RS256`, `PS384`, `RSA-OAEP` for SHA-1, `RSA-OAEP-256`, ...), and exports EC keys
+/// without any `alg`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebCryptoAlgorithm {
+    /// `RSASSA-PKCS1-v1_5` (`RS256`, `RS384`, `RS512`).
+    RsassaPkcs1V15(WebCryptoHash),
+
+    /// `RSA-PSS` (`PS256`, `PS384`, `PS512`).
+    RsaPss(WebCryptoHash),
+
+    /// `RSA-OAEP` (`RSA-OAEP`, `RSA-OAEP-256`, `RSA-OAEP-384`, `RSA-OAEP-512`).
+    RsaOaep(WebCryptoHash),
+
+    /// `ECDSA` on the key's curve.
+    Ecdsa,
+
+    /// `ECDH` on the key's curve.
+    Ecdh,
+
+    /// `HMAC` (`HS256`, `HS384`, `HS512`).
+    Hmac(WebCryptoHash),
+}
+
+impl WebCryptoAlgorithm {
+    /// Returns the `alg` WebCrypto writes for this algorithm, or `None` for ECDSA and ECDH.
+    pub fn jwk_alg(&self) -> Option<String> {
+        match self {
+            WebCryptoAlgorithm::RsassaPkcs1V15(hash) => Some(format!("RS{}", hash.bits())),
+            WebCryptoAlgorithm::RsaPss(hash) => Some(format!("PS{}", hash.bits())),
+            WebCryptoAlgorithm::RsaOaep(WebCryptoHash::Sha1) => Some("RSA-OAEP".to_string()),
+            WebCryptoAlgorithm::RsaOaep(hash) => Some(format!("RSA-OAEP-{}", hash.bits())),
+            WebCryptoAlgorithm::Hmac(hash) => Some(format!("HS{}", hash.bits())),
+            WebCryptoAlgorithm::Ecdsa | WebCryptoAlgorithm::Ecdh => None,
+        }
+    }
+
+    /// Returns the `use` WebCrypto accepts on import for this algorithm.
+    pub fn key_use(&self) -> &'static str {
+        match self {
+            WebCryptoAlgorithm::RsaOaep(_) | WebCryptoAlgorithm::Ecdh => "enc",
+            _ => "sig",
+        }
+    }
+
+    /// Returns the key usages WebCrypto allows for a public or private (or secret) key.
+    pub fn key_ops(&self, private: bool) -> &'static [KeyOperation] {
+        use KeyOperation::*;
+
+        match (self, private) {
+            (WebCryptoAlgorithm::Hmac(_), _) => &[Sign, Verify],
+            (WebCryptoAlgorithm::RsaOaep(_), false) => &[Encrypt, WrapKey],
+            (WebCryptoAlgorithm::RsaOaep(_), true) => &[Decrypt, UnwrapKey],
+            (WebCryptoAlgorithm::Ecdh, false) => &[],
+            (WebCryptoAlgorithm::Ecdh, true) => &[DeriveKey, DeriveBits],
+            (_, false) => &[Verify],
+            (_, true) => &[Sign],
+        }
+    }
+
+    fn kty(&self) -> &'static str {
+        match self {
+            WebCryptoAlgorithm::Ecdsa | WebCryptoAlgorithm::Ecdh => "EC",
+            WebCryptoAlgorithm::Hmac(_) => "oct",
+            _ => "RSA",
+        }
+    }
+
+    /// Infers the algorithm of a JWK exported by WebCrypto from its `kty`, `alg`, `use`
+    /// and `key_ops`.
+    fn infer(jwk: &Jwk) -> Result<WebCryptoAlgorithm, JwkError> {
+        let hash = |bits: &str| match bits {
+            "1" => Some(WebCryptoHash::Sha1),
+            "256" => Some(WebCryptoHash::Sha256),
+            "384" => Some(WebCryptoHash::Sha384),
+            "512" => Some(WebCryptoHash::Sha512),
+            _ => None,
+        };
+        let alg = jwk.alg.as_deref().unwrap_or_default();
+        let unsupported = || JwkError::UnsupportedAlgorithm(format!("'{}' for a '{}' key", alg, jwk.kty));
+
+        let algorithm = match jwk.kty.as_str() {
+            "RSA" => {
+                if alg == "RSA-OAEP" {
+                    WebCryptoAlgorithm::RsaOaep(WebCryptoHash::Sha1)
+                } else if let Some(bits) = alg.strip_prefix("RSA-OAEP-") {
+                    WebCryptoAlgorithm::RsaOaep(hash(bits).ok_or_else(unsupported)?)
+                } else if let Some(bits) = alg.strip_prefix("RS") {
+                    WebCryptoAlgorithm::RsassaPkcs1V15(hash(bits).ok_or_else(unsupported)?)
+                } else if let Some(bits) = alg.strip_prefix("PS") {
+                    WebCryptoAlgorithm::RsaPss(hash(bits).ok_or_else(unsupported)?)
+                } else {
+                    return Err(unsupported());
+                }
+            }
+            "EC" => {
+                let derives = jwk.key_ops.as_ref().is_some_and(|ops| {
+                    ops.is_empty() || ops.iter().any(|op| op.starts_with("derive"))
+                });
+                if derives || alg.starts_with("ECDH-ES") || jwk.use_.as_deref() == Some("enc") {
+                    WebCryptoAlgorithm::Ecdh
+                } else {
+                    WebCryptoAlgorithm::Ecdsa
+                }
+            }
+            "oct" => match alg.strip_prefix("HS").and_then(hash) {
+                Some(hash) if hash != WebCryptoHash::Sha1 => WebCryptoAlgorithm::Hmac(hash),
+                _ => return Err(unsupported()),
+            },
+            _ => return Err(JwkError::UnsupportedKeyType(jwk.kty.clone())),
+        };
+
+        Ok(algorithm)
+    }
+}
+
+/// Converts a `Jwk` into the JSON WebCrypto's `crypto.subtle.importKey("jwk", ...)` expects.
+///
+/// The result carries the WebCrypto `alg` for `algorithm`, the `key_ops` WebCrypto grants a
+/// public or private key for it, and `ext` set to `extractable`. `use` is removed, as WebCrypto
+/// never exports it and rejects it on import when it disagrees with the algorithm.
+/// RSA private keys are completed with the CRT members (`p`, `q`, `dp`, `dq` and `qi`),
+/// recovered from `d` when missing, since browsers refuse to import a private key without them.
+///
+/// # Parameters
+/// - `jwk`: The key to convert, e.g. loaded from a PEM produced by the `generator` module.
+/// - `algorithm`: The algorithm the browser will import the key for.
+/// - `extractable`: The value of `ext`; WebCrypto refuses to import a key as extractable
+///   when `ext` is `false`.
+///
+/// # Errors
+/// - `JwkError::UnsupportedAlgorithm` if `algorithm` cannot be used with the key's type or curve.
+/// - `JwkError::OperationNotPermitted` if the key's `use` or `key_ops` forbid the operations
+///   required by `algorithm`.
+/// - `JwkError::RsaParseError` or `JwkError::MissingRsaParams` if the CRT members of an RSA
+///   private key cannot be recovered, e.g. for a multi-prime key.
+/// - Any error returned by [`Jwk::validate`].
+///
+/// # Example
+/// ```rust
+/// use jwk_kit::loader::load_key;
+/// use jwk_kit::webcrypto::{to_webcrypto_jwk, WebCryptoAlgorithm};
+///
+/// let jwk = load_key(&std::fs::read("./examples/ecdsa/ecdsa-legacy-public.pem").unwrap()).unwrap();
+/// let json = to_webcrypto_jwk(&jwk, WebCryptoAlgorithm::Ecdsa, true).unwrap();
+/// assert!(json.contains(r#""key_ops":["verify"]"#));
+/// ```
+pub fn to_webcrypto_jwk(jwk: &Jwk, algorithm: WebCryptoAlgorithm, extractable: bool) -> Result<String, JwkError> {
+    jwk.validate()?;
+
+    let mut converted = jwk.clone();
+    converted.alg = algorithm.jwk_alg();
+    if converted.kty != algorithm.kty()
+        || converted.alg.as_deref().is_some_and(|alg| !alg_matches_key(alg, jwk))
+    {
+        return Err(JwkError::UnsupportedAlgorithm(format!("{:?} for a '{}' key", algorithm, jwk.kty)));
+    }
+
+    let private = jwk.is_private();
+    let ops = algorithm.key_ops(private);
+    for op in ops {
+        jwk.check_operation(*op)?;
+    }
+
+    if private && converted.kty == "RSA" {
+        let complete = rsa_private_key_to_jwk(&jwk_to_rsa_private_key(jwk)?)?;
+        if complete.qi.is_none() {
+            return Err(JwkError::MissingRsaParams);
+        }
+        (converted.p, converted.q) = (complete.p, complete.q);
+        (converted.dp, converted.dq, converted.qi) = (complete.dp, complete.dq, complete.qi);
+    }
+
+    converted.use_ = None;
+    converted.key_ops = Some(ops.iter().map(|op| op.as_str().to_string()).collect());
+    converted.extra.insert("ext".to_string(), extractable.into());
+
+    serde_json::to_string(&converted).map_err(|e| JwkError::InvalidJson(e.to_string()))
+}
+
+/// Parses a JWK exported by WebCrypto's `crypto.subtle.exportKey("jwk", ...)`.
+///
+/// The algorithm the key was exported for is inferred from `alg` (RSA and HMAC keys) or from
+/// `key_ops` and `use` (EC keys, which WebCrypto exports without `alg`), and the key is checked
+/// the way WebCrypto checks it on import: `use`, if present, must match the algorithm, and
+/// `key_ops` must only list operations the algorithm allows for a public or private key.
+/// `ext` is kept in `Jwk::extra`.
+///
+/// # Returns
+/// The key, and the algorithm it was exported for.
+///
+/// # Errors
+/// - `JwkError::InvalidJson` if the input is not a JWK, or `ext` is not a boolean.
+/// - `JwkError::UnsupportedAlgorithm` if `alg` is not a WebCrypto algorithm for the key.
+/// - `JwkError::InvalidKeyOps` if `use` or `key_ops` disagree with the algorithm.
+/// - Any error returned by [`Jwk::validate`].
+pub fn from_webcrypto_jwk(json: &str) -> Result<(Jwk, WebCryptoAlgorithm), JwkError> {
+    let jwk: Jwk = serde_json::from_str(json).map_err(|e| JwkError::InvalidJson(e.to_string()))?;
+    jwk.validate()?;
+
+    if jwk.extra_member("ext").is_some_and(|ext| !ext.is_boolean()) {
+        return Err(JwkError::InvalidJson("'ext' must be a boolean".into()));
+    }
+
+    let algorithm = WebCryptoAlgorithm::infer(&jwk)?;
+    if jwk.alg.as_deref().is_some_and(|alg| !alg_matches_key(alg, &jwk)) {
+        return Err(JwkError::UnsupportedAlgorithm(format!("{:?} for this key", jwk.alg)));
+    }
+
+    if let Some(key_use) = jwk.use_.as_deref()
+        && key_use != algorithm.key_use()
+    {
+        return Err(JwkError::InvalidKeyOps(format!("use '{}' is not allowed with {:?}", key_use, algorithm)));
+    }
+
+    let allowed = algorithm.key_ops(jwk.is_private());
+    if let Some(op) = jwk
+        .key_ops
+        .iter()
+        .flatten()
+        .find(|op| !allowed.iter().any(|allowed| allowed.as_str() == op.as_str()))
+    {
+        return Err(JwkError::InvalidKeyOps(format!("'{}' is not allowed with {:?} for this key", op, algorithm)));
+    }
+
+    Ok((jwk, algorithm))
+}
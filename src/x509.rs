@@ -0,0 +1,392 @@
+use base64::{
+    Engine,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD}
+};
+use crate::error::JwkError;
+use crate::generator::ecdsa::jwk_to_es256_secret_key;
+use crate::generator::rsa::jwk_to_rsa_private_key;
+use crate::jwk::{Jwk, KeyOperation};
+use crate::loader::{load_key, load_spki_der};
+use p256::elliptic_curve::rand_core::{OsRng, RngCore};
+use rsa::{
+    pkcs1v15,
+    signature::{Keypair, Signer},
+};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+use x509_cert::{
+    builder::{Builder, CertificateBuilder, Profile},
+    der::asn1::{Ia5String, OctetString},
+    der::flagset::FlagSet,
+    der::{Decode, Encode, EncodePem},
+    ext::pkix::{name::GeneralName, BasicConstraints, KeyUsage, KeyUsages, SubjectAltName},
+    name::Name,
+    serial_number::SerialNumber,
+    spki::{DynSignatureAlgorithmIdentifier, EncodePublicKey, SignatureBitStringEncoding, SubjectPublicKeyInfoOwned},
+    time::{Time, Validity},
+    Certificate,
+};
+
+/// Builds a public `Jwk` from an X.509 certificate or certificate chain.
+///
+/// The input may be a single DER-encoded certificate, or one or more PEM `CERTIFICATE`
+/// blocks ordered leaf first. The public key is taken from the leaf certificate, and the
+/// resulting `Jwk` has `x5c` set to the whole chain and `x5t` / `x5t#S256` set to the
+/// SHA-1 and SHA-256 thumbprints of the leaf.
+///
+/// # Errors
+/// - `JwkError::CertificateParseError` if the input is not a valid certificate.
+/// - Any error produced while converting the certificate's public key.
+///
+/// # Example
+/// ```rust
+/// use jwk_kit::x509::jwk_from_certificate;
+///
+/// let cert_pem = std::fs::read("./examples/x509/rsa-legacy-cert.pem").unwrap();
+/// let jwk = jwk_from_certificate(&cert_pem).unwrap();
+/// assert_eq!(jwk.kty, "RSA");
+/// assert_eq!(jwk.x5c.as_ref().map(Vec::len), Some(1));
+/// ```
+pub fn jwk_from_certificate(cert: &[u8]) -> Result<Jwk, JwkError> {
+    let chain = if cert.trim_ascii_start().starts_with(b"-----BEGIN") {
+        Certificate::load_pem_chain(cert).map_err(|_| JwkError::CertificateParseError)?
+    } else {
+        vec![Certificate::from_der(cert).map_err(|_| JwkError::CertificateParseError)?]
+    };
+
+    let chain_der = chain
+        .iter()
+        .map(|cert| cert.to_der().map_err(|_| JwkError::CertificateParseError))
+        .collect::<Result<Vec<_>, _>>()?;
+    let leaf = chain.first().ok_or(JwkError::CertificateParseError)?;
+    let leaf_der = chain_der.first().ok_or(JwkError::CertificateParseError)?;
+
+    let mut jwk = certificate_public_key_to_jwk(leaf)?;
+    jwk.x5t = Some(x509_thumbprint(leaf_der));
+    jwk.x5t_s256 = Some(x509_thumbprint_sha256(leaf_der));
+    jwk.x5c = Some(chain_der.iter().map(|der| STANDARD.encode(der)).collect());
+
+    Ok(jwk)
+}
+
+/// Checks that the X.509 members of a `Jwk` are consistent with its key.
+///
+/// When `x5c` is present, the public key of its first (leaf) certificate must match the
+/// key in `n`/`e`, `x`/`y` or `x`. When `x5t` or `x5t#S256` are present alongside `x5c`,
+/// they must be the thumbprints of the leaf certificate. Thumbprints without `x5c` cannot
+/// be checked and are accepted as-is.
+///
+/// # Errors
+/// - `JwkError::InvalidBase64` if an `x5c` entry is not valid base64.
+/// - `JwkError::CertificateParseError` if the leaf certificate cannot be parsed.
+/// - `JwkError::X509Mismatch` if the certificate or thumbprints do not match the key.
+pub fn check_x509_consistency(jwk: &Jwk) -> Result<(), JwkError> {
+    let Some(chain) = &jwk.x5c else {
+        return Ok(());
+    };
+    let leaf_b64 = chain
+        .first()
+        .ok_or_else(|| JwkError::X509Mismatch("x5c is empty".into()))?;
+    let leaf_der = STANDARD
+        .decode(leaf_b64)
+        .map_err(|_| JwkError::InvalidBase64("x5c".into()))?;
+    let leaf = Certificate::from_der(&leaf_der).map_err(|_| JwkError::CertificateParseError)?;
+
+    let cert_jwk = certificate_public_key_to_jwk(&leaf)?;
+    if !same_public_key(jwk, &cert_jwk) {
+        return Err(JwkError::X509Mismatch("x5c leaf public key differs from the key".into()));
+    }
+
+    if jwk.x5t.as_ref().is_some_and(|x5t| *x5t != x509_thumbprint(&leaf_der)) {
+        return Err(JwkError::X509Mismatch("x5t is not the SHA-1 thumbprint of the x5c leaf".into()));
+    }
+
+    if jwk.x5t_s256.as_ref().is_some_and(|x5t| *x5t != x509_thumbprint_sha256(&leaf_der)) {
+        return Err(JwkError::X509Mismatch("x5t#S256 is not the SHA-256 thumbprint of the x5c leaf".into()));
+    }
+
+    Ok(())
+}
+
+/// Computes the `x5t` value (base64url SHA-1 digest) of a DER-encoded certificate.
+pub fn x509_thumbprint(cert_der: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(Sha1::digest(cert_der))
+}
+
+/// Computes the `x5t#S256` value (base64url SHA-256 digest) of a DER-encoded certificate.
+pub fn x509_thumbprint_sha256(cert_der: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(cert_der))
+}
+
+fn certificate_public_key_to_jwk(cert: &Certificate) -> Result<Jwk, JwkError> {
+    let spki_der = cert
+        .tbs_certificate
+        .subject_public_key_info
+        .to_der()
+        .map_err(|_| JwkError::CertificateParseError)?;
+
+    load_spki_der(&spki_der)
+}
+
+/// Compares the public key members of two JWKs, ignoring leading zero bytes in RSA integers.
+fn same_public_key(a: &Jwk, b: &Jwk) -> bool {
+    let integer = |value: &Option<String>| {
+        value
+            .as_deref()
+            .and_then(|v| URL_SAFE_NO_PAD.decode(v).ok())
+            .map(|bytes| bytes.into_iter().skip_while(|&b| b == 0).collect::<Vec<_>>())
+    };
+
+    a.kty == b.kty
+        && match a.kty.as_str() {
+            "RSA" => integer(&a.n) == integer(&b.n) && integer(&a.e) == integer(&b.e),
+            "EC" => a.crv == b.crv && a.x == b.x && a.y == b.y,
+            _ => a.crv == b.crv && a.x == b.x,
+        }
+}
+
+/// Key usages that can be asserted by a generated certificate.
+///
+/// These map one-to-one onto the bits of the X.509 `keyUsage` extension
+/// ([RFC 5280 §4.2.1.3](https://datatracker.ietf.org/doc/html/rfc5280#section-4.2.1.3)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertificateKeyUsage {
+    DigitalSignature,
+    NonRepudiation,
+    KeyEncipherment,
+    DataEncipherment,
+    KeyAgreement,
+    KeyCertSign,
+    CrlSign,
+}
+
+impl From<CertificateKeyUsage> for KeyUsages {
+    fn from(usage: CertificateKeyUsage) -> Self {
+        match usage {
+            CertificateKeyUsage::DigitalSignature => KeyUsages::DigitalSignature,
+            CertificateKeyUsage::NonRepudiation => KeyUsages::NonRepudiation,
+            CertificateKeyUsage::KeyEncipherment => KeyUsages::KeyEncipherment,
+            CertificateKeyUsage::DataEncipherment => KeyUsages::DataEncipherment,
+            CertificateKeyUsage::KeyAgreement => KeyUsages::KeyAgreement,
+            CertificateKeyUsage::KeyCertSign => KeyUsages::KeyCertSign,
+            CertificateKeyUsage::CrlSign => KeyUsages::CRLSign,
+        }
+    }
+}
+
+/// A self-signed certificate issued by [`SelfSignedCertificateBuilder`].
+#[derive(Debug, Clone)]
+pub struct SelfSignedCertificate {
+    /// The certificate as a PEM `CERTIFICATE` block.
+    pub certificate_pem: String,
+
+    /// The public key of the certificate as a `Jwk`, with `x5c`, `x5t` and `x5t#S256` populated.
+    pub jwk: Jwk,
+}
+
+/// # A builder for issuing self-signed X.509 certificates.
+///
+/// Issues a self-signed certificate for a private key produced by the `generator` module
+/// (RSA or ES256), as a native replacement for `openssl req -x509`. RSA keys are signed
+/// with `sha256WithRSAEncryption` and P-256 keys with `ecdsa-with-SHA256`.
+///
+/// ## Defaults:
+/// - Validity: from now, for 365 days.
+/// - Key usage: `digitalSignature`.
+/// - No subject alternative names.
+///
+/// ## Example
+/// ```rust
+/// use jwk_kit::generator::ecdsa::generate_es256_keypair_pem;
+/// use jwk_kit::x509::SelfSignedCertificateBuilder;
+///
+/// let (private_pem, _) = generate_es256_keypair_pem().unwrap();
+/// let issued = SelfSignedCertificateBuilder::new("CN=internal-service,O=Example")
+///     .set_validity_days(90)
+///     .add_dns_name("internal-service.local")
+///     .build(&private_pem)
+///     .unwrap();
+///
+/// assert!(issued.certificate_pem.starts_with("-----BEGIN CERTIFICATE-----"));
+/// assert!(issued.jwk.x5c.is_some());
+/// ```
+pub struct SelfSignedCertificateBuilder {
+    subject: String,
+    not_before: Option<SystemTime>,
+    not_after: Option<SystemTime>,
+    validity_days: u64,
+    dns_names: Vec<String>,
+    ip_addresses: Vec<IpAddr>,
+    key_usages: Vec<CertificateKeyUsage>,
+}
+
+impl SelfSignedCertificateBuilder {
+    /// Creates a builder for a certificate with the given subject, as an RFC 4514
+    /// distinguished name string (e.g. `"CN=service,O=Example"`).
+    pub fn new(subject: &str) -> Self {
+        Self {
+            subject: subject.to_string(),
+            not_before: None,
+            not_after: None,
+            validity_days: 365,
+            dns_names: Vec::new(),
+            ip_addresses: Vec::new(),
+            key_usages: vec![CertificateKeyUsage::DigitalSignature],
+        }
+    }
+
+    pub fn set_validity(&mut self, not_before: SystemTime, not_after: SystemTime) -> &mut Self {
+        self.not_before = Some(not_before);
+        self.not_after = Some(not_after);
+        self
+    }
+
+    pub fn set_validity_days(&mut self, days: u64) -> &mut Self {
+        self.not_before = None;
+        self.not_after = None;
+        self.validity_days = days;
+        self
+    }
+
+    pub fn add_dns_name(&mut self, value: &str) -> &mut Self {
+        self.dns_names.push(value.to_string());
+        self
+    }
+
+    pub fn add_ip_address(&mut self, value: IpAddr) -> &mut Self {
+        self.ip_addresses.push(value);
+        self
+    }
+
+    pub fn set_key_usage(&mut self, value: &[CertificateKeyUsage]) -> &mut Self {
+        self.key_usages = value.to_vec();
+        self
+    }
+
+    /// Issues the certificate, signing it with `private_key_pem`.
+    ///
+    /// # Parameters
+    /// - `private_key_pem`: An RSA or P-256 private key in any PEM encoding accepted by the
+    ///   `loader` module (PKCS#8, PKCS#1 or SEC1).
+    ///
+    /// # Errors
+    /// - `JwkError::UnsupportedKeyType` if the key is not an RSA or P-256 private key.
+    /// - `JwkError::CertificateBuildError` if the subject, names or validity are invalid,
+    ///   or if signing fails.
+    pub fn build(&self, private_key_pem: &str) -> Result<SelfSignedCertificate, JwkError> {
+        self.build_with_jwk(&load_key(private_key_pem.as_bytes())?)
+    }
+
+    /// Issues the certificate, signing it with the private key held in `jwk`.
+    ///
+    /// # Errors
+    /// - `JwkError::OperationNotPermitted` if the key's `use` or `key_ops` do not allow `sign`.
+    /// - The same errors as [`SelfSignedCertificateBuilder::build`].
+    pub fn build_with_jwk(&self, jwk: &Jwk) -> Result<SelfSignedCertificate, JwkError> {
+        if jwk.d.is_none() {
+            return Err(JwkError::CertificateBuildError("a private key is required".into()));
+        }
+        jwk.check_operation(KeyOperation::Sign)?;
+
+        let certificate = match jwk.kty.as_str() {
+            "RSA" => {
+                let signer = pkcs1v15::SigningKey::<Sha256>::new(jwk_to_rsa_private_key(jwk)?);
+                self.issue::<_, pkcs1v15::Signature>(&signer)?
+            }
+            "EC" => {
+                let signer = p256::ecdsa::SigningKey::from(jwk_to_es256_secret_key(jwk)?);
+                self.issue::<_, p256::ecdsa::DerSignature>(&signer)?
+            }
+            other => return Err(JwkError::UnsupportedKeyType(other.to_string())),
+        };
+
+        let certificate_pem = certificate
+            .to_pem(x509_cert::der::pem::LineEnding::LF)
+            .map_err(|e| JwkError::CertificateBuildError(e.to_string()))?;
+        let jwk = jwk_from_certificate(certificate_pem.as_bytes())?;
+
+        Ok(SelfSignedCertificate { certificate_pem, jwk })
+    }
+
+    fn issue<S, Signature>(&self, signer: &S) -> Result<Certificate, JwkError>
+    where
+        S: Keypair + DynSignatureAlgorithmIdentifier + Signer<Signature>,
+        S::VerifyingKey: EncodePublicKey,
+        Signature: SignatureBitStringEncoding,
+    {
+        let build_error = |e: &dyn std::fmt::Display| JwkError::CertificateBuildError(e.to_string());
+
+        let subject = Name::from_str(&self.subject).map_err(|e| build_error(&e))?;
+        let spki = SubjectPublicKeyInfoOwned::from_key(signer.verifying_key())
+            .map_err(|e| build_error(&e))?;
+
+        let not_before = self.not_before.unwrap_or_else(SystemTime::now);
+        let not_after = match self.not_after {
+            Some(not_after) => not_after,
+            None => self
+                .validity_days
+                .checked_mul(24 * 60 * 60)
+                .and_then(|secs| not_before.checked_add(Duration::from_secs(secs)))
+                .ok_or_else(|| build_error(&"validity period is too long"))?,
+        };
+        let validity = Validity {
+            not_before: Time::try_from(not_before).map_err(|e| build_error(&e))?,
+            not_after: Time::try_from(not_after).map_err(|e| build_error(&e))?,
+        };
+
+        let mut serial = [0u8; 16];
+        OsRng.fill_bytes(&mut serial);
+        serial[0] &= 0x7f;
+        let serial = SerialNumber::new(&serial).map_err(|e| build_error(&e))?;
+
+        let mut builder = CertificateBuilder::new(
+            Profile::Manual { issuer: None },
+            serial,
+            validity,
+            subject,
+            spki,
+            signer,
+        )
+        .map_err(|e| build_error(&e))?;
+
+        builder
+            .add_extension(&BasicConstraints { ca: false, path_len_constraint: None })
+            .map_err(|e| build_error(&e))?;
+
+        if !self.key_usages.is_empty() {
+            let flags = self
+                .key_usages
+                .iter()
+                .fold(FlagSet::<KeyUsages>::default(), |flags, usage| flags | KeyUsages::from(*usage));
+            builder
+                .add_extension(&KeyUsage(flags))
+                .map_err(|e| build_error(&e))?;
+        }
+
+        let mut names = Vec::new();
+        for dns_name in &self.dns_names {
+            names.push(GeneralName::DnsName(
+                Ia5String::new(dns_name).map_err(|e| build_error(&e))?,
+            ));
+        }
+        for ip_address in &self.ip_addresses {
+            let octets = match ip_address {
+                IpAddr::V4(ip) => ip.octets().to_vec(),
+                IpAddr::V6(ip) => ip.octets().to_vec(),
+            };
+            names.push(GeneralName::IpAddress(
+                OctetString::new(octets).map_err(|e| build_error(&e))?,
+            ));
+        }
+        if !names.is_empty() {
+            builder
+                .add_extension(&SubjectAltName(names))
+                .map_err(|e| build_error(&e))?;
+        }
+
+        builder.build::<Signature>().map_err(|e| build_error(&e))
+    }
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use jwk_kit::error::JwkError;
+use jwk_kit::keystore::audit::{AuditRecord, AuditSink, JsonLinesSink, KeyEvent};
+use jwk_kit::keystore::rotation::{FakeClock, RotationDriver, RotationPolicy};
+use jwk_kit::keystore::{KeyAlgorithm, KeyState, KeyStore};
+use common::t0;
+
+#[derive(Default)]
+struct MemorySink(Mutex<Vec<AuditRecord>>);
+
+impl AuditSink for MemorySink {
+    fn record(&self, record: &AuditRecord) -> Result<(), JwkError> {
+        self.0.lock().unwrap().push(record.clone());
+        Ok(())
+    }
+}
+
+impl MemorySink {
+    fn events(&self) -> Vec<KeyEvent> {
+        self.0.lock().unwrap().iter().map(|record| record.event.clone()).collect()
+    }
+}
+
+#[test]
+fn test_lifecycle_events() {
+    let sink = Arc::new(MemorySink::default());
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    store.set_audit_sink(sink.clone());
+
+    let first = store.rotate_at(t0()).unwrap().kid().to_string();
+    let second = store.rotate_at(t0() + Duration::from_secs(60)).unwrap().kid().to_string();
+    store.transition_at(&first, KeyState::Retired, t0() + Duration::from_secs(120)).unwrap();
+    store.revoke(&second).unwrap();
+    store.export(&first).unwrap();
+
+    let generated = |kid: &str| KeyEvent::Generated { kid: kid.to_string(), alg: "ES256".to_string() };
+    assert_eq!(
+        sink.events(),
+        [
+            generated(&first),
+            KeyEvent::Activated { kid: first.clone() },
+            KeyEvent::Retiring { kid: first.clone() },
+            generated(&second),
+            KeyEvent::Activated { kid: second.clone() },
+            KeyEvent::Retired { kid: first.clone() },
+            KeyEvent::Revoked { kid: second.clone() },
+            KeyEvent::Exported { kid: first.clone() },
+        ]
+    );
+    assert_eq!(sink.0.lock().unwrap()[3].at, t0() + Duration::from_secs(60));
+    assert_eq!(store.access_signing_key(), Err(JwkError::NoActiveKey));
+}
+
+#[test]
+fn test_json_lines_sink() {
+    let path = std::env::temp_dir().join(format!("jwk-kit-audit-{}.jsonl", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    store.set_audit_sink(JsonLinesSink::open(&path).unwrap());
+    let kid = store.rotate_at(t0()).unwrap().kid().to_string();
+    store.access_signing_key().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let first_line = contents.lines().next().unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(first_line).unwrap(),
+        serde_json::json!({ "at": 1_700_000_000, "event": "generated", "kid": kid, "alg": "ES256" })
+    );
+
+    let records = JsonLinesSink::read(&path).unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[1].event, KeyEvent::Activated { kid: kid.clone() });
+    assert_eq!(records[2].event, KeyEvent::PrivateKeyAccessed { kid });
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_rotation_driver_and_sink_failures() {
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    let sink = Arc::new(MemorySink::default());
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    store.set_audit_sink(sink.clone());
+
+    let clock = FakeClock::new(t0());
+    let mut driver = RotationDriver::new(store, RotationPolicy::new(30 * DAY, DAY, 7 * DAY), &clock);
+    driver.tick().unwrap();
+    clock.advance(30 * DAY);
+    driver.tick().unwrap();
+    clock.advance(7 * DAY);
+    driver.tick().unwrap();
+
+    let kinds: Vec<_> = sink
+        .events()
+        .iter()
+        .map(|event| serde_json::to_value(event).unwrap()["event"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(kinds, ["generated", "activated", "generated", "retiring", "activated", "retired"]);
+
+    struct Failing;
+    impl AuditSink for Failing {
+        fn record(&self, _: &AuditRecord) -> Result<(), JwkError> {
+            Err(JwkError::KeyStoreIo("disk full".into()))
+        }
+    }
+
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    store.set_audit_sink(Failing);
+    assert_eq!(store.rotate().unwrap_err(), JwkError::KeyStoreIo("disk full".into()));
+    // The key was added all the same.
+    assert_eq!(store.keys().len(), 1);
+
+    // Rotating past an active key leaves the new key active and the previous one retiring.
+    let first = store.keys()[0].kid().to_string();
+    assert_eq!(store.rotate().unwrap_err(), JwkError::KeyStoreIo("disk full".into()));
+    let second = store.keys()[1].kid().to_string();
+    assert_eq!(store.get(&first).unwrap().state, KeyState::Retiring);
+    assert_eq!(store.get(&second).unwrap().state, KeyState::Active);
+    assert_eq!(store.current_signing_key().unwrap().kid.as_deref(), Some(second.as_str()));
+}
@@ -0,0 +1,139 @@
+#![cfg(feature = "client")]
+
+mod common;
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use jwk_kit::client::JwksClient;
+use jwk_kit::error::JwkError;
+use jwk_kit::jwk::{Jwk, create_jwks};
+use jwk_kit::keystore::KeyAlgorithm;
+use common::{key, signing_key, t0};
+
+/// Serves one response per connection, built by `handler` from the request number, and returns the URL and the headers of every request received.
+fn serve(handler: impl Fn(usize) -> String + Send + 'static) -> (String, Arc<Mutex<Vec<Vec<String>>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/.well-known/jwks.json", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let received = requests.clone();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let headers: Vec<String> = BufReader::new(&stream)
+                .lines()
+                .map(Result::unwrap)
+                .take_while(|line| !line.is_empty())
+                .map(|line| line.to_ascii_lowercase())
+                .collect();
+            let count = {
+                let mut requests = received.lock().unwrap();
+                requests.push(headers);
+                requests.len()
+            };
+            let _ = stream.write_all(handler(count).as_bytes());
+        }
+    });
+
+    (url, requests)
+}
+
+fn response(status: &str, headers: &[&str], body: &str) -> String {
+    let mut response = format!("HTTP/1.1 {}\r\nConnection: close\r\nContent-Length: {}\r\n", status, body.len());
+    for header in headers {
+        response.push_str(header);
+        response.push_str("\r\n");
+    }
+    response + "\r\n" + body
+}
+
+fn document(keys: &[&Jwk]) -> String {
+    serde_json::to_string(&create_jwks(keys.iter().map(|jwk| (*jwk).clone()).collect())).unwrap()
+}
+
+#[test]
+fn test_caches_and_revalidates() {
+    // The private key is served by mistake; the client keeps only its public members.
+    let body = document(&[&signing_key(KeyAlgorithm::Es256, "a")]);
+    let (url, requests) = serve(move |count| match count {
+        1 => response("200 OK", &["ETag: \"v1\"", "Cache-Control: public, max-age=60"], &body),
+        _ => response("304 Not Modified", &["ETag: \"v1\"", "Cache-Control: max-age=120"], ""),
+    });
+    let client = JwksClient::new(&url);
+
+    let jwks = client.keys_at(t0()).unwrap();
+    assert!(!jwks.keys[0].is_private());
+    assert!(Arc::ptr_eq(&client.keys_at(t0() + Duration::from_secs(59)).unwrap(), &jwks));
+    assert_eq!(requests.lock().unwrap().len(), 1);
+
+    // Once expired, the keys are revalidated, and the 304 extends them by its own max-age.
+    let revalidated = client.keys_at(t0() + Duration::from_secs(60)).unwrap();
+    assert!(Arc::ptr_eq(&revalidated, &jwks));
+    assert!(requests.lock().unwrap()[1].contains(&"if-none-match: \"v1\"".to_string()));
+    client.keys_at(t0() + Duration::from_secs(179)).unwrap();
+    assert_eq!(requests.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn test_unknown_kid_refresh_is_rate_limited() {
+    let (a, b) = (key("a"), key("b"));
+    let (first, second) = (document(&[&a]), document(&[&a, &b]));
+    let (url, requests) = serve(move |count| {
+        let body = if count == 1 { &first } else { &second };
+        response("200 OK", &["Cache-Control: max-age=3600"], body)
+    });
+    let mut client = JwksClient::new(&url);
+    client.set_min_refresh_interval(Duration::from_secs(30));
+
+    assert_eq!(client.find_key_at("a", t0()).unwrap().kid.as_deref(), Some("a"));
+    // The keys were just downloaded: an unknown kid does not trigger another request.
+    assert!(matches!(client.find_key_at("b", t0() + Duration::from_secs(10)), Err(JwkError::UnknownKeyId(_))));
+    assert_eq!(requests.lock().unwrap().len(), 1);
+
+    let later = t0() + Duration::from_secs(30);
+    assert_eq!(client.find_key_at("b", later).unwrap().kid.as_deref(), Some("b"));
+    assert!(matches!(client.find_key_at("forged", later), Err(JwkError::UnknownKeyId(_))));
+    assert!(matches!(client.find_key_at("forged", later + Duration::from_secs(1)), Err(JwkError::UnknownKeyId(_))));
+    assert_eq!(requests.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn test_serves_stale_keys_on_failure() {
+    let body = document(&[&key("a")]);
+    let (url, requests) = serve(move |count| match count {
+        1 => response("200 OK", &["Cache-Control: max-age=60"], &body),
+        _ => response("500 Internal Server Error", &[], "down"),
+    });
+    let mut client = JwksClient::new(&url);
+    client.set_max_stale(Duration::from_secs(600)).set_min_refresh_interval(Duration::from_secs(30));
+
+    let jwks = client.keys_at(t0()).unwrap();
+    let stale = client.keys_at(t0() + Duration::from_secs(61)).unwrap();
+    assert!(Arc::ptr_eq(&stale, &jwks));
+    assert!(matches!(client.refresh_at(t0() + Duration::from_secs(62)), Err(JwkError::JwksFetch(_))));
+    assert_eq!(requests.lock().unwrap().len(), 3);
+
+    // Past the stale limit, the error is returned instead.
+    assert!(matches!(client.keys_at(t0() + Duration::from_secs(660)), Err(JwkError::JwksFetch(_))));
+    assert!(matches!(client.find_key_at("a", t0() + Duration::from_secs(661)), Err(JwkError::JwksFetch(_))));
+    assert_eq!(requests.lock().unwrap().len(), 4);
+}
+
+#[test]
+fn test_no_cache_and_invalid_documents() {
+    let (url, requests) = serve(|count| match count {
+        1 => response("200 OK", &[], "not json"),
+        _ => response("200 OK", &["Cache-Control: no-cache"], &document(&[&key("a")])),
+    });
+    let mut client = JwksClient::new(&url);
+    client.set_min_refresh_interval(Duration::ZERO);
+
+    assert!(matches!(client.keys_at(t0()), Err(JwkError::JwksFetch(_))));
+    client.keys_at(t0()).unwrap();
+    // `no-cache` keys expire immediately, so every call downloads them again.
+    client.keys_at(t0()).unwrap();
+    assert_eq!(requests.lock().unwrap().len(), 3);
+}
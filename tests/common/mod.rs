@@ -0,0 +1,66 @@
+//! Helpers shared by the integration tests. Each test file uses only some of them.
+#![allow(dead_code)]
+
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use jwk_kit::jwk::Jwk;
+use jwk_kit::keystore::KeyAlgorithm;
+
+/// A fixed instant, for tests that pass the current time explicitly.
+pub fn t0() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+}
+
+/// Generates a private key of kind `algorithm` with the given `kid`.
+pub fn signing_key(algorithm: KeyAlgorithm, kid: &str) -> Jwk {
+    let mut jwk = algorithm.generate().unwrap();
+    jwk.kid = Some(kid.to_string());
+    jwk
+}
+
+/// Generates a public P-256 key with the given `kid`.
+pub fn key(kid: &str) -> Jwk {
+    signing_key(KeyAlgorithm::Es256, kid).to_public()
+}
+
+/// A path in the system's temporary directory, unique to the test process and emptied
+/// beforehand. The directory is not created, and is removed when the value is dropped, even
+/// if the test fails.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("jwk-kit-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Self(dir)
+    }
+
+    /// Same as [`TempDir::new`], creating the directory.
+    pub fn create(name: &str) -> Self {
+        let dir = Self::new(name);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
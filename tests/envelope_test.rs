@@ -0,0 +1,163 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use jwk_kit::error::JwkError;
+use jwk_kit::jwk::{Jwk, JwkBuilder, KeyOperation};
+use jwk_kit::keystore::envelope::{AesKeyWrapper, KeyWrapper, PBKDF2_ITERATIONS, PasswordKeyWrapper, SealedKey};
+use jwk_kit::keystore::fs::FileKeyStore;
+use jwk_kit::keystore::{KeyAlgorithm, KeyStore};
+use common::{TempDir, t0};
+
+fn kek(kid: &str, k: &str) -> Jwk {
+    JwkBuilder::new("oct")
+        .set_key_id(kid)
+        .set_algorithm("A256KW")
+        .set_key_operations(&[KeyOperation::WrapKey, KeyOperation::UnwrapKey])
+        .set_symmetric_key(k)
+        .build()
+        .unwrap()
+}
+
+fn password(label: &str) -> PasswordKeyWrapper {
+    let mut wrapper = PasswordKeyWrapper::new("correct horse battery staple", label);
+    wrapper.set_iterations(*PBKDF2_ITERATIONS.start()).unwrap();
+    wrapper
+}
+
+const K1: &str = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8";
+const K2: &str = "HxweHRwbGhkYFxYVFBMSERAPDg0MCwoJCAcGBQQDAgE";
+
+#[test]
+fn test_seal_and_open_with_aes_and_password() {
+    let jwk = KeyAlgorithm::Es256.generate().unwrap();
+    let kid = jwk.kid.clone().unwrap();
+
+    let aes = AesKeyWrapper::new(&kek("kek-1", K1)).unwrap();
+    let sealed = SealedKey::seal(&jwk, &aes).unwrap();
+    assert_eq!(sealed.kek, "kek-1");
+    assert_eq!(sealed.open(&kid, &aes).unwrap(), jwk);
+
+    let password = password("master");
+    let sealed = SealedKey::seal(&jwk, &password).unwrap();
+    assert_eq!(sealed.open(&kid, &password).unwrap(), jwk);
+
+    // The ciphertext is bound to the kid.
+    assert!(matches!(sealed.open("other", &password), Err(JwkError::KeyWrapError(_))));
+}
+
+#[test]
+fn test_wrong_kek_is_rejected() {
+    let jwk = KeyAlgorithm::Es256.generate().unwrap();
+    let kid = jwk.kid.clone().unwrap();
+    let sealed = SealedKey::seal(&jwk, &AesKeyWrapper::new(&kek("kek-1", K1)).unwrap()).unwrap();
+
+    // Same label, different key material.
+    let impostor = AesKeyWrapper::new(&kek("kek-1", K2)).unwrap();
+    assert!(matches!(sealed.open(&kid, &impostor), Err(JwkError::KeyWrapError(_))));
+
+    let wrong_password = PasswordKeyWrapper::new("hunter2", "master");
+    let sealed = SealedKey::seal(&jwk, &password("master")).unwrap();
+    assert!(matches!(sealed.open(&kid, &wrong_password), Err(JwkError::KeyWrapError(_))));
+
+    let signing_only = JwkBuilder::new("oct")
+        .set_key_id("kek")
+        .set_key_use("sig")
+        .set_symmetric_key(K1)
+        .build()
+        .unwrap();
+    assert!(matches!(AesKeyWrapper::new(&signing_only), Err(JwkError::OperationNotPermitted(_))));
+}
+
+#[test]
+fn test_password_iteration_bounds() {
+    let mut wrapper = password("master");
+    for iterations in [0, 1, 99_999, 10_000_001, u32::MAX] {
+        assert!(matches!(wrapper.set_iterations(iterations), Err(JwkError::KeyWrapError(_))), "{}", iterations);
+    }
+
+    // A stored iteration count outside of the range is refused before running PBKDF2.
+    let wrapped = wrapper.wrap_key(&[7; 32]).unwrap();
+    assert_eq!(wrapper.unwrap_key(&wrapped).unwrap(), vec![7; 32]);
+    let mut tampered = wrapped.clone();
+    tampered[..4].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert!(matches!(wrapper.unwrap_key(&tampered), Err(JwkError::KeyWrapError(_))));
+}
+
+#[test]
+fn test_encrypted_file_store_hides_private_keys() {
+    let dir = TempDir::new("files");
+    let files = FileKeyStore::open_encrypted(&dir, AesKeyWrapper::new(&kek("kek-1", K1)).unwrap()).unwrap();
+
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    let kid = store.rotate_at(t0()).unwrap().kid().to_string();
+    files.save_all(&store).unwrap();
+
+    let contents = fs::read_to_string(dir.join(format!("{}.json", kid))).unwrap();
+    let d = store.current_signing_key().unwrap().d.clone().unwrap();
+    assert!(!contents.contains(&d));
+    assert!(!contents.contains(r#""d""#));
+
+    let reloaded = files.load_store(KeyAlgorithm::Es256).unwrap();
+    assert_eq!(reloaded.keys(), store.keys());
+
+    // Without the KEK the store cannot be read.
+    let plain = FileKeyStore::open(&dir).unwrap();
+    assert!(matches!(plain.load(&kid), Err(JwkError::KeyWrapError(_))));
+}
+
+#[test]
+fn test_rewrap_keeps_signing_keys() {
+    let dir = TempDir::new("rewrap");
+    let mut files = FileKeyStore::open_encrypted(&dir, AesKeyWrapper::new(&kek("kek-1", K1)).unwrap()).unwrap();
+
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    let kid = store.rotate_at(t0()).unwrap().kid().to_string();
+    store.rotate_at(t0() + Duration::from_secs(60)).unwrap();
+    files.save_all(&store).unwrap();
+
+    let path = dir.join(format!("{}.json", kid));
+    let ciphertext = |path: &PathBuf| {
+        let record: serde_json::Value = serde_json::from_slice(&fs::read(path).unwrap()).unwrap();
+        (record["sealed"]["kek"].clone(), record["sealed"]["ciphertext"].clone())
+    };
+    let (kek_before, ciphertext_before) = ciphertext(&path);
+    assert_eq!(kek_before, "kek-1");
+
+    files.rewrap(password("master-2")).unwrap();
+    let (kek_after, ciphertext_after) = ciphertext(&path);
+    assert_eq!(kek_after, "master-2");
+    assert_eq!(ciphertext_after, ciphertext_before);
+    assert_eq!(files.load_store(KeyAlgorithm::Es256).unwrap().keys(), store.keys());
+
+    // The old KEK no longer opens the store, and re-running the re-wrap is a no-op.
+    let old = FileKeyStore::open_encrypted(&dir, AesKeyWrapper::new(&kek("kek-1", K1)).unwrap()).unwrap();
+    assert!(matches!(old.load(&kid), Err(JwkError::KeyWrapError(_))));
+    files.rewrap(password("master-2")).unwrap();
+    assert_eq!(ciphertext(&path), (kek_after, ciphertext_after));
+}
+
+#[test]
+fn test_custom_wrapper() {
+    struct Xor(u8);
+
+    impl KeyWrapper for Xor {
+        fn key_id(&self) -> String {
+            format!("xor-{}", self.0)
+        }
+
+        fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, JwkError> {
+            Ok(key.iter().map(|b| b ^ self.0).collect())
+        }
+
+        fn unwrap_key(&self, wrapped: &[u8]) -> Result<Vec<u8>, JwkError> {
+            self.wrap_key(wrapped)
+        }
+    }
+
+    let jwk = KeyAlgorithm::Es256.generate().unwrap();
+    let sealed = SealedKey::seal(&jwk, &Xor(0x5a)).unwrap();
+    assert_eq!(sealed.kek, "xor-90");
+    assert_eq!(sealed.open(jwk.kid.as_deref().unwrap(), &Xor(0x5a)).unwrap(), jwk);
+}
@@ -0,0 +1,95 @@
+use jwk_kit::error::JwkError;
+use jwk_kit::jwk::{Jwk, JwkBuilder, Jwks};
+use serde_json::json;
+
+const PARTNER_JWKS: &str = r#"{
+  "keys": [
+    {
+      "kty": "RSA",
+      "use": "sig",
+      "kid": "nOo3ZDrODXEK1jKWhXslHR_KXEg",
+      "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+      "e": "AQAB",
+      "issuer": "https://login.microsoftonline.com/tenant/v2.0",
+      "ext": true,
+      "x-rotation": {"generation": 3, "tags": ["prod"]}
+    }
+  ]
+}"#;
+
+#[test]
+fn test_unknown_members_round_trip() {
+    let jwks: Jwks = serde_json::from_str(PARTNER_JWKS).unwrap();
+    let jwk = &jwks.keys[0];
+
+    assert_eq!(jwk.extra.len(), 3);
+    assert_eq!(jwk.extra_str("issuer"), Some("https://login.microsoftonline.com/tenant/v2.0"));
+    assert_eq!(jwk.extra_bool("ext"), Some(true));
+    assert_eq!(jwk.extra_member("x-rotation").unwrap()["generation"], json!(3));
+
+    let original: serde_json::Value = serde_json::from_str(PARTNER_JWKS).unwrap();
+    assert_eq!(serde_json::to_value(&jwks).unwrap(), original);
+
+    // Extension members do not take part in the RFC 7638 thumbprint.
+    assert_eq!(jwk.thumbprint().unwrap(), "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs");
+}
+
+#[test]
+fn test_typed_extra_accessors() {
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Rotation {
+        generation: u32,
+        tags: Vec<String>,
+    }
+
+    let jwks: Jwks = serde_json::from_str(PARTNER_JWKS).unwrap();
+    let jwk = &jwks.keys[0];
+
+    let rotation: Option<Rotation> = jwk.extra_as("x-rotation").unwrap();
+    assert_eq!(rotation, Some(Rotation { generation: 3, tags: vec!["prod".into()] }));
+    assert_eq!(jwk.extra_as::<Rotation>("missing").unwrap(), None);
+    assert!(matches!(jwk.extra_as::<Rotation>("issuer"), Err(JwkError::InvalidJson(_))));
+    assert_eq!(jwk.extra_str("ext"), None);
+}
+
+#[test]
+fn test_builder_extra_members() {
+    let jwk = JwkBuilder::new("oct")
+        .set_symmetric_key("c2VjcmV0")
+        .set_extra_member("tenant", json!("acme"))
+        .build()
+        .unwrap();
+    assert_eq!(serde_json::to_value(&jwk).unwrap()["tenant"], json!("acme"));
+
+    let result = JwkBuilder::new("oct")
+        .set_symmetric_key("c2VjcmV0")
+        .set_extra_member("kid", json!("shadowed"))
+        .build();
+    assert_eq!(result, Err(JwkError::DuplicateMember("kid".into())));
+}
+
+#[test]
+fn test_strict_mode_rejects_unknown_and_duplicate_members() {
+    assert_eq!(Jwks::from_json_strict(PARTNER_JWKS), Err(JwkError::UnknownMember("ext".into())));
+
+    let duplicated = r#"{"kty":"oct","k":"c2VjcmV0","kid":"a","kid":"b"}"#;
+    assert_eq!(Jwk::from_json_strict(duplicated), Err(JwkError::DuplicateMember("kid".into())));
+
+    // Lenient deserialization lets the last of several extension members win.
+    let duplicated_extra = r#"{"kty":"oct","k":"c2VjcmV0","tenant":"a","tenant":"b"}"#;
+    assert_eq!(serde_json::from_str::<Jwk>(duplicated_extra).unwrap().extra_str("tenant"), Some("b"));
+    assert_eq!(Jwk::from_json_strict(duplicated_extra), Err(JwkError::DuplicateMember("tenant".into())));
+
+    let nested = r#"{"keys":[{"kty":"oct","k":"c2VjcmV0","x5c":["AA"]}],"keys":[]}"#;
+    assert_eq!(Jwks::from_json_strict(nested), Err(JwkError::DuplicateMember("keys".into())));
+
+    let extra_top_level = r#"{"keys":[],"issuer":"x"}"#;
+    assert_eq!(Jwks::from_json_strict(extra_top_level), Err(JwkError::UnknownMember("issuer".into())));
+
+    let clean = r#"{"keys":[{"kty":"oct","k":"c2VjcmV0","kid":"hmac-1","key_ops":["sign","verify"]}]}"#;
+    let jwks = Jwks::from_json_strict(clean).unwrap();
+    assert_eq!(jwks.keys[0].kid.as_deref(), Some("hmac-1"));
+    assert!(jwks.keys[0].extra.is_empty());
+
+    assert!(matches!(Jwk::from_json_strict("{\"kty\":"), Err(JwkError::InvalidJson(_))));
+}
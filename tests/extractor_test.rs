@@ -53,4 +53,38 @@ mod tests {
         let result = extract_rsa_n_e(&pem_data);
         assert_eq!(result.unwrap_err(), JwkError::MissingRsaParams);
     }
+
+    #[test]
+    fn test_extract_rsa_n_e_accepts_all_encodings() {
+        let read = |path: &str| fs::read_to_string(path).expect("Failed to read RSA PEM file");
+
+        // PKCS#8 private key vs SPKI public key
+        assert_eq!(
+            extract_rsa_n_e(&read("./examples/rsa/rsa-access-private.pem")).unwrap(),
+            extract_rsa_n_e(&read("./examples/rsa/rsa-access-public.pem")).unwrap()
+        );
+
+        // PKCS#1 private key vs PKCS#1 public key
+        assert_eq!(
+            extract_rsa_n_e(&read("./examples/rsa/rsa-legacy-private.pem")).unwrap(),
+            extract_rsa_n_e(&read("./examples/rsa/rsa-legacy-public.pem")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_extract_es256_coordinates_accepts_all_encodings() {
+        let read = |path: &str| fs::read_to_string(path).expect("Failed to read EC PEM file");
+
+        // PKCS#8 private key vs SPKI public key
+        assert_eq!(
+            extract_es256_coordinates(&read("./examples/ecdsa/ecdsa-access-private.pem")).unwrap(),
+            extract_es256_coordinates(&read("./examples/ecdsa/ecdsa-access-public.pem")).unwrap()
+        );
+
+        // SEC1 private key vs SPKI public key
+        assert_eq!(
+            extract_es256_coordinates(&read("./examples/ecdsa/ecdsa-legacy-private.pem")).unwrap(),
+            extract_es256_coordinates(&read("./examples/ecdsa/ecdsa-legacy-public.pem")).unwrap()
+        );
+    }
 }
@@ -0,0 +1,132 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use jwk_kit::error::JwkError;
+use jwk_kit::keystore::fs::FileKeyStore;
+use jwk_kit::keystore::{KeyAlgorithm, KeyState, KeyStore};
+use common::TempDir;
+
+#[test]
+fn test_save_and_reload_store() {
+    let dir = TempDir::new("reload");
+    let files = FileKeyStore::open(dir.join("keys")).unwrap();
+
+    let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    let first = store.rotate_at(t0).unwrap().kid().to_string();
+    let second = store.rotate_at(t0 + Duration::from_secs(60)).unwrap().kid().to_string();
+    store.transition_at(&first, KeyState::Retired, t0 + Duration::from_secs(120)).unwrap();
+    files.save_all(&store).unwrap();
+
+    let reloaded = files.load_store(KeyAlgorithm::Es256).unwrap();
+    assert_eq!(reloaded.keys(), store.keys());
+    assert_eq!(reloaded.current_signing_key().unwrap().kid.as_deref(), Some(second.as_str()));
+    assert!(reloaded.current_signing_key().unwrap().is_private());
+
+    let key = files.load(&first).unwrap();
+    assert_eq!(key.state, KeyState::Retired);
+    assert_eq!(key.expires_at, Some(t0 + Duration::from_secs(120)));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_files_are_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new("permissions");
+    let files = FileKeyStore::open(&dir).unwrap();
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    let kid = store.rotate().unwrap().kid().to_string();
+    files.save(&store.keys()[0]).unwrap();
+
+    let mode = |path: PathBuf| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode(dir.join(format!("{}.json", kid))), 0o600);
+    assert_eq!(mode(dir.to_path_buf()), 0o700);
+
+    // Only the key file and the lock file remain: the temporary file was renamed.
+    let mut names: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    let mut expected = vec![".lock".to_string(), format!("{}.json", kid)];
+    names.sort();
+    expected.sort();
+    assert_eq!(names, expected);
+}
+
+#[test]
+fn test_remove_and_invalid_kids() {
+    let dir = TempDir::new("remove");
+    let files = FileKeyStore::open(&dir).unwrap();
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    let kid = store.rotate().unwrap().kid().to_string();
+    files.save_all(&store).unwrap();
+
+    files.remove(&kid).unwrap();
+    assert_eq!(files.load(&kid), Err(JwkError::UnknownKeyId(kid.clone())));
+    assert_eq!(files.remove(&kid), Err(JwkError::UnknownKeyId(kid)));
+    assert_eq!(files.load("../etc/passwd"), Err(JwkError::InvalidKeyId("../etc/passwd".into())));
+
+    let mut key = store.keys()[0].clone();
+    key.jwk.kid = Some("a/b".into());
+    assert_eq!(files.save(&key), Err(JwkError::InvalidKeyId("a/b".into())));
+
+    fs::write(dir.join("broken.json"), "{").unwrap();
+    assert!(matches!(files.load_all(), Err(JwkError::InvalidJson(_))));
+}
+
+#[test]
+fn test_concurrent_writers() {
+    let dir = TempDir::new("concurrent");
+    let files = FileKeyStore::open(&dir).unwrap();
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    store.rotate().unwrap();
+    let key = store.keys()[0].clone();
+
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            let files = files.clone();
+            let mut key = key.clone();
+            std::thread::spawn(move || {
+                for j in 0..20u64 {
+                    key.created_at = SystemTime::UNIX_EPOCH + Duration::from_secs(i * 100 + j);
+                    files.save(&key).unwrap();
+                    // Readers never observe a partially written file.
+                    files.load(key.kid()).unwrap();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(files.load_all().unwrap().len(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_read_only_store() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new("read-only");
+    let files = FileKeyStore::open(&dir).unwrap();
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    store.rotate_at(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)).unwrap();
+    files.save_all(&store).unwrap();
+
+    // A reader without write access loads the keys, with or without the lock file.
+    let read_only = |path: &Path, mode| fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+    read_only(&dir.join(".lock"), 0o400);
+    read_only(&dir, 0o500);
+    assert_eq!(FileKeyStore::open(&dir).unwrap().load_store(KeyAlgorithm::Es256).unwrap().keys(), store.keys());
+
+    read_only(&dir, 0o700);
+    fs::remove_file(dir.join(".lock")).unwrap();
+    read_only(&dir, 0o500);
+    assert_eq!(FileKeyStore::open(&dir).unwrap().load_all().unwrap().len(), 1);
+
+    read_only(&dir, 0o700);
+}
@@ -0,0 +1,72 @@
+use jwk_kit::jwk::{create_jwks, Jwk, JwkBuilder, Jwks, JwksIndex};
+
+fn key(kid: &str, kty: &str, key_use: Option<&str>, alg: &str) -> Jwk {
+    let mut builder = JwkBuilder::new(kty);
+    builder.set_key_id(kid).set_algorithm(alg);
+    if let Some(key_use) = key_use {
+        builder.set_key_use(key_use);
+    }
+    match kty {
+        "RSA" => builder.set_modulus(&format!("modulus-{}", kid)).set_exponent("AQAB"),
+        _ => builder
+            .set_curve_type("P-256")
+            .set_x_coordinate(&format!("x-{}", kid))
+            .set_y_coordinate(&format!("y-{}", kid)),
+    };
+    builder.build().unwrap()
+}
+
+fn sample() -> Jwks {
+    create_jwks(vec![
+        key("rsa-sig", "RSA", Some("sig"), "RS256"),
+        key("rsa-enc", "RSA", Some("enc"), "RSA-OAEP"),
+        key("ec-sig", "EC", Some("sig"), "ES256"),
+        key("ec-any", "EC", None, "ES256"),
+    ])
+}
+
+#[test]
+fn test_find_by_kid_and_thumbprint() {
+    let jwks = sample();
+    assert_eq!(jwks.find_by_kid("ec-sig").unwrap().kty, "EC");
+    assert!(jwks.find_by_kid("missing").is_none());
+
+    let thumbprint = jwks.keys[1].thumbprint().unwrap();
+    assert_eq!(jwks.find_by_thumbprint(&thumbprint).unwrap().kid.as_deref(), Some("rsa-enc"));
+}
+
+#[test]
+fn test_query_iterators() {
+    let jwks = sample();
+    let kids = |keys: Vec<&Jwk>| keys.iter().map(|k| k.kid.clone().unwrap()).collect::<Vec<_>>();
+
+    assert_eq!(kids(jwks.by_use("enc").collect()), ["rsa-enc"]);
+    assert_eq!(kids(jwks.by_alg("ES256").collect()), ["ec-sig", "ec-any"]);
+    assert_eq!(kids(jwks.by_kty("RSA").collect()), ["rsa-sig", "rsa-enc"]);
+    assert_eq!(jwks.by_crv("P-256").count(), 2);
+    assert_eq!(kids(jwks.signing_keys().collect()), ["rsa-sig", "ec-sig", "ec-any"]);
+    assert_eq!(kids(jwks.encryption_keys().collect()), ["rsa-enc", "ec-any"]);
+    assert_eq!(kids(jwks.filter(|k| k.kid.as_deref().unwrap().starts_with("ec")).collect()), ["ec-sig", "ec-any"]);
+}
+
+#[test]
+fn test_index_lookup() {
+    let mut jwks = sample();
+    jwks.keys.push(key("rsa-sig", "RSA", Some("sig"), "RS512"));
+
+    let index = JwksIndex::from(jwks.clone());
+    assert_eq!(index.find_by_kid("rsa-sig").unwrap().alg.as_deref(), Some("RS256"));
+    assert!(index.find_by_kid("missing").is_none());
+    assert_eq!(index.into_inner(), jwks);
+}
+
+#[test]
+fn test_thumbprint_ignores_private_and_optional_members() {
+    let public = key("a", "EC", Some("sig"), "ES256");
+    let mut private = key("b", "EC", None, "ES384");
+    private.x = public.x.clone();
+    private.y = public.y.clone();
+    private.d = Some("private".into());
+
+    assert_eq!(public.thumbprint().unwrap(), private.thumbprint().unwrap());
+}
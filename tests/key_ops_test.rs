@@ -0,0 +1,111 @@
+use std::fs;
+use jwk_kit::error::JwkError;
+use jwk_kit::jwk::{create_jwks, Jwk, JwkBuilder, KeyOperation};
+use jwk_kit::lint::LintKind;
+use jwk_kit::loader::load_key;
+use jwk_kit::x509::SelfSignedCertificateBuilder;
+
+fn ec_private_key() -> Jwk {
+    load_key(&fs::read("./examples/ecdsa/ecdsa-legacy-private.pem").unwrap()).unwrap()
+}
+
+#[test]
+fn test_key_ops_round_trip() {
+    let jwk = JwkBuilder::new("oct")
+        .set_symmetric_key("c2VjcmV0")
+        .set_key_operations(&[KeyOperation::WrapKey, KeyOperation::UnwrapKey])
+        .build()
+        .unwrap();
+
+    let json = serde_json::to_value(&jwk).unwrap();
+    assert_eq!(json["key_ops"], serde_json::json!(["wrapKey", "unwrapKey"]));
+
+    let parsed: Jwk = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, jwk);
+    assert!(parsed.permits(KeyOperation::WrapKey));
+    assert!(!parsed.permits(KeyOperation::Encrypt));
+}
+
+#[test]
+fn test_key_ops_consistency_rules() {
+    let ec = ec_private_key();
+
+    let mut encrypting_ec = ec.clone();
+    encrypting_ec.key_ops = Some(vec!["encrypt".into()]);
+    assert!(matches!(encrypting_ec.validate(), Err(JwkError::InvalidKeyOps(_))));
+
+    let mut contradicting = ec.clone();
+    contradicting.use_ = Some("enc".into());
+    contradicting.key_ops = Some(vec!["sign".into()]);
+    assert!(matches!(contradicting.validate(), Err(JwkError::InvalidKeyOps(_))));
+
+    let mut duplicated = ec.clone();
+    duplicated.key_ops = Some(vec!["sign".into(), "sign".into()]);
+    assert!(matches!(duplicated.validate(), Err(JwkError::InvalidKeyOps(_))));
+
+    let mut unknown = ec.clone();
+    unknown.key_ops = Some(vec!["teleport".into()]);
+    assert!(matches!(unknown.validate(), Err(JwkError::InvalidKeyOps(_))));
+
+    let mut consistent = ec;
+    consistent.use_ = Some("sig".into());
+    consistent.key_ops = Some(vec!["sign".into(), "verify".into()]);
+    assert!(consistent.validate().is_ok());
+
+    let result = JwkBuilder::new("OKP")
+        .set_curve_type("Ed25519")
+        .set_x_coordinate("11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo")
+        .set_key_operations(&[KeyOperation::DeriveBits])
+        .build();
+    assert!(matches!(result, Err(JwkError::InvalidKeyOps(_))));
+}
+
+#[test]
+fn test_certificate_signing_respects_key_ops() {
+    let builder = SelfSignedCertificateBuilder::new("CN=key-ops-test");
+
+    let mut verify_only = ec_private_key();
+    verify_only.key_ops = Some(vec!["verify".into()]);
+    assert_eq!(
+        builder.build_with_jwk(&verify_only).unwrap_err(),
+        JwkError::OperationNotPermitted("sign".into())
+    );
+
+    let mut encryption_key = ec_private_key();
+    encryption_key.use_ = Some("enc".into());
+    assert_eq!(
+        builder.build_with_jwk(&encryption_key).unwrap_err(),
+        JwkError::OperationNotPermitted("sign".into())
+    );
+
+    let mut signing_key = ec_private_key();
+    signing_key.key_ops = Some(vec!["sign".into()]);
+    let issued = builder.build_with_jwk(&signing_key).unwrap();
+    assert_eq!(issued.jwk.x, signing_key.x);
+}
+
+#[test]
+fn test_key_ops_in_queries_and_lint() {
+    let mut signing = ec_private_key().to_public();
+    signing.kid = Some("sig-1".into());
+    signing.alg = Some("ES256".into());
+    signing.key_ops = Some(vec!["verify".into()]);
+
+    let mut both = signing.clone();
+    both.kid = Some("sig-2".into());
+    both.use_ = Some("sig".into());
+
+    let mut invalid = signing.clone();
+    invalid.kid = Some("sig-3".into());
+    invalid.key_ops = Some(vec!["decrypt".into()]);
+
+    let jwks = create_jwks(vec![signing, both, invalid]);
+    let kids: Vec<_> = jwks.signing_keys().filter_map(|jwk| jwk.kid.as_deref()).collect();
+    assert_eq!(kids, vec!["sig-1", "sig-2"]);
+    assert_eq!(jwks.encryption_keys().count(), 1);
+
+    let findings: Vec<_> = jwks.lint().findings.into_iter().map(|f| (f.index, f.kind)).collect();
+    assert_eq!(findings.len(), 2);
+    assert_eq!(findings[0], (1, LintKind::UseAndKeyOps));
+    assert!(matches!(findings[1], (2, LintKind::InvalidKeyOps(_))));
+}
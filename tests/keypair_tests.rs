@@ -1,5 +1,7 @@
-use jwk_kit::generator::rsa::generate_rsa_keypair_pem;
-use jwk_kit::generator::ecdsa::generate_es256_keypair_pem;
+use jwk_kit::error::JwkError;
+use jwk_kit::generator::KeyEncoding;
+use jwk_kit::generator::rsa::{extract_rsa_n_e, generate_rsa_keypair_pem, generate_rsa_keypair_pem_with_encoding};
+use jwk_kit::generator::ecdsa::{extract_es256_coordinates, generate_es256_keypair_pem, generate_es256_keypair_pem_with_encoding};
 
 #[test]
 fn test_generate_rsa_keypair_pem() {
@@ -22,3 +24,35 @@ fn test_generate_es256_keypair_pem() {
     assert!(!private_pem.is_empty());
     assert!(!public_pem.is_empty());
 }
+
+#[test]
+fn test_generate_rsa_keypair_pem_pkcs1() {
+    let (private_pem, public_pem) = generate_rsa_keypair_pem_with_encoding(2048, KeyEncoding::Pkcs1)
+        .expect("RSA PKCS#1 keypair generation should succeed");
+
+    assert!(private_pem.contains("BEGIN RSA PRIVATE KEY"));
+    assert!(public_pem.contains("BEGIN RSA PUBLIC KEY"));
+    assert_eq!(extract_rsa_n_e(&private_pem).unwrap(), extract_rsa_n_e(&public_pem).unwrap());
+}
+
+#[test]
+fn test_generate_es256_keypair_pem_sec1() {
+    let (private_pem, public_pem) = generate_es256_keypair_pem_with_encoding(KeyEncoding::Sec1)
+        .expect("ES256 SEC1 keypair generation should succeed");
+
+    assert!(private_pem.contains("BEGIN EC PRIVATE KEY"));
+    assert!(public_pem.contains("BEGIN PUBLIC KEY"));
+    assert_eq!(extract_es256_coordinates(&private_pem).unwrap(), extract_es256_coordinates(&public_pem).unwrap());
+}
+
+#[test]
+fn test_generate_keypair_rejects_mismatched_encoding() {
+    assert!(matches!(
+        generate_rsa_keypair_pem_with_encoding(2048, KeyEncoding::Sec1),
+        Err(JwkError::UnsupportedEncoding(_))
+    ));
+    assert!(matches!(
+        generate_es256_keypair_pem_with_encoding(KeyEncoding::Pkcs1),
+        Err(JwkError::UnsupportedEncoding(_))
+    ));
+}
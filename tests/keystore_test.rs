@@ -0,0 +1,91 @@
+use std::fs;
+use std::time::{Duration, SystemTime};
+use jwk_kit::error::JwkError;
+use jwk_kit::keystore::{KeyAlgorithm, KeyState, KeyStore};
+use jwk_kit::loader::load_key;
+
+#[test]
+fn test_rotate_retires_previous_active_key() {
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    assert!(store.current_signing_key().is_none());
+
+    let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let first = store.rotate_at(t0).unwrap().kid().to_string();
+    let second = store.rotate_at(t0 + Duration::from_secs(3600)).unwrap().kid().to_string();
+    assert_ne!(first, second);
+
+    let signing = store.current_signing_key().unwrap();
+    assert_eq!(signing.kid.as_deref(), Some(second.as_str()));
+    assert_eq!(signing.alg.as_deref(), Some("ES256"));
+    assert!(signing.is_private());
+
+    assert_eq!(store.get(&first).unwrap().state, KeyState::Retiring);
+    assert_eq!(store.get(&second).unwrap().activates_at, Some(t0 + Duration::from_secs(3600)));
+
+    let published = store.verification_keys();
+    assert_eq!(published.keys.len(), 2);
+    assert!(published.keys.iter().all(|jwk| !jwk.is_private()));
+    assert!(published.find_by_kid(&first).is_some());
+}
+
+#[test]
+fn test_lifecycle_transitions() {
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+    let pending = store.generate_at(KeyState::Pending, t0).unwrap().kid().to_string();
+    assert!(store.current_signing_key().is_none());
+    assert!(store.verification_keys().keys.is_empty());
+
+    store.transition_at(&pending, KeyState::Active, t0 + Duration::from_secs(60)).unwrap();
+    store.transition_at(&pending, KeyState::Retiring, t0 + Duration::from_secs(120)).unwrap();
+    assert_eq!(store.verification_keys().keys.len(), 1);
+
+    store.transition_at(&pending, KeyState::Retired, t0 + Duration::from_secs(180)).unwrap();
+    let key = store.get(&pending).unwrap();
+    assert_eq!(key.created_at, t0);
+    assert_eq!(key.activates_at, Some(t0 + Duration::from_secs(60)));
+    assert_eq!(key.expires_at, Some(t0 + Duration::from_secs(180)));
+    assert!(store.verification_keys().keys.is_empty());
+
+    assert!(matches!(
+        store.transition(&pending, KeyState::Active),
+        Err(JwkError::InvalidStateTransition(_))
+    ));
+    store.revoke(&pending).unwrap();
+    assert!(matches!(store.revoke(&pending), Err(JwkError::InvalidStateTransition(_))));
+    assert_eq!(store.revoke("missing"), Err(JwkError::UnknownKeyId("missing".into())));
+}
+
+#[test]
+fn test_revoked_key_is_withdrawn() {
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    let first = store.rotate().unwrap().kid().to_string();
+    let second = store.rotate().unwrap().kid().to_string();
+
+    store.revoke(&second).unwrap();
+    assert!(store.current_signing_key().is_none());
+
+    let kids: Vec<_> = store.verification_keys().keys.into_iter().filter_map(|jwk| jwk.kid).collect();
+    assert_eq!(kids, vec![first]);
+}
+
+#[test]
+fn test_insert_existing_keys() {
+    let mut store = KeyStore::new(KeyAlgorithm::Rs256 { bits: 2048 });
+
+    let mut jwk = load_key(&fs::read("./examples/rsa/rsa-legacy-private.pem").unwrap()).unwrap();
+    assert_eq!(store.insert(jwk.clone(), KeyState::Active).unwrap_err(), JwkError::MissingKeyId);
+
+    jwk.kid = Some("legacy".into());
+    store.insert(jwk.clone(), KeyState::Active).unwrap();
+    assert_eq!(
+        store.insert(jwk, KeyState::Pending).unwrap_err(),
+        JwkError::DuplicateKeyId("legacy".into())
+    );
+
+    let rotated = store.rotate().unwrap();
+    assert_eq!(rotated.jwk.kty, "RSA");
+    assert_eq!(rotated.jwk.alg.as_deref(), Some("RS256"));
+    assert_eq!(store.get("legacy").unwrap().state, KeyState::Retiring);
+}
@@ -0,0 +1,138 @@
+use std::fs;
+use jwk_kit::jwk::{create_jwks, Jwk, JwkBuilder, Jwks};
+use jwk_kit::lint::{LintKind, LintSeverity};
+use jwk_kit::loader::load_key;
+use jwk_kit::x509::jwk_from_certificate;
+
+fn published_key(path: &str, kid: &str, alg: &str) -> Jwk {
+    let mut jwk = load_key(&fs::read(path).unwrap()).unwrap().to_public();
+    jwk.kid = Some(kid.into());
+    jwk.alg = Some(alg.into());
+    jwk.use_ = Some("sig".into());
+    jwk
+}
+
+fn kinds(jwks: &Jwks) -> Vec<LintKind> {
+    jwks.lint().findings.into_iter().map(|f| f.kind).collect()
+}
+
+#[test]
+fn test_lint_clean_jwks() {
+    let jwks = create_jwks(vec![
+        published_key("./examples/rsa/rsa-legacy-public.pem", "rsa-1", "RS256"),
+        published_key("./examples/ecdsa/ecdsa-legacy-public.pem", "ec-1", "ES256"),
+    ]);
+
+    let report = jwks.lint();
+    assert!(report.is_clean(), "{:?}", report.findings);
+}
+
+#[test]
+fn test_lint_duplicate_and_missing_members() {
+    let rsa_jwk = published_key("./examples/rsa/rsa-legacy-public.pem", "key-1", "RS256");
+    let ec_jwk = published_key("./examples/ecdsa/ecdsa-legacy-public.pem", "key-1", "ES256");
+    let mut bare = rsa_jwk.clone();
+    bare.kid = None;
+    bare.alg = None;
+    bare.use_ = None;
+
+    let report = create_jwks(vec![rsa_jwk, ec_jwk, bare]).lint();
+
+    let duplicates: Vec<_> = report.errors().map(|f| f.index).collect();
+    assert_eq!(duplicates, vec![0, 1]);
+
+    let warnings: Vec<_> = report.warnings().map(|f| (f.index, f.kind.clone())).collect();
+    assert_eq!(warnings, vec![
+        (2, LintKind::MissingKid),
+        (2, LintKind::MissingAlg),
+        (2, LintKind::MissingUse),
+    ]);
+}
+
+#[test]
+fn test_lint_private_and_weak_keys() {
+    let mut private = load_key(&fs::read("./examples/ecdsa/ecdsa-legacy-private.pem").unwrap()).unwrap();
+    private.kid = Some("ec-1".into());
+    private.alg = Some("RS256".into());
+    private.use_ = Some("sig".into());
+
+    // 1024-bit modulus: 0x80 followed by 127 zero bytes.
+    let mut modulus = vec![0x80u8];
+    modulus.extend([0u8; 127]);
+    let weak = JwkBuilder::new("RSA")
+        .set_key_id("rsa-weak")
+        .set_algorithm("RS256")
+        .set_key_use("sig")
+        .set_modulus(&base64_url(&modulus))
+        .set_exponent("AQAB")
+        .build()
+        .unwrap();
+
+    let jwks = create_jwks(vec![private, weak]);
+    let report = jwks.lint();
+    assert!(report.has_errors());
+    assert_eq!(kinds(&jwks), vec![
+        LintKind::PrivateKeyMaterial,
+        LintKind::AlgorithmMismatch("RS256".into()),
+        LintKind::WeakRsaKey(1024),
+    ]);
+    assert_eq!(report.findings[2].kid.as_deref(), Some("rsa-weak"));
+}
+
+#[test]
+fn test_lint_invalid_members_and_curves() {
+    let mut bad_base64 = published_key("./examples/ecdsa/ecdsa-legacy-public.pem", "ec-1", "ES256");
+    bad_base64.x = Some("not+base64url==".into());
+
+    let mut unknown_curve = published_key("./examples/ecdsa/ecdsa-legacy-public.pem", "ec-2", "ES256");
+    unknown_curve.crv = Some("P-192".into());
+
+    let mut missing = published_key("./examples/rsa/rsa-legacy-public.pem", "rsa-1", "RS256");
+    missing.e = None;
+
+    let jwks = create_jwks(vec![bad_base64, unknown_curve, missing]);
+    assert_eq!(kinds(&jwks), vec![
+        LintKind::InvalidBase64("x".into()),
+        LintKind::UnknownCurve("P-192".into()),
+        LintKind::AlgorithmMismatch("ES256".into()),
+        LintKind::InvalidKey(jwk_kit::error::JwkError::MissingRsaParams),
+    ]);
+
+    let message = jwks.lint().findings[0].to_string();
+    assert_eq!(message, "error: key #0 (kid \"ec-1\"): invalid base64 in 'x'");
+}
+
+#[test]
+fn test_lint_secp256k1_key() {
+    // The secp256k1 generator point, as an RFC 8812 ES256K signing key.
+    let jwk = JwkBuilder::new("EC")
+        .set_key_id("k1")
+        .set_algorithm("ES256K")
+        .set_key_use("sig")
+        .set_curve_type("secp256k1")
+        .set_x_coordinate("eb5mfvncu6xVoGKVzocLBwKb_NstzijZWfKBWxb4F5g")
+        .set_y_coordinate("SDradyajxGVdpPv8DhEIqP0XtEimhVQZnEfQj_sQ1Lg")
+        .build()
+        .unwrap();
+
+    let report = create_jwks(vec![jwk]).lint();
+    assert!(report.is_clean(), "{:?}", report.findings);
+}
+
+#[test]
+fn test_lint_mismatched_x5c() {
+    let cert_jwk = jwk_from_certificate(&fs::read("./examples/x509/rsa-legacy-cert.pem").unwrap()).unwrap();
+
+    let mut jwk = published_key("./examples/ecdsa/ecdsa-legacy-public.pem", "ec-1", "ES256");
+    jwk.x5c = cert_jwk.x5c.clone();
+
+    let report = create_jwks(vec![jwk]).lint();
+    assert_eq!(report.findings.len(), 1);
+    assert_eq!(report.findings[0].severity, LintSeverity::Error);
+    assert!(matches!(report.findings[0].kind, LintKind::X509Mismatch(_)));
+}
+
+fn base64_url(bytes: &[u8]) -> String {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    URL_SAFE_NO_PAD.encode(bytes)
+}
@@ -0,0 +1,95 @@
+use std::fs;
+use jwk_kit::error::JwkError;
+use jwk_kit::generator::ecdsa::extract_es256_coordinates;
+use jwk_kit::generator::rsa::extract_rsa_n_e;
+use jwk_kit::jwk::{create_jwks, JwkBuilder};
+use jwk_kit::loader::{load_key, load_keys};
+
+fn read(path: &str) -> Vec<u8> {
+    fs::read(path).unwrap_or_else(|_| panic!("Missing test key file: {}", path))
+}
+
+#[test]
+fn test_load_key_from_pem_variants() {
+    let pairs = [
+        ("./examples/rsa/rsa-access-public.pem", "./examples/rsa/rsa-access-private.pem"),
+        ("./examples/rsa/rsa-legacy-public.pem", "./examples/rsa/rsa-legacy-private.pem"),
+    ];
+
+    for (public_path, private_path) in pairs {
+        let (n, e) = extract_rsa_n_e(&fs::read_to_string(public_path).unwrap()).unwrap();
+
+        let public_jwk = load_key(&read(public_path)).expect("RSA public key should load");
+        assert_eq!(public_jwk.kty, "RSA");
+        assert_eq!(public_jwk.n.as_deref(), Some(n.as_str()));
+        assert_eq!(public_jwk.e.as_deref(), Some(e.as_str()));
+        assert!(public_jwk.d.is_none());
+
+        let private_jwk = load_key(&read(private_path)).expect("RSA private key should load");
+        assert_eq!(private_jwk.n, public_jwk.n);
+        assert!(private_jwk.d.is_some() && private_jwk.p.is_some() && private_jwk.qi.is_some());
+    }
+}
+
+#[test]
+fn test_load_key_from_der() {
+    let pem_data = fs::read_to_string("./examples/ecdsa/ecdsa-legacy-private.pem").unwrap();
+    let (_, der) = rsa::pkcs8::der::pem::decode_vec(pem_data.as_bytes()).unwrap();
+
+    let jwk = load_key(&der).expect("SEC1 DER should load");
+    let (x, y) = extract_es256_coordinates(&pem_data).unwrap();
+    assert_eq!(jwk.crv.as_deref(), Some("P-256"));
+    assert_eq!(jwk.x, Some(x));
+    assert_eq!(jwk.y, Some(y));
+    assert!(jwk.d.is_some());
+}
+
+#[test]
+fn test_load_keys_from_multi_block_pem() {
+    let mut bundle = fs::read_to_string("./examples/rsa/rsa-access-public.pem").unwrap();
+    bundle.push_str(&fs::read_to_string("./examples/ecdsa/ecdsa-access-public.pem").unwrap());
+
+    let keys = load_keys(bundle.as_bytes()).unwrap();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[0].kty, "RSA");
+    assert_eq!(keys[1].kty, "EC");
+
+    assert_eq!(load_key(bundle.as_bytes()).unwrap_err(), JwkError::UnexpectedKeyCount(2));
+}
+
+#[test]
+fn test_load_keys_from_json() {
+    let jwk = JwkBuilder::new("EC")
+        .set_key_id("ec-1")
+        .set_curve_type("P-256")
+        .set_x_coordinate("x")
+        .set_y_coordinate("y")
+        .build()
+        .unwrap();
+
+    let single = serde_json::to_vec(&jwk).unwrap();
+    assert_eq!(load_key(&single).unwrap(), jwk);
+
+    let set = serde_json::to_vec(&create_jwks(vec![jwk.clone(), jwk.clone()])).unwrap();
+    assert_eq!(load_keys(&set).unwrap(), vec![jwk.clone(), jwk]);
+}
+
+#[test]
+fn test_load_keys_from_authorized_keys() {
+    let keys = load_keys(&read("./examples/ssh/authorized_keys")).unwrap();
+    assert_eq!(keys.len(), 2);
+
+    let rsa_jwk = load_key(&read("./examples/rsa/rsa-legacy-public.pem")).unwrap();
+    let ec_jwk = load_key(&read("./examples/ecdsa/ecdsa-legacy-public.pem")).unwrap();
+    assert_eq!(keys[0], rsa_jwk);
+    assert_eq!(keys[1], ec_jwk);
+}
+
+#[test]
+fn test_load_key_unrecognized() {
+    assert_eq!(load_key(b"not a key").unwrap_err(), JwkError::UnrecognizedKeyFormat);
+
+    // An unknown PEM label is not mistaken for a key type.
+    let pem = "-----BEGIN DH PARAMETERS-----\nMAA=\n-----END DH PARAMETERS-----\n";
+    assert_eq!(load_key(pem.as_bytes()).unwrap_err(), JwkError::UnrecognizedKeyFormat);
+}
@@ -0,0 +1,108 @@
+use jwk_kit::error::JwkError;
+use jwk_kit::jwk::{Jwk, create_jwks};
+use jwk_kit::keystore::KeyAlgorithm;
+use jwk_kit::oidc::{ProviderMetadata, ProviderMetadataBuilder};
+
+const ISSUER: &str = "https://auth.example.com";
+
+fn builder() -> ProviderMetadataBuilder {
+    let mut builder = ProviderMetadataBuilder::new(ISSUER);
+    builder.set_authorization_endpoint("https://auth.example.com/authorize");
+    builder
+}
+
+fn with(mut jwk: Jwk, use_: &str, alg: Option<&str>) -> Jwk {
+    jwk.use_ = Some(use_.to_string());
+    jwk.alg = alg.map(str::to_string);
+    jwk
+}
+
+#[test]
+fn test_algorithms_follow_keys() {
+    let rsa = KeyAlgorithm::Rs256 { bits: 2048 }.generate().unwrap().to_public();
+    let ec = KeyAlgorithm::Es256.generate().unwrap().to_public();
+    let jwks = create_jwks(vec![
+        with(rsa.clone(), "sig", None),
+        with(ec.clone(), "sig", Some("ES256")),
+        with(ec.clone(), "sig", Some("ES256")),
+        with(rsa.clone(), "enc", Some("RSA-OAEP")),
+        // An `alg` that does not fit the key is not advertised.
+        with(ec, "sig", Some("RS512")),
+    ]);
+
+    let metadata = builder().set_userinfo_endpoint("https://auth.example.com/userinfo").set_keys(&jwks).build().unwrap();
+    assert_eq!(metadata.id_token_signing_alg_values_supported, ["ES256", "RS256"]);
+    assert_eq!(metadata.userinfo_signing_alg_values_supported.as_deref(), Some(&["ES256".to_string(), "RS256".to_string()][..]));
+    assert_eq!(metadata.id_token_encryption_alg_values_supported.as_deref(), Some(&["RSA-OAEP".to_string()][..]));
+
+    // Encryption-only key sets cannot sign ID tokens.
+    let encryption_only = create_jwks(vec![with(rsa, "enc", Some("RSA-OAEP"))]);
+    assert!(matches!(builder().set_keys(&encryption_only).build(), Err(JwkError::InvalidMetadata(_))));
+}
+
+#[test]
+fn test_invalid_metadata_is_rejected() {
+    let jwks = create_jwks(vec![KeyAlgorithm::Es256.generate().unwrap()]);
+
+    let http = ProviderMetadataBuilder::new("http://auth.example.com")
+        .set_authorization_endpoint("https://auth.example.com/authorize")
+        .set_keys(&jwks)
+        .build();
+    assert!(matches!(http, Err(JwkError::InvalidMetadata(_))));
+
+    let query = ProviderMetadataBuilder::new("https://auth.example.com?tenant=a")
+        .set_authorization_endpoint("https://auth.example.com/authorize")
+        .set_keys(&jwks)
+        .build();
+    assert!(matches!(query, Err(JwkError::InvalidMetadata(_))));
+
+    let missing = ProviderMetadataBuilder::new(ISSUER).set_keys(&jwks).build();
+    assert!(matches!(missing, Err(JwkError::InvalidMetadata(_))));
+    assert!(matches!(builder().build(), Err(JwkError::InvalidMetadata(_))));
+    assert!(matches!(builder().set_token_endpoint("ftp://auth.example.com/token").set_keys(&jwks).build(), Err(JwkError::InvalidMetadata(_))));
+
+    let local = ProviderMetadataBuilder::new("http://localhost:8080")
+        .set_authorization_endpoint("http://localhost:8080/authorize")
+        .set_keys(&jwks)
+        .set_require_rs256(false)
+        .build()
+        .unwrap();
+    assert_eq!(local.jwks_uri, "http://localhost:8080/.well-known/jwks.json");
+}
+
+#[test]
+fn test_serialization() {
+    let jwks = create_jwks(vec![KeyAlgorithm::Es256.generate().unwrap()]);
+    let metadata = builder()
+        .set_scopes_supported(&["openid", "email"])
+        .set_extra_member("end_session_endpoint", serde_json::json!("https://auth.example.com/logout"))
+        .set_keys(&jwks)
+        .set_require_rs256(false)
+        .build()
+        .unwrap();
+
+    let json = serde_json::to_value(&metadata).unwrap();
+    assert_eq!(json["issuer"], ISSUER);
+    assert_eq!(json["response_types_supported"], serde_json::json!(["code"]));
+    assert_eq!(json["subject_types_supported"], serde_json::json!(["public"]));
+    assert_eq!(json["end_session_endpoint"], "https://auth.example.com/logout");
+    assert!(json.get("token_endpoint").is_none());
+    assert!(json.get("userinfo_signing_alg_values_supported").is_none());
+    assert!(json.get("id_token_encryption_alg_values_supported").is_none());
+
+    let parsed: ProviderMetadata = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, metadata);
+
+    // Extra members cannot override the typed ones.
+    let overriding = builder().set_extra_member("jwks_uri", serde_json::json!("https://evil.example.com")).set_keys(&jwks).build();
+    assert_eq!(overriding, Err(JwkError::DuplicateMember("jwks_uri".into())));
+}
+
+#[test]
+fn test_rs256_is_required() {
+    let jwks = create_jwks(vec![KeyAlgorithm::Es256.generate().unwrap()]);
+    assert!(matches!(builder().set_keys(&jwks).build(), Err(JwkError::InvalidMetadata(_))));
+
+    let metadata = builder().set_keys(&jwks).set_require_rs256(false).build().unwrap();
+    assert_eq!(metadata.id_token_signing_alg_values_supported, ["ES256"]);
+}
@@ -0,0 +1,62 @@
+use std::fs;
+use jwk_kit::error::JwkError;
+use jwk_kit::jwk::{create_jwks, JwkBuilder, Jwks};
+use jwk_kit::loader::load_key;
+
+fn private_set() -> Jwks {
+    let mut rsa_jwk = load_key(&fs::read("./examples/rsa/rsa-legacy-private.pem").unwrap()).unwrap();
+    rsa_jwk.kid = Some("rsa-1".into());
+
+    let mut ec_jwk = load_key(&fs::read("./examples/ecdsa/ecdsa-legacy-private.pem").unwrap()).unwrap();
+    ec_jwk.kid = Some("ec-1".into());
+
+    let oct_jwk = JwkBuilder::new("oct")
+        .set_key_id("hmac-1")
+        .set_symmetric_key("c2VjcmV0")
+        .build()
+        .unwrap();
+
+    create_jwks(vec![rsa_jwk, ec_jwk, oct_jwk])
+}
+
+#[test]
+fn test_jwk_to_public_strips_private_members() {
+    let jwks = private_set();
+    let rsa_jwk = &jwks.keys[0];
+    assert!(rsa_jwk.is_private());
+
+    let public = rsa_jwk.to_public();
+    assert!(!public.is_private());
+    assert_eq!(public.n, rsa_jwk.n);
+    assert_eq!(public.kid, rsa_jwk.kid);
+    assert_eq!(public.thumbprint().unwrap(), rsa_jwk.thumbprint().unwrap());
+
+    let json = serde_json::to_value(&public).unwrap();
+    for member in ["d", "p", "q", "dp", "dq", "qi", "k"] {
+        assert!(json.get(member).is_none(), "'{}' leaked", member);
+    }
+}
+
+#[test]
+fn test_jwks_to_public_drops_symmetric_keys() {
+    let public = private_set().to_public();
+    let kids: Vec<_> = public.keys.iter().map(|k| k.kid.as_deref().unwrap()).collect();
+    assert_eq!(kids, ["rsa-1", "ec-1"]);
+    assert!(public.keys.iter().all(|k| !k.is_private()));
+}
+
+#[test]
+fn test_serialize_public_refuses_private_material() {
+    let jwks = private_set();
+    assert_eq!(jwks.serialize_public(), Err(JwkError::PrivateKeyMaterial("rsa-1".into())));
+
+    let json = jwks.to_public().serialize_public().unwrap();
+    let parsed: Jwks = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, jwks.to_public());
+}
+
+#[test]
+fn test_oct_key_requires_k() {
+    let result = JwkBuilder::new("oct").set_key_id("hmac").build();
+    assert_eq!(result.unwrap_err(), JwkError::MissingOctParams);
+}
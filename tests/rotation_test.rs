@@ -0,0 +1,153 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use jwk_kit::keystore::rotation::{Clock, FakeClock, RotationDriver, RotationPolicy};
+use jwk_kit::keystore::{KeyAlgorithm, KeyState, KeyStore};
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn driver() -> (RotationDriver<Arc<FakeClock>>, Arc<FakeClock>, SystemTime) {
+    let start = SystemTime::UNIX_EPOCH + 20_000 * DAY;
+    let clock = Arc::new(FakeClock::new(start));
+    let mut policy = RotationPolicy::new(30 * DAY, 48 * HOUR, 7 * DAY);
+    policy.max_cache_age = 365 * DAY;
+    let driver = RotationDriver::new(KeyStore::new(KeyAlgorithm::Es256), policy, clock.clone());
+    (driver, clock, start)
+}
+
+fn kids(driver: &RotationDriver<Arc<FakeClock>>, state: KeyState) -> Vec<String> {
+    driver
+        .store()
+        .keys()
+        .iter()
+        .filter(|key| key.state == state)
+        .map(|key| key.kid().to_string())
+        .collect()
+}
+
+#[test]
+fn test_rotation_schedule() {
+    let (mut driver, clock, start) = driver();
+
+    // Bootstrap: one active key, cacheable until the next key is published.
+    let step = driver.tick().unwrap();
+    assert!(step.changed);
+    assert_eq!(step.jwks.keys.len(), 1);
+    assert_eq!(step.max_age, 28 * DAY);
+    assert_eq!(step.next_tick_at, start + 28 * DAY);
+    let first = kids(&driver, KeyState::Active).remove(0);
+
+    // Nothing to do until the next key must be published.
+    clock.advance(10 * DAY);
+    let step = driver.tick().unwrap();
+    assert!(!step.changed);
+    assert_eq!(step.max_age, 18 * DAY);
+
+    // 48 hours before rotation, the next key is published but does not sign yet.
+    clock.set(start + 28 * DAY);
+    let step = driver.tick().unwrap();
+    assert!(step.changed);
+    assert_eq!(step.jwks.keys.len(), 2);
+    assert_eq!(step.next_tick_at, start + 30 * DAY);
+    assert_eq!(step.max_age, 30 * DAY);
+    let second = kids(&driver, KeyState::Pending).remove(0);
+    assert_eq!(driver.store().current_signing_key().unwrap().kid.as_deref(), Some(first.as_str()));
+
+    // At rotation, the new key signs and the old one keeps verifying for 7 days.
+    clock.set(start + 30 * DAY);
+    let step = driver.tick().unwrap();
+    assert_eq!(driver.store().current_signing_key().unwrap().kid.as_deref(), Some(second.as_str()));
+    assert_eq!(kids(&driver, KeyState::Retiring), vec![first.clone()]);
+    assert_eq!(step.jwks.keys.len(), 2);
+    assert_eq!(step.next_tick_at, start + 37 * DAY);
+
+    // After the retention period, the old key is no longer published.
+    clock.set(start + 37 * DAY);
+    let step = driver.tick().unwrap();
+    assert_eq!(kids(&driver, KeyState::Retired), vec![first.clone()]);
+    assert_eq!(step.jwks.keys.len(), 1);
+    assert!(step.jwks.find_by_kid(&first).is_none());
+    assert_eq!(step.cache_control(), format!("public, max-age={}", (21 * DAY).as_secs()));
+    assert_eq!(step.next_tick_at, start + 58 * DAY);
+}
+
+#[test]
+fn test_catch_up_after_downtime() {
+    let (mut driver, clock, start) = driver();
+    driver.tick().unwrap();
+    let first = kids(&driver, KeyState::Active).remove(0);
+
+    // The driver did not run for 100 days: rotate immediately and start the retention period.
+    clock.advance(100 * DAY);
+    let step = driver.tick().unwrap();
+    let current = driver.store().current_signing_key().unwrap().kid.clone().unwrap();
+    assert_ne!(current, first);
+    assert_eq!(driver.store().get(&current).unwrap().activates_at, Some(start + 100 * DAY));
+    assert_eq!(driver.store().get(&first).unwrap().expires_at, Some(start + 107 * DAY));
+    assert_eq!(step.max_age, 28 * DAY);
+    assert_eq!(step.jwks.keys.len(), 2);
+}
+
+#[test]
+fn test_revoked_signing_key_is_replaced() {
+    let (mut driver, clock, _) = driver();
+    driver.tick().unwrap();
+    let first = kids(&driver, KeyState::Active).remove(0);
+
+    driver.store_mut().revoke(&first).unwrap();
+    clock.advance(HOUR);
+    let step = driver.tick().unwrap();
+
+    let current = driver.store().current_signing_key().unwrap();
+    assert_ne!(current.kid.as_deref(), Some(first.as_str()));
+    assert!(step.jwks.find_by_kid(&first).is_none());
+    assert_eq!(step.jwks.keys.len(), 1);
+    assert_eq!(driver.clock().now(), clock.now());
+}
+
+#[test]
+fn test_cached_jwks_expires_before_next_activation() {
+    let start = SystemTime::UNIX_EPOCH + 20_000 * DAY;
+    let clock = FakeClock::new(start);
+    let mut driver = RotationDriver::new(KeyStore::new(KeyAlgorithm::Es256), RotationPolicy::new(30 * DAY, 48 * HOUR, 7 * DAY), &clock);
+    assert_eq!(driver.tick().unwrap().max_age, DAY);
+
+    // Fetched shortly before the next key is published: the copy expires before it signs.
+    clock.set(start + 28 * DAY - HOUR);
+    let fetched = driver.tick().unwrap();
+    assert_eq!(fetched.jwks.keys.len(), 1);
+    assert_eq!(fetched.max_age, HOUR);
+
+    clock.set(start + 28 * DAY);
+    let published = driver.tick().unwrap();
+    assert_eq!(published.jwks.keys.len(), 2);
+    assert!(start + 28 * DAY - HOUR + fetched.max_age < start + 30 * DAY);
+}
+
+#[test]
+fn test_unbounded_durations_mean_never() {
+    let start = SystemTime::UNIX_EPOCH + 20_000 * DAY;
+    let clock = Arc::new(FakeClock::new(start));
+
+    // Never rotate: the first key stays active, and the JWKS is cached for the longest time allowed.
+    let policy = RotationPolicy::new(Duration::MAX, Duration::MAX, 7 * DAY);
+    let mut driver = RotationDriver::new(KeyStore::new(KeyAlgorithm::Es256), policy, clock.clone());
+    driver.tick().unwrap();
+    clock.advance(10_000 * DAY);
+    let step = driver.tick().unwrap();
+    assert!(!step.changed);
+    assert_eq!(step.jwks.keys.len(), 1);
+    assert_eq!(step.max_age, DAY);
+
+    // Never retire: a replaced key stays published.
+    let policy = RotationPolicy::new(30 * DAY, 48 * HOUR, Duration::MAX);
+    let mut driver = RotationDriver::new(KeyStore::new(KeyAlgorithm::Es256), policy, clock.clone());
+    driver.tick().unwrap();
+    let first = kids(&driver, KeyState::Active).remove(0);
+    clock.advance(100 * DAY);
+    driver.tick().unwrap();
+    clock.advance(10_000 * DAY);
+    driver.tick().unwrap();
+    assert!(kids(&driver, KeyState::Retiring).contains(&first));
+    assert_eq!(driver.store().get(&first).unwrap().expires_at, None);
+}
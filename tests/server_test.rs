@@ -0,0 +1,150 @@
+#![cfg(feature = "server")]
+
+use std::time::{Duration, SystemTime};
+use axum::body::{Body, to_bytes};
+use axum::http::{HeaderMap, Request, StatusCode, header};
+use jwk_kit::jwk::{Jwks, JwkBuilder, create_jwks};
+use jwk_kit::keystore::rotation::{Clock, FakeClock, RotationDriver, RotationPolicy};
+use jwk_kit::keystore::{KeyAlgorithm, KeyStore};
+use jwk_kit::oidc::{OPENID_CONFIGURATION_PATH, ProviderMetadata, ProviderMetadataBuilder};
+use jwk_kit::server::{DiscoveryEndpoint, JWK_SET_CONTENT_TYPE, JWKS_PATH, JwksEndpoint, ProviderEndpoints};
+use tower::ServiceExt;
+
+fn request(if_none_match: Option<&str>) -> Request<Body> {
+    let mut builder = Request::get(JWKS_PATH);
+    if let Some(etag) = if_none_match {
+        builder = builder.header(header::IF_NONE_MATCH, etag);
+    }
+    builder.body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn test_serves_public_keys() {
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    store.rotate().unwrap();
+    let mut jwks = store.verification_keys();
+    jwks.keys[0] = store.current_signing_key().unwrap().clone();
+    jwks.keys.push(JwkBuilder::new("oct").set_key_id("hmac").set_symmetric_key("c2VjcmV0").build().unwrap());
+
+    let endpoint = JwksEndpoint::new(&jwks, Duration::from_secs(600)).unwrap();
+    let response = endpoint.router().oneshot(request(None)).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], JWK_SET_CONTENT_TYPE);
+    assert_eq!(response.headers()[header::CACHE_CONTROL], "public, max-age=600");
+    assert_eq!(response.headers()[header::ETAG], endpoint.etag().as_str());
+    assert!(endpoint.etag().starts_with('"'));
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let served: Jwks = serde_json::from_slice(&body).unwrap();
+    assert_eq!(served.keys.len(), 1);
+    assert!(!served.keys[0].is_private());
+}
+
+#[tokio::test]
+async fn test_conditional_requests() {
+    let jwks = create_jwks(vec![KeyAlgorithm::Es256.generate().unwrap()]);
+    let endpoint = JwksEndpoint::new(&jwks, Duration::from_secs(60)).unwrap();
+    let etag = endpoint.etag();
+    let router = endpoint.router();
+
+    for header in [etag.clone(), format!("W/{}", etag), format!("\"other\", {}", etag), "*".to_string()] {
+        let response = router.clone().oneshot(request(Some(&header))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED, "If-None-Match: {}", header);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+        assert!(to_bytes(response.into_body(), usize::MAX).await.unwrap().is_empty());
+    }
+
+    let response = router.clone().oneshot(request(Some("\"stale\""))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Updating the keys changes the ETag seen through existing routers.
+    endpoint.update(&create_jwks(vec![KeyAlgorithm::Es256.generate().unwrap()]), Duration::from_secs(60)).unwrap();
+    assert_ne!(endpoint.etag(), etag);
+    let response = router.oneshot(request(Some(&etag))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_cache_control_follows_rotation() {
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    let clock = FakeClock::new(SystemTime::UNIX_EPOCH + 20_000 * DAY);
+    let policy = RotationPolicy::new(30 * DAY, DAY, 7 * DAY);
+    let mut driver = RotationDriver::new(KeyStore::new(KeyAlgorithm::Es256), policy, &clock);
+
+    let endpoint = JwksEndpoint::from_step(&driver.tick().unwrap()).unwrap();
+    clock.advance(29 * DAY);
+    let step = driver.tick().unwrap();
+    endpoint.update_from_step(&step).unwrap();
+
+    let response = endpoint.respond_at(&HeaderMap::new(), clock.now());
+    assert_eq!(response.headers()[header::CACHE_CONTROL], step.cache_control().as_str());
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(serde_json::from_slice::<Jwks>(&body).unwrap().keys.len(), 2);
+
+    // Later responses count down to the same instant instead of restarting the max-age.
+    let later = endpoint.respond_at(&HeaderMap::new(), clock.now() + HOUR);
+    assert_eq!(later.headers()[header::CACHE_CONTROL], format!("public, max-age={}", (step.max_age - HOUR).as_secs()).as_str());
+    let expired = endpoint.respond_at(&HeaderMap::new(), step.fresh_until + HOUR);
+    assert_eq!(expired.headers()[header::CACHE_CONTROL], "public, max-age=0");
+}
+
+#[tokio::test]
+async fn test_serves_discovery_alongside_keys() {
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    store.rotate().unwrap();
+    let jwks = store.published_keys();
+    let metadata = ProviderMetadataBuilder::new("https://auth.example.com")
+        .set_authorization_endpoint("https://auth.example.com/authorize")
+        .set_keys(&jwks)
+        .set_require_rs256(false)
+        .build()
+        .unwrap();
+
+    let discovery = DiscoveryEndpoint::new(&metadata, Duration::from_secs(300)).unwrap();
+    let router = discovery.route(JwksEndpoint::new(&jwks, Duration::from_secs(300)).unwrap().router());
+
+    let response = router.clone().oneshot(Request::get(OPENID_CONFIGURATION_PATH).body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    assert_eq!(response.headers()[header::ETAG], discovery.etag().as_str());
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(serde_json::from_slice::<ProviderMetadata>(&body).unwrap(), metadata);
+
+    let conditional = Request::get(OPENID_CONFIGURATION_PATH).header(header::IF_NONE_MATCH, discovery.etag()).body(Body::empty()).unwrap();
+    assert_eq!(router.clone().oneshot(conditional).await.unwrap().status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(router.oneshot(request(None)).await.unwrap().status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_discovery_follows_key_updates() {
+    let mut metadata = ProviderMetadataBuilder::new("https://auth.example.com");
+    metadata.set_authorization_endpoint("https://auth.example.com/authorize");
+
+    let rsa = create_jwks(vec![KeyAlgorithm::Rs256 { bits: 2048 }.generate().unwrap()]);
+    let endpoints = ProviderEndpoints::new(&metadata, &rsa, Duration::from_secs(300)).unwrap();
+    let router = endpoints.router();
+    let served = |router: axum::Router| async move {
+        let response = router.oneshot(Request::get(OPENID_CONFIGURATION_PATH).body(Body::empty()).unwrap()).await.unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<ProviderMetadata>(&body).unwrap()
+    };
+    assert_eq!(served(router.clone()).await.id_token_signing_alg_values_supported, ["RS256"]);
+
+    // Adding a key of another algorithm updates both documents.
+    let mut both = rsa.clone();
+    both.keys.push(KeyAlgorithm::Es256.generate().unwrap().to_public());
+    endpoints.update(&both, Duration::from_secs(300)).unwrap();
+    assert_eq!(served(router.clone()).await.id_token_signing_alg_values_supported, ["ES256", "RS256"]);
+    let response = router.clone().oneshot(request(None)).await.unwrap();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(serde_json::from_slice::<Jwks>(&body).unwrap().keys.len(), 2);
+
+    // Metadata that can no longer be built leaves both documents unchanged.
+    let etags = (endpoints.jwks_endpoint().etag(), endpoints.discovery_endpoint().etag());
+    let es256 = create_jwks(vec![KeyAlgorithm::Es256.generate().unwrap()]);
+    assert!(endpoints.update(&es256, Duration::from_secs(300)).is_err());
+    assert_eq!((endpoints.jwks_endpoint().etag(), endpoints.discovery_endpoint().etag()), etags);
+}
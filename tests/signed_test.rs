@@ -0,0 +1,172 @@
+mod common;
+
+use std::time::Duration;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jwk_kit::error::JwkError;
+use jwk_kit::jwk::{Jwks, create_jwks};
+use jwk_kit::keystore::{KeyAlgorithm, KeyStore};
+use jwk_kit::signed::{JwksSigner, JwksVerifier, SIGNED_JWKS_TYPE};
+use common::{signing_key, t0};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn published() -> Jwks {
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    store.rotate_at(t0()).unwrap();
+    store.published_keys()
+}
+
+fn part(token: &str, index: usize) -> serde_json::Value {
+    serde_json::from_slice(&URL_SAFE_NO_PAD.decode(token.split('.').nth(index).unwrap()).unwrap()).unwrap()
+}
+
+#[test]
+fn test_sign_and_verify() {
+    let jwks = published();
+
+    for root in [signing_key(KeyAlgorithm::Es256, "root-ec"), signing_key(KeyAlgorithm::Rs256 { bits: 2048 }, "root-rsa")] {
+        let token = JwksSigner::new(&root)
+            .unwrap()
+            .set_issuer("https://auth.example.com")
+            .set_subject("https://auth.example.com")
+            .set_lifetime(DAY)
+            .sign_at(&jwks, t0())
+            .unwrap();
+
+        let header = part(&token, 0);
+        assert_eq!(header["typ"], SIGNED_JWKS_TYPE);
+        assert_eq!(header["kid"], root.kid.clone().unwrap());
+        assert!(part(&token, 1).get("d").is_none());
+
+        let mut verifier = JwksVerifier::new(&create_jwks(vec![root.to_public()]));
+        verifier.set_issuer("https://auth.example.com");
+        let verified = verifier.verify_at(&token, t0() + Duration::from_secs(60)).unwrap();
+        assert_eq!(verified.jwks, jwks);
+        assert_eq!(verified.signed_by, root.kid);
+        assert_eq!(verified.issued_at, Some(t0()));
+        assert_eq!(verified.expires_at, Some(t0() + DAY));
+    }
+}
+
+#[test]
+fn test_rejects_untrusted_documents() {
+    let root = signing_key(KeyAlgorithm::Es256, "root");
+    let token = JwksSigner::new(&root).unwrap().set_issuer("https://auth.example.com").set_lifetime(DAY).sign_at(&published(), t0()).unwrap();
+    let verifier = JwksVerifier::new(&create_jwks(vec![root.to_public()]));
+
+    // A payload swapped for another key set no longer matches the signature.
+    let mut parts: Vec<&str> = token.split('.').collect();
+    let forged = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&published()).unwrap());
+    parts[1] = &forged;
+    assert_eq!(verifier.verify_at(&parts.join("."), t0()), Err(JwkError::InvalidSignature));
+
+    // Another key with the same kid.
+    let other = signing_key(KeyAlgorithm::Es256, "root");
+    assert_eq!(JwksVerifier::new(&create_jwks(vec![other.to_public()])).verify_at(&token, t0()), Err(JwkError::InvalidSignature));
+    assert!(matches!(JwksVerifier::new(&published()).verify_at(&token, t0()), Err(JwkError::UnknownKeyId(_))));
+
+    assert!(matches!(verifier.verify_at(&token, t0() + DAY), Err(JwkError::InvalidToken(_))));
+    let mut expecting = verifier.clone();
+    expecting.set_issuer("https://other.example.com");
+    assert!(matches!(expecting.verify_at(&token, t0()), Err(JwkError::UntrustedIssuer(_))));
+
+    let unsigned = format!("{}.{}.", URL_SAFE_NO_PAD.encode(r#"{"alg":"none"}"#), parts[1]);
+    assert!(matches!(verifier.verify_at(&unsigned, t0()), Err(JwkError::UnsupportedAlgorithm(_))));
+}
+
+#[test]
+fn test_key_use_is_respected() {
+    let mut encryption = signing_key(KeyAlgorithm::Es256, "enc");
+    encryption.use_ = Some("enc".to_string());
+    assert!(matches!(JwksSigner::new(&encryption), Err(JwkError::OperationNotPermitted(_))));
+    assert!(matches!(JwksSigner::new(&signing_key(KeyAlgorithm::Es256, "pub").to_public()), Err(JwkError::SigningFailed(_))));
+
+    let root = signing_key(KeyAlgorithm::Es256, "root");
+    let token = JwksSigner::new(&root).unwrap().sign_at(&published(), t0()).unwrap();
+
+    // The pinned key must allow `verify`, and its `alg` must match the document's.
+    let mut sign_only = root.to_public();
+    sign_only.key_ops = Some(vec!["sign".to_string()]);
+    let mut other_alg = root.to_public();
+    other_alg.alg = Some("ES384".to_string());
+    for pinned in [sign_only, other_alg] {
+        assert_eq!(JwksVerifier::new(&create_jwks(vec![pinned])).verify_at(&token, t0()), Err(JwkError::InvalidSignature));
+    }
+}
+
+#[test]
+fn test_pinned_keys_must_be_valid() {
+    let root = signing_key(KeyAlgorithm::Es256, "root");
+    let token = JwksSigner::new(&root).unwrap().set_lifetime(DAY).sign_at(&published(), t0()).unwrap();
+    let mut anonymous_root = root.clone();
+    anonymous_root.kid = None;
+    let unnamed = JwksSigner::new(&anonymous_root).unwrap().set_lifetime(DAY).sign_at(&published(), t0()).unwrap();
+
+    let mut revoked = root.to_public();
+    revoked.set_revoked(true);
+    let mut expired = root.to_public();
+    expired.set_expires_at(Some(t0()));
+    let mut not_yet_valid = root.to_public();
+    not_yet_valid.set_not_before(Some(t0() + DAY));
+    for pinned in [revoked, expired, not_yet_valid] {
+        assert_eq!(JwksVerifier::new(&create_jwks(vec![pinned.clone()])).verify_at(&token, t0()), Err(JwkError::UnknownKeyId("root".into())));
+
+        // Without a kid in the header, the pinned key is not tried either.
+        let mut anonymous = pinned;
+        anonymous.kid = None;
+        assert_eq!(JwksVerifier::new(&create_jwks(vec![anonymous])).verify_at(&unnamed, t0()), Err(JwkError::InvalidSignature));
+    }
+
+    assert!(matches!(JwksSigner::new(&root).unwrap().set_lifetime(Duration::MAX).sign_at(&published(), t0()), Err(JwkError::SigningFailed(_))));
+}
+
+#[test]
+fn test_private_keys_are_never_signed() {
+    let root = signing_key(KeyAlgorithm::Es256, "root");
+    let private = create_jwks(vec![KeyAlgorithm::Es256.generate().unwrap()]);
+
+    let signer = JwksSigner::new(&root).unwrap();
+    assert!(matches!(signer.sign_at(&private, t0()), Err(JwkError::PrivateKeyMaterial(_))));
+    assert!(signer.sign_at(&private.to_public(), t0()).is_ok());
+}
+
+#[test]
+fn test_claims_are_checked() {
+    let root = signing_key(KeyAlgorithm::Es256, "root");
+    let mut verifier = JwksVerifier::new(&create_jwks(vec![root.to_public()]));
+    let unbounded = JwksSigner::new(&root).unwrap().sign_at(&published(), t0()).unwrap();
+
+    // Documents without `exp` are rejected unless the verifier opts out.
+    assert!(matches!(verifier.verify_at(&unbounded, t0()), Err(JwkError::InvalidToken(_))));
+    verifier.set_require_expiry(false);
+    assert!(verifier.verify_at(&unbounded, t0() + 100 * DAY).is_ok());
+    verifier.set_max_age(DAY);
+    assert!(verifier.verify_at(&unbounded, t0() + Duration::from_secs(60)).is_ok());
+    assert!(matches!(verifier.verify_at(&unbounded, t0() + DAY), Err(JwkError::InvalidToken(_))));
+
+    // A document issued after `now` is rejected, even before its `exp`.
+    let token = JwksSigner::new(&root).unwrap().set_lifetime(DAY).sign_at(&published(), t0() + Duration::from_secs(60)).unwrap();
+    assert!(matches!(verifier.verify_at(&token, t0()), Err(JwkError::InvalidToken(_))));
+    assert!(verifier.verify_at(&token, t0() + Duration::from_secs(60)).is_ok());
+}
+
+#[test]
+fn test_critical_headers_are_rejected() {
+    use p256::ecdsa::signature::Signer;
+
+    let root = signing_key(KeyAlgorithm::Es256, "root");
+    let verifier = JwksVerifier::new(&create_jwks(vec![root.to_public()]));
+    let token = JwksSigner::new(&root).unwrap().set_lifetime(DAY).sign_at(&published(), t0()).unwrap();
+
+    // Re-sign the same payload under a header with a `crit` extension.
+    let mut header = part(&token, 0);
+    header["crit"] = serde_json::json!(["b64"]);
+    header["b64"] = serde_json::json!(false);
+    let signing_input = format!("{}.{}", URL_SAFE_NO_PAD.encode(header.to_string()), token.split('.').nth(1).unwrap());
+    let d = URL_SAFE_NO_PAD.decode(root.d.as_deref().unwrap()).unwrap();
+    let signature: p256::ecdsa::Signature = p256::ecdsa::SigningKey::from_slice(&d).unwrap().sign(signing_input.as_bytes());
+    let critical = format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature.to_bytes()));
+
+    assert!(verifier.verify_at(&token, t0()).is_ok());
+    assert!(matches!(verifier.verify_at(&critical, t0()), Err(JwkError::InvalidToken(_))));
+}
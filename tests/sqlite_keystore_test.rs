@@ -0,0 +1,133 @@
+#![cfg(feature = "sqlite")]
+
+mod common;
+
+use std::time::{Duration, SystemTime};
+use jwk_kit::error::JwkError;
+use jwk_kit::jwk::{JwkBuilder, KeyOperation};
+use jwk_kit::keystore::envelope::AesKeyWrapper;
+use jwk_kit::keystore::sqlite::SqliteKeyStore;
+use jwk_kit::keystore::{KeyAlgorithm, KeyState, KeyStore};
+use rusqlite::Connection;
+use common::TempDir;
+
+#[test]
+fn test_save_and_reload_store() {
+    let dir = TempDir::create("reload");
+    let path = dir.join("app.db");
+
+    let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    let first = store.rotate_at(t0).unwrap().kid().to_string();
+    let second = store.rotate_at(t0 + Duration::from_secs(60)).unwrap().kid().to_string();
+    store.transition_at(&first, KeyState::Retired, t0 + Duration::from_secs(120)).unwrap();
+    SqliteKeyStore::open(&path).unwrap().save_all(&store).unwrap();
+
+    let db = SqliteKeyStore::open(&path).unwrap();
+    let reloaded = db.load_store(KeyAlgorithm::Es256).unwrap();
+    assert_eq!(reloaded.keys(), store.keys());
+    assert_eq!(reloaded.current_signing_key().unwrap().kid.as_deref(), Some(second.as_str()));
+    assert!(reloaded.current_signing_key().unwrap().is_private());
+    assert_eq!(db.load(&first).unwrap().expires_at, Some(t0 + Duration::from_secs(120)));
+    assert!(matches!(db.load("missing"), Err(JwkError::UnknownKeyId(_))));
+}
+
+#[test]
+fn test_history_records_state_changes() {
+    let mut db = SqliteKeyStore::open_in_memory().unwrap();
+    let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+    let first = db.rotate_at(KeyAlgorithm::Es256, t0).unwrap().kid().to_string();
+    db.rotate_at(KeyAlgorithm::Es256, t0 + Duration::from_secs(60)).unwrap();
+    db.update(KeyAlgorithm::Es256, |store| store.revoke(&first)).unwrap();
+    // Saving an unchanged key does not add to its history.
+    db.save(&db.load(&first).unwrap()).unwrap();
+    db.remove(&first).unwrap();
+
+    let history = db.history(&first).unwrap();
+    let states: Vec<_> = history.iter().map(|entry| (entry.from, entry.to)).collect();
+    assert_eq!(
+        states,
+        [
+            (None, KeyState::Active),
+            (Some(KeyState::Active), KeyState::Retiring),
+            (Some(KeyState::Retiring), KeyState::Revoked),
+        ]
+    );
+    assert_eq!(history[0].at, t0);
+    assert_eq!(history[1].at, t0 + Duration::from_secs(60));
+
+    // Changes made within an update are recorded at the time given to the key store.
+    let second = db.load_all().unwrap().pop().unwrap().kid().to_string();
+    let retired_at = t0 + Duration::from_secs(3600);
+    db.update(KeyAlgorithm::Es256, |store| store.transition_at(&second, KeyState::Retired, retired_at)).unwrap();
+    assert_eq!(db.history(&second).unwrap().last().unwrap().at, retired_at);
+}
+
+#[test]
+fn test_failed_update_is_rolled_back() {
+    let mut db = SqliteKeyStore::open_in_memory().unwrap();
+    let kid = db.rotate(KeyAlgorithm::Es256).unwrap().kid().to_string();
+
+    let result = db.update(KeyAlgorithm::Es256, |store| {
+        store.rotate()?;
+        store.transition(&kid, KeyState::Pending)
+    });
+    assert!(matches!(result, Err(JwkError::InvalidStateTransition(_))));
+
+    let keys = db.load_all().unwrap();
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0].state, KeyState::Active);
+    assert_eq!(db.history(&kid).unwrap().len(), 1);
+}
+
+#[test]
+fn test_shares_an_existing_database() {
+    let dir = TempDir::create("shared");
+    let path = dir.join("app.db");
+
+    let conn = Connection::open(&path).unwrap();
+    conn.execute_batch("CREATE TABLE users (id INTEGER PRIMARY KEY); PRAGMA user_version = 7;").unwrap();
+
+    let mut db = SqliteKeyStore::from_connection(conn).unwrap();
+    assert_eq!(db.schema_version().unwrap(), 2);
+    db.rotate(KeyAlgorithm::Es256).unwrap();
+
+    // Reopening does not migrate again, and leaves the application's schema alone.
+    let conn = SqliteKeyStore::open(&path).unwrap().into_connection();
+    let user_version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+    assert_eq!(user_version, 7);
+    conn.execute("INSERT INTO users (id) VALUES (1)", []).unwrap();
+
+    let newer = Connection::open(&path).unwrap();
+    newer.execute("INSERT INTO jwk_kit_migrations (version, applied_at) VALUES (99, 0)", []).unwrap();
+    assert!(matches!(SqliteKeyStore::from_connection(newer), Err(JwkError::KeyStoreDatabase(_))));
+}
+
+#[test]
+fn test_encrypted_store_hides_private_keys() {
+    let kek = JwkBuilder::new("oct")
+        .set_key_id("kek-1")
+        .set_algorithm("A256KW")
+        .set_key_operations(&[KeyOperation::WrapKey, KeyOperation::UnwrapKey])
+        .set_symmetric_key("AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8")
+        .build()
+        .unwrap();
+    let dir = TempDir::create("encrypted");
+    let path = dir.join("app.db");
+
+    let mut db = SqliteKeyStore::open_encrypted(&path, AesKeyWrapper::new(&kek).unwrap()).unwrap();
+    let key = db.rotate(KeyAlgorithm::Es256).unwrap();
+    let d = key.jwk.d.clone().unwrap();
+    assert_eq!(db.load(key.kid()).unwrap().jwk, key.jwk);
+
+    let conn = Connection::open(&path).unwrap();
+    let (jwk, sealed): (String, String) = conn
+        .query_row("SELECT jwk, sealed FROM jwk_kit_keys", [], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap();
+    assert!(!jwk.contains(&d) && !jwk.contains(r#""d""#));
+    assert!(!sealed.contains(&d));
+
+    // Without the KEK the keys cannot be read.
+    assert!(matches!(SqliteKeyStore::open(&path).unwrap().load_all(), Err(JwkError::KeyWrapError(_))));
+}
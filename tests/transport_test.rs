@@ -0,0 +1,127 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+use jwk_kit::client::transport::{AsyncHttpTransport, HttpRequest, HttpResponse, HttpTransport, MAX_DOCUMENT_SIZE, StubTransport};
+use jwk_kit::client::{AsyncJwksClient, JwksClient};
+use jwk_kit::error::JwkError;
+use jwk_kit::jwk::{Jwk, create_jwks};
+use common::{key, t0};
+
+const URL: &str = "https://auth.example.com/.well-known/jwks.json";
+
+fn ok(keys: &[&Jwk], cache_control: &str) -> HttpResponse {
+    let body = serde_json::to_vec(&create_jwks(keys.iter().map(|jwk| (*jwk).clone()).collect())).unwrap();
+    let mut response = HttpResponse::new(200, body);
+    response.set_header("Cache-Control", cache_control).set_header("ETag", "\"v1\"");
+    response
+}
+
+/// Answers like a `StubTransport`, after yielding to the runtime a few times.
+struct SlowTransport(StubTransport);
+
+impl AsyncHttpTransport for SlowTransport {
+    async fn get(&self, request: &HttpRequest) -> Result<HttpResponse, JwkError> {
+        for _ in 0..3 {
+            tokio::task::yield_now().await;
+        }
+        HttpTransport::get(&self.0, request)
+    }
+}
+
+#[test]
+fn test_blocking_client_on_stub() {
+    let transport = Arc::new(StubTransport::new());
+    transport.push_response(ok(&[&key("a")], "max-age=60"));
+    transport.push_response(HttpResponse::new(304, ""));
+    transport.push_error(JwkError::JwksFetch("connection refused".into()));
+    let client = JwksClient::with_transport(URL, transport.clone());
+
+    let jwks = client.keys_at(t0()).unwrap();
+    assert!(Arc::ptr_eq(&client.keys_at(t0() + Duration::from_secs(60)).unwrap(), &jwks));
+    // The stale keys are served when the server cannot be reached.
+    assert!(Arc::ptr_eq(&client.keys_at(t0() + Duration::from_secs(10 * 60 + 60)).unwrap(), &jwks));
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0].url, URL);
+    assert_eq!(requests[0].header("if-none-match"), None);
+    assert_eq!(requests[1].header("if-none-match"), Some("\"v1\""));
+}
+
+#[test]
+fn test_closure_transport() {
+    let (a, b) = (key("a"), key("b"));
+    let transport = move |request: &HttpRequest| {
+        assert!(request.header("Accept").unwrap().contains("application/jwk-set+json"));
+        Ok(ok(&[&a, &b], "max-age=3600"))
+    };
+    let client = JwksClient::with_transport(URL, Arc::new(transport));
+
+    assert_eq!(client.find_key_at("b", t0()).unwrap().kid.as_deref(), Some("b"));
+    assert!(matches!(client.find_key_at("c", t0()), Err(JwkError::UnknownKeyId(_))));
+
+    let failing = JwksClient::with_transport(URL, Arc::new(|_: &HttpRequest| Ok(HttpResponse::new(503, ""))));
+    assert!(matches!(failing.keys_at(t0()), Err(JwkError::JwksFetch(_))));
+}
+
+#[tokio::test]
+async fn test_async_client_downloads_once() {
+    let stub = StubTransport::new();
+    stub.push_response(ok(&[&key("a")], "max-age=60"));
+    stub.push_response(ok(&[&key("a"), &key("b")], "max-age=60"));
+    let transport = Arc::new(SlowTransport(stub));
+    let mut client = AsyncJwksClient::with_transport(URL, transport.clone());
+    client.set_min_refresh_interval(Duration::from_secs(5));
+
+    // Concurrent callers share a single download.
+    let (first, second, third) = tokio::join!(client.keys_at(t0()), client.keys_at(t0()), client.find_key_at("a", t0()));
+    assert!(Arc::ptr_eq(&first.unwrap(), &second.unwrap()));
+    assert!(third.is_ok());
+    assert_eq!(transport.0.requests().len(), 1);
+
+    // An unknown kid triggers a download once the minimum refresh interval has passed.
+    assert!(matches!(client.find_key_at("b", t0() + Duration::from_secs(1)).await, Err(JwkError::UnknownKeyId(_))));
+    assert!(client.find_key_at("b", t0() + Duration::from_secs(5)).await.is_ok());
+    assert_eq!(transport.0.requests().len(), 2);
+}
+
+#[test]
+fn test_unbounded_cache_durations() {
+    let transport = Arc::new(StubTransport::new());
+    transport.push_response(ok(&[&key("a")], &format!("max-age={}", u64::MAX)));
+    transport.push_response(ok(&[&key("a")], "max-age=60"));
+    let mut client = JwksClient::with_transport(URL, transport.clone());
+    client.set_max_age_limit(Duration::MAX).set_max_stale(Duration::MAX);
+
+    // A max-age too long to represent never expires.
+    let jwks = client.keys_at(t0()).unwrap();
+    assert!(Arc::ptr_eq(&client.keys_at(t0() + Duration::from_secs(100 * 365 * 24 * 3600)).unwrap(), &jwks));
+    assert_eq!(transport.requests().len(), 1);
+
+    // Expired keys are served stale for as long as the server cannot be reached.
+    client.refresh_at(t0()).unwrap();
+    assert!(client.refresh_at(t0() + Duration::from_secs(3600)).is_err());
+    assert!(client.keys_at(t0() + Duration::from_secs(100 * 365 * 24 * 3600)).is_ok());
+}
+
+#[tokio::test]
+async fn test_oversized_documents_are_rejected() {
+    // A valid JWKS padded with whitespace past the limit.
+    let mut oversized = ok(&[&key("a")], "max-age=60");
+    oversized.body.resize(MAX_DOCUMENT_SIZE + 1, b' ');
+    let mut limit = ok(&[&key("a")], "max-age=60");
+    limit.body.resize(MAX_DOCUMENT_SIZE, b' ');
+
+    let transport = Arc::new(StubTransport::new());
+    transport.push_response(oversized.clone());
+    transport.push_response(limit);
+    let client = JwksClient::with_transport(URL, transport);
+    assert!(matches!(client.refresh_at(t0()), Err(JwkError::JwksFetch(_))));
+    assert_eq!(client.refresh_at(t0()).unwrap().keys.len(), 1);
+
+    let transport = Arc::new(StubTransport::new());
+    transport.push_response(oversized);
+    let client = AsyncJwksClient::with_transport(URL, transport);
+    assert!(matches!(client.refresh_at(t0()).await, Err(JwkError::JwksFetch(_))));
+}
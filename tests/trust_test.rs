@@ -0,0 +1,114 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jwk_kit::client::JwksClient;
+use jwk_kit::client::transport::{HttpResponse, StubTransport};
+use jwk_kit::error::JwkError;
+use jwk_kit::jwk::{Jwk, create_jwks};
+use jwk_kit::trust::{TrustStore, TrustedIssuer};
+use serde_json::{Value, json};
+use common::{key, t0};
+
+const IDP_A: &str = "https://a.example.com";
+const IDP_B: &str = "https://b.example.com";
+
+fn token(header: Value, claims: Value) -> String {
+    let encode = |value: Value| URL_SAFE_NO_PAD.encode(serde_json::to_vec(&value).unwrap());
+    format!("{}.{}.c2ln", encode(header), encode(claims))
+}
+
+fn es256(kid: &str, iss: &str) -> String {
+    token(json!({"alg": "ES256", "kid": kid}), json!({"iss": iss}))
+}
+
+#[test]
+fn test_resolves_key_per_issuer() {
+    let transport = Arc::new(StubTransport::new());
+    let body = |keys: Vec<Jwk>| serde_json::to_vec(&create_jwks(keys)).unwrap();
+    transport.push_response(HttpResponse::new(200, body(vec![key("b1")])));
+    transport.push_response(HttpResponse::new(200, body(vec![key("b1"), key("b2")])));
+    let mut client = JwksClient::with_transport("https://b.example.com/jwks", transport.clone());
+    client.set_min_refresh_interval(Duration::ZERO);
+
+    let mut store = TrustStore::new();
+    store
+        .add_issuer(TrustedIssuer::new(IDP_A, create_jwks(vec![key("shared")])))
+        .add_issuer(TrustedIssuer::new(IDP_B, client));
+
+    let resolved = store.resolve_at(&es256("shared", IDP_A), t0()).unwrap();
+    assert_eq!(resolved.issuer, IDP_A);
+    assert_eq!(resolved.header.kid.as_deref(), Some("shared"));
+    // A kid of one issuer is never looked up in the keys of another.
+    assert!(matches!(store.resolve_at(&es256("shared", IDP_B), t0()), Err(JwkError::UnknownKeyId(_))));
+    // A key published by a rotation is fetched on first use.
+    assert_eq!(store.resolve_at(&es256("b2", IDP_B), t0()).unwrap().key.kid.as_deref(), Some("b2"));
+    assert_eq!(transport.requests().len(), 2);
+
+    let untrusted = store.resolve_at(&es256("shared", "https://evil.example.com"), t0());
+    assert_eq!(untrusted, Err(JwkError::UntrustedIssuer("https://evil.example.com".into())));
+    assert_eq!(store.issuers().count(), 2);
+    assert!(store.remove_issuer(IDP_B).is_some());
+}
+
+#[test]
+fn test_algorithm_policy() {
+    let mut encryption = key("enc");
+    encryption.use_ = Some("enc".to_string());
+    let mut tagged = key("tagged");
+    tagged.alg = Some("ES256".to_string());
+
+    let mut rsa_only = TrustedIssuer::new(IDP_B, create_jwks(vec![key("b")]));
+    rsa_only.set_algorithms(&["RS256"]);
+    let mut store = TrustStore::new();
+    store
+        .add_issuer(TrustedIssuer::new(IDP_A, create_jwks(vec![key("a"), encryption, tagged])))
+        .add_issuer(rsa_only);
+
+    let resolve = |header: Value, iss: &str| store.resolve_at(&token(header, json!({"iss": iss})), t0());
+    for header in [
+        json!({"alg": "HS256", "kid": "a"}),
+        json!({"alg": "none", "kid": "a"}),
+        json!({"alg": "RS256", "kid": "a"}),
+        json!({"alg": "ES256", "kid": "enc"}),
+    ] {
+        assert!(matches!(resolve(header.clone(), IDP_A), Err(JwkError::UnsupportedAlgorithm(_))), "{}", header);
+    }
+    assert!(resolve(json!({"alg": "ES256", "kid": "tagged"}), IDP_A).is_ok());
+    assert!(matches!(resolve(json!({"alg": "ES256", "kid": "b"}), IDP_B), Err(JwkError::UnsupportedAlgorithm(_))));
+}
+
+#[test]
+fn test_audience_expectations() {
+    let mut issuer = TrustedIssuer::new(IDP_A, create_jwks(vec![key("a")]));
+    issuer.set_audiences(&["orders", "billing"]);
+    let mut store = TrustStore::new();
+    store.add_issuer(issuer).add_issuer(TrustedIssuer::new(IDP_B, create_jwks(vec![key("b")])));
+
+    let header = json!({"alg": "ES256", "kid": "a"});
+    let resolve = |claims: Value| store.resolve_at(&token(header.clone(), claims), t0());
+    assert!(resolve(json!({"iss": IDP_A, "aud": "billing"})).is_ok());
+    assert!(resolve(json!({"iss": IDP_A, "aud": ["other", "orders"]})).is_ok());
+    assert!(matches!(resolve(json!({"iss": IDP_A, "aud": ["other"]})), Err(JwkError::AudienceMismatch(_))));
+    assert!(matches!(resolve(json!({"iss": IDP_A})), Err(JwkError::AudienceMismatch(_))));
+
+    // Issuers without expected audiences accept any.
+    assert!(store.resolve_at(&token(json!({"alg": "ES256", "kid": "b"}), json!({"iss": IDP_B})), t0()).is_ok());
+}
+
+#[test]
+fn test_tokens_without_kid_and_malformed_tokens() {
+    let mut store = TrustStore::new();
+    store
+        .add_issuer(TrustedIssuer::new(IDP_A, create_jwks(vec![key("a")])))
+        .add_issuer(TrustedIssuer::new(IDP_B, create_jwks(vec![key("b1"), key("b2")])));
+
+    let no_kid = |iss: &str| token(json!({"alg": "ES256"}), json!({"iss": iss}));
+    assert_eq!(store.resolve_at(&no_kid(IDP_A), t0()).unwrap().key.kid.as_deref(), Some("a"));
+    assert_eq!(store.resolve_at(&no_kid(IDP_B), t0()), Err(JwkError::MissingKeyId));
+
+    for malformed in ["", "a.b", "a.b.c.d", "!!.e30.c2ln", &token(json!({"alg": "ES256"}), json!({"sub": "x"}))] {
+        assert!(matches!(store.resolve_at(malformed, t0()), Err(JwkError::InvalidToken(_))), "{}", malformed);
+    }
+}
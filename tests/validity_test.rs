@@ -0,0 +1,98 @@
+mod common;
+
+use std::time::Duration;
+use jwk_kit::error::JwkError;
+use jwk_kit::jwk::{create_jwks, Jwk, JwkBuilder};
+use jwk_kit::keystore::{KeyAlgorithm, KeyState, KeyStore};
+use common::t0;
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+fn key(kid: &str) -> JwkBuilder {
+    let mut builder = JwkBuilder::new("oct");
+    builder.set_key_id(kid).set_key_use("sig").set_symmetric_key("c2VjcmV0");
+    builder
+}
+
+#[test]
+fn test_validity_window() {
+    let jwk = key("a").set_issued_at(t0()).set_not_before(t0() + HOUR).set_expires_at(t0() + 2 * HOUR).build().unwrap();
+    assert_eq!(jwk.issued_at(), Some(t0()));
+    assert!(!jwk.is_valid_at(t0()));
+    assert!(jwk.is_valid_at(t0() + HOUR));
+    assert!(!jwk.is_valid_at(t0() + 2 * HOUR));
+
+    let json = serde_json::to_value(&jwk).unwrap();
+    assert_eq!(json["nbf"], 1_700_003_600u64);
+    assert!(json.get("revoked").is_none());
+    // Keys without validity members do not serialize any.
+    assert!(serde_json::to_value(key("b").build().unwrap()).unwrap().get("exp").is_none());
+
+    let mut revoked = jwk.clone();
+    revoked.set_revoked(true);
+    assert!(!revoked.is_valid_at(t0() + HOUR));
+    revoked.set_revoked(false).set_not_before(None).set_expires_at(None);
+    assert!(revoked.is_valid_at(t0()));
+}
+
+#[test]
+fn test_invalid_members_are_rejected() {
+    let json = r#"{"kty":"oct","k":"c2VjcmV0","nbf":1700000000,"exp":1800000000,"revoked":false}"#;
+    assert!(Jwk::from_json_strict(json).unwrap().is_valid_at(t0()));
+
+    let bad_exp: Jwk = serde_json::from_str(r#"{"kty":"oct","k":"c2VjcmV0","exp":"tomorrow"}"#).unwrap();
+    assert!(matches!(bad_exp.validate(), Err(JwkError::InvalidValidity(_))));
+    assert!(!bad_exp.is_valid_at(t0()));
+
+    let bad_revoked = r#"{"kty":"oct","k":"c2VjcmV0","revoked":"yes"}"#;
+    assert!(matches!(Jwk::from_json_strict(bad_revoked), Err(JwkError::InvalidValidity(_))));
+
+    // An instant the platform cannot represent makes the key invalid instead of panicking.
+    let far_future = r#"{"kty":"oct","kid":"far","k":"c2VjcmV0","exp":18446744073709551615}"#;
+    assert!(matches!(Jwk::from_json_strict(far_future), Err(JwkError::InvalidValidity(_))));
+    let far_future: Jwk = serde_json::from_str(far_future).unwrap();
+    assert_eq!(far_future.expires_at(), None);
+    assert!(!far_future.is_valid_at(t0()));
+    let jwks = create_jwks(vec![far_future]);
+    assert!(jwks.find_by_kid_at("far", t0()).is_none());
+    assert_eq!(jwks.valid_at(t0()).count(), 0);
+}
+
+#[test]
+fn test_key_selection_at_instant() {
+    let jwks = create_jwks(vec![
+        key("current").set_expires_at(t0() + HOUR).build().unwrap(),
+        key("next").set_not_before(t0() + HOUR).build().unwrap(),
+        key("revoked").set_revoked(true).build().unwrap(),
+    ]);
+
+    let kids = |keys: Vec<&Jwk>| keys.into_iter().filter_map(|jwk| jwk.kid.clone()).collect::<Vec<_>>();
+    assert_eq!(kids(jwks.signing_keys_at(t0()).collect()), ["current"]);
+    assert_eq!(kids(jwks.valid_at(t0() + HOUR).collect()), ["next"]);
+    assert!(jwks.find_by_kid_at("next", t0()).is_none());
+    assert!(jwks.find_by_kid("next").is_some());
+    assert!(jwks.clone().index().find_by_kid_at("next", t0() + HOUR).is_some());
+    assert!(jwks.encryption_keys_at(t0()).next().is_none());
+}
+
+#[test]
+fn test_published_pending_key_is_not_valid_yet() {
+    let mut store = KeyStore::new(KeyAlgorithm::Es256);
+    let active = store.rotate_at(t0()).unwrap().kid().to_string();
+    let pending = store.generate_at(KeyState::Pending, t0()).unwrap().kid().to_string();
+    store.schedule(&pending, Some(t0() + HOUR), None).unwrap();
+
+    let jwks = store.published_keys();
+    assert_eq!(jwks.keys.len(), 2);
+    assert_eq!(jwks.find_by_kid(&active).unwrap().not_before(), Some(t0()));
+    assert!(jwks.find_by_kid_at(&pending, t0()).is_none());
+    assert!(jwks.find_by_kid_at(&pending, t0() + HOUR).is_some());
+
+    // A pending key that was never scheduled is not published, so it cannot be used early.
+    let unscheduled = store.generate_at(KeyState::Pending, t0()).unwrap().kid().to_string();
+    let jwks = store.published_keys();
+    assert_eq!(jwks.keys.len(), 2);
+    assert!(jwks.find_by_kid(&unscheduled).is_none());
+    store.schedule(&unscheduled, Some(t0() + 2 * HOUR), None).unwrap();
+    assert!(store.published_keys().find_by_kid_at(&unscheduled, t0() + HOUR).is_none());
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use jwk_kit::error::JwkError;
+use jwk_kit::generator::ecdsa::generate_es256_keypair_pem;
+use jwk_kit::loader::load_dir;
+use jwk_kit::watch::JwksWatcher;
+use common::TempDir;
+
+#[test]
+fn test_load_dir_merges_key_pairs() {
+    let dir = TempDir::create("load");
+    let (private_pem, public_pem) = generate_es256_keypair_pem().unwrap();
+    fs::write(dir.join("signing.key"), &private_pem).unwrap();
+    fs::write(dir.join("signing.pub"), &public_pem).unwrap();
+    fs::write(dir.join(".hidden"), "not a key").unwrap();
+
+    let jwks = load_dir(&dir).unwrap();
+    assert_eq!(jwks.keys.len(), 1);
+    assert!(jwks.keys[0].is_private());
+    assert_eq!(jwks.keys[0].kid, Some(jwks.keys[0].thumbprint().unwrap()));
+
+    fs::write(dir.join("broken.pem"), "-----BEGIN PUBLIC KEY-----\nAAAA\n-----END PUBLIC KEY-----\n").unwrap();
+    match load_dir(&dir) {
+        Err(JwkError::InvalidKeyFile(path, _)) => assert!(path.ends_with("broken.pem")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let empty = TempDir::create("empty");
+    assert_eq!(load_dir(&empty), Err(JwkError::UnexpectedKeyCount(0)));
+}
+
+#[test]
+fn test_bad_files_keep_current_keys() {
+    let dir = TempDir::create("check");
+    fs::write(dir.join("a.pem"), generate_es256_keypair_pem().unwrap().1).unwrap();
+
+    let watcher = JwksWatcher::new(&dir).unwrap();
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let sink = errors.clone();
+    watcher.set_error_handler(move |error| sink.lock().unwrap().push(error.clone()));
+    let before = watcher.keys();
+    assert!(!watcher.check().unwrap());
+
+    fs::write(dir.join("b.pem"), generate_es256_keypair_pem().unwrap().1).unwrap();
+    assert!(watcher.check().unwrap());
+    assert_eq!(watcher.keys().keys.len(), 2);
+    // Snapshots taken before the swap are unaffected.
+    assert_eq!(before.keys.len(), 1);
+
+    fs::write(dir.join("c.pem"), "garbage").unwrap();
+    assert!(watcher.check().is_err());
+    assert!(!watcher.check().unwrap());
+    assert_eq!(errors.lock().unwrap().len(), 1);
+    assert_eq!(watcher.keys().keys.len(), 2);
+
+    fs::remove_file(dir.join("c.pem")).unwrap();
+    fs::remove_file(dir.join("a.pem")).unwrap();
+    assert!(watcher.check().unwrap());
+    assert_eq!(watcher.keys().keys.len(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_watch_picks_up_secret_volume_update() {
+    use std::os::unix::fs::symlink;
+
+    // The layout of a Kubernetes secret volume: files link through `..data`, which is
+    // atomically replaced on update.
+    let dir = TempDir::create("secret");
+    let write_version = |name: &str| {
+        let version = dir.join(name);
+        fs::create_dir(&version).unwrap();
+        fs::write(version.join("jwt.key"), generate_es256_keypair_pem().unwrap().0).unwrap();
+        symlink(name, dir.join("..data_tmp")).unwrap();
+        fs::rename(dir.join("..data_tmp"), dir.join("..data")).unwrap();
+    };
+    write_version("..v1");
+    symlink("..data/jwt.key", dir.join("jwt.key")).unwrap();
+
+    let watcher = JwksWatcher::new(&dir).unwrap();
+    let first = watcher.keys().keys[0].kid.clone();
+    let handle = watcher.watch(Duration::from_millis(10));
+
+    write_version("..v2");
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while watcher.keys().keys[0].kid == first {
+        assert!(Instant::now() < deadline, "the watcher did not reload the keys");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(watcher.keys().keys.len(), 1);
+
+    drop(handle);
+}
@@ -0,0 +1,124 @@
+use std::fs;
+use jwk_kit::error::JwkError;
+use jwk_kit::jwk::Jwk;
+use jwk_kit::loader::load_key;
+use jwk_kit::webcrypto::{from_webcrypto_jwk, to_webcrypto_jwk, WebCryptoAlgorithm, WebCryptoHash};
+use serde_json::{json, Value};
+
+// The fixtures in examples/webcrypto were captured from `crypto.subtle.exportKey("jwk", ...)`.
+fn fixture(name: &str) -> String {
+    fs::read_to_string(format!("./examples/webcrypto/{}", name)).unwrap()
+}
+
+#[test]
+fn test_import_browser_exports() {
+    let cases = [
+        ("rsassa-private.json", WebCryptoAlgorithm::RsassaPkcs1V15(WebCryptoHash::Sha256), true),
+        ("rsassa-public.json", WebCryptoAlgorithm::RsassaPkcs1V15(WebCryptoHash::Sha256), false),
+        ("rsa-pss-public.json", WebCryptoAlgorithm::RsaPss(WebCryptoHash::Sha384), false),
+        ("rsa-oaep-public.json", WebCryptoAlgorithm::RsaOaep(WebCryptoHash::Sha256), false),
+        ("ecdsa-private.json", WebCryptoAlgorithm::Ecdsa, true),
+        ("ecdsa-public.json", WebCryptoAlgorithm::Ecdsa, false),
+        ("ecdh-public.json", WebCryptoAlgorithm::Ecdh, false),
+    ];
+
+    for (name, expected, private) in cases {
+        let (jwk, algorithm) = from_webcrypto_jwk(&fixture(name)).unwrap();
+        assert_eq!(algorithm, expected, "{}", name);
+        assert_eq!(jwk.is_private(), private, "{}", name);
+        assert_eq!(jwk.extra_bool("ext"), Some(true), "{}", name);
+
+        // Re-exporting reproduces the browser's JWK member for member.
+        let exported = to_webcrypto_jwk(&jwk, algorithm, true).unwrap();
+        let original: Value = serde_json::from_str(&fixture(name)).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&exported).unwrap(), original, "{}", name);
+    }
+}
+
+#[test]
+fn test_browser_keys_are_usable() {
+    let (private, _) = from_webcrypto_jwk(&fixture("ecdsa-private.json")).unwrap();
+    let (public, _) = from_webcrypto_jwk(&fixture("ecdsa-public.json")).unwrap();
+    assert_eq!(private.thumbprint().unwrap(), public.thumbprint().unwrap());
+
+    let (rsa_private, _) = from_webcrypto_jwk(&fixture("rsassa-private.json")).unwrap();
+    let issued = jwk_kit::x509::SelfSignedCertificateBuilder::new("CN=browser")
+        .build_with_jwk(&rsa_private)
+        .unwrap();
+    assert_eq!(issued.jwk.n, rsa_private.n);
+}
+
+#[test]
+fn test_export_generated_keys() {
+    let private = load_key(&fs::read("./examples/rsa/rsa-legacy-private.pem").unwrap()).unwrap();
+    let json: Value = serde_json::from_str(
+        &to_webcrypto_jwk(&private, WebCryptoAlgorithm::RsaPss(WebCryptoHash::Sha256), false).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(json["alg"], json!("PS256"));
+    assert_eq!(json["key_ops"], json!(["sign"]));
+    assert_eq!(json["ext"], json!(false));
+    assert!(json.get("use").is_none());
+
+    // Browsers only import RSA private keys with their CRT members, recovered here from `d`.
+    let mut without_crt = private.clone();
+    (without_crt.p, without_crt.q, without_crt.dp, without_crt.dq, without_crt.qi) = (None, None, None, None, None);
+    let json: Value = serde_json::from_str(
+        &to_webcrypto_jwk(&without_crt, WebCryptoAlgorithm::RsaPss(WebCryptoHash::Sha256), false).unwrap(),
+    )
+    .unwrap();
+    for member in ["p", "q", "dp", "dq", "qi"] {
+        assert!(json[member].is_string(), "{}", member);
+        assert_eq!(json[member].as_str(), serde_json::to_value(&private).unwrap()[member].as_str(), "{}", member);
+    }
+
+    let public = private.to_public();
+    let json: Value = serde_json::from_str(
+        &to_webcrypto_jwk(&public, WebCryptoAlgorithm::RsaOaep(WebCryptoHash::Sha1), true).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(json["alg"], json!("RSA-OAEP"));
+    assert_eq!(json["key_ops"], json!(["encrypt", "wrapKey"]));
+
+    let ec = load_key(&fs::read("./examples/ecdsa/ecdsa-legacy-private.pem").unwrap()).unwrap();
+    assert!(matches!(
+        to_webcrypto_jwk(&ec, WebCryptoAlgorithm::RsaPss(WebCryptoHash::Sha256), true),
+        Err(JwkError::UnsupportedAlgorithm(_))
+    ));
+
+    let mut signing_only = ec;
+    signing_only.use_ = Some("sig".into());
+    assert_eq!(
+        to_webcrypto_jwk(&signing_only, WebCryptoAlgorithm::Ecdh, true),
+        Err(JwkError::OperationNotPermitted("deriveKey".into()))
+    );
+}
+
+#[test]
+fn test_import_rejects_what_webcrypto_rejects() {
+    let with = |name: &str, member: &str, value: Value| {
+        let mut json: Value = serde_json::from_str(&fixture(name)).unwrap();
+        json[member] = value;
+        json.to_string()
+    };
+
+    // `use` must agree with the algorithm.
+    let result = from_webcrypto_jwk(&with("rsa-oaep-public.json", "use", json!("sig")));
+    assert!(matches!(result, Err(JwkError::InvalidKeyOps(_))));
+
+    // A public key cannot be imported for signing.
+    let result = from_webcrypto_jwk(&with("rsassa-public.json", "key_ops", json!(["sign"])));
+    assert!(matches!(result, Err(JwkError::InvalidKeyOps(_))));
+
+    // RSA keys need a WebCrypto `alg`, and `ext` must be a boolean.
+    let result = from_webcrypto_jwk(&with("rsassa-public.json", "alg", json!("RSA1_5")));
+    assert!(matches!(result, Err(JwkError::UnsupportedAlgorithm(_))));
+    let result = from_webcrypto_jwk(&with("ecdsa-public.json", "ext", json!("yes")));
+    assert!(matches!(result, Err(JwkError::InvalidJson(_))));
+
+    let result = from_webcrypto_jwk(&with("ecdsa-public.json", "alg", json!("ES384")));
+    assert!(matches!(result, Err(JwkError::UnsupportedAlgorithm(_))));
+
+    let jwk: Jwk = serde_json::from_str(&fixture("ecdh-public.json")).unwrap();
+    assert_eq!(jwk.key_ops, Some(vec![]));
+}
@@ -0,0 +1,116 @@
+use std::fs;
+use std::time::{Duration, UNIX_EPOCH};
+use jwk_kit::error::JwkError;
+use jwk_kit::jwk::{JwkBuilder, Jwks};
+use jwk_kit::loader::{load_key, load_keys};
+use jwk_kit::x509::{check_x509_consistency, jwk_from_certificate, CertificateKeyUsage, SelfSignedCertificateBuilder};
+use x509_cert::der::DecodePem;
+use x509_cert::ext::pkix::{KeyUsage, SubjectAltName};
+use x509_cert::Certificate;
+
+fn read(path: &str) -> Vec<u8> {
+    fs::read(path).unwrap_or_else(|_| panic!("Missing test file: {}", path))
+}
+
+#[test]
+fn test_jwk_from_certificate_matches_key() {
+    let cert_jwk = jwk_from_certificate(&read("./examples/x509/rsa-legacy-cert.pem")).unwrap();
+    let key_jwk = load_key(&read("./examples/rsa/rsa-legacy-public.pem")).unwrap();
+
+    assert_eq!(cert_jwk.n, key_jwk.n);
+    assert_eq!(cert_jwk.e, key_jwk.e);
+    assert_eq!(cert_jwk.x5c.as_ref().map(Vec::len), Some(1));
+    assert!(cert_jwk.x5t.is_some() && cert_jwk.x5t_s256.is_some());
+    assert!(check_x509_consistency(&cert_jwk).is_ok());
+}
+
+#[test]
+fn test_certificate_chain_loads_as_single_key() {
+    let keys = load_keys(&read("./examples/x509/ecdsa-legacy-chain.pem")).unwrap();
+    assert_eq!(keys.len(), 1);
+
+    let key_jwk = load_key(&read("./examples/ecdsa/ecdsa-legacy-public.pem")).unwrap();
+    assert_eq!(keys[0].x, key_jwk.x);
+    assert_eq!(keys[0].y, key_jwk.y);
+    assert_eq!(keys[0].x5c.as_ref().map(Vec::len), Some(2));
+}
+
+#[test]
+fn test_x509_members_round_trip_through_json() {
+    let cert_jwk = jwk_from_certificate(&read("./examples/x509/ecdsa-legacy-cert.pem")).unwrap();
+    let json = format!(r#"{{"keys":[{}]}}"#, serde_json::to_string(&cert_jwk).unwrap());
+    assert!(json.contains("\"x5t#S256\""));
+
+    let jwks: Jwks = serde_json::from_str(&json).unwrap();
+    assert_eq!(jwks.keys[0], cert_jwk);
+}
+
+#[test]
+fn test_mismatched_x5c_is_rejected() {
+    let cert_jwk = jwk_from_certificate(&read("./examples/x509/rsa-legacy-cert.pem")).unwrap();
+    let other = load_key(&read("./examples/rsa/rsa-access-public.pem")).unwrap();
+
+    let result = JwkBuilder::new("RSA")
+        .set_modulus(other.n.as_deref().unwrap())
+        .set_exponent(other.e.as_deref().unwrap())
+        .set_x509_chain(cert_jwk.x5c.as_deref().unwrap())
+        .build();
+    assert!(matches!(result, Err(JwkError::X509Mismatch(_))));
+
+    let mut tampered = cert_jwk.clone();
+    tampered.x5t = cert_jwk.x5t_s256.clone();
+    assert!(matches!(check_x509_consistency(&tampered), Err(JwkError::X509Mismatch(_))));
+}
+
+#[test]
+fn test_self_signed_certificate_for_rsa_key() {
+    let private_pem = fs::read_to_string("./examples/rsa/rsa-legacy-private.pem").unwrap();
+    let issued = SelfSignedCertificateBuilder::new("CN=jwks-signer,O=Example")
+        .add_dns_name("jwks-signer.internal")
+        .add_ip_address("10.0.0.7".parse().unwrap())
+        .set_key_usage(&[CertificateKeyUsage::DigitalSignature, CertificateKeyUsage::KeyEncipherment])
+        .build(&private_pem)
+        .unwrap();
+
+    let key_jwk = load_key(private_pem.as_bytes()).unwrap();
+    assert_eq!(issued.jwk.n, key_jwk.n);
+    assert!(issued.jwk.d.is_none());
+    assert!(check_x509_consistency(&issued.jwk).is_ok());
+
+    let cert = Certificate::from_pem(&issued.certificate_pem).unwrap();
+    assert_eq!(cert.tbs_certificate.subject.to_string(), "CN=jwks-signer,O=Example");
+    assert_eq!(cert.tbs_certificate.issuer, cert.tbs_certificate.subject);
+    assert!(cert.tbs_certificate.get::<SubjectAltName>().unwrap().is_some());
+    let (_, key_usage) = cert.tbs_certificate.get::<KeyUsage>().unwrap().unwrap();
+    assert!(key_usage.digital_signature() && key_usage.key_encipherment());
+}
+
+#[test]
+fn test_self_signed_certificate_validity() {
+    let private_pem = fs::read_to_string("./examples/ecdsa/ecdsa-legacy-private.pem").unwrap();
+    let not_before = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let not_after = not_before + Duration::from_secs(30 * 24 * 60 * 60);
+
+    let issued = SelfSignedCertificateBuilder::new("CN=ec-service")
+        .set_validity(not_before, not_after)
+        .build(&private_pem)
+        .unwrap();
+
+    let cert = Certificate::from_pem(&issued.certificate_pem).unwrap();
+    let validity = &cert.tbs_certificate.validity;
+    assert_eq!(validity.not_before.to_system_time(), not_before);
+    assert_eq!(validity.not_after.to_system_time(), not_after);
+    assert_eq!(issued.jwk.crv.as_deref(), Some("P-256"));
+
+    for days in [u64::MAX, u64::MAX / (24 * 60 * 60)] {
+        let result = SelfSignedCertificateBuilder::new("CN=ec-service").set_validity_days(days).build(&private_pem);
+        assert!(matches!(result, Err(JwkError::CertificateBuildError(_))), "{}", days);
+    }
+}
+
+#[test]
+fn test_self_signed_certificate_requires_private_key() {
+    let public_pem = fs::read_to_string("./examples/ecdsa/ecdsa-legacy-public.pem").unwrap();
+    let result = SelfSignedCertificateBuilder::new("CN=nope").build(&public_pem);
+    assert!(matches!(result, Err(JwkError::CertificateBuildError(_))));
+}